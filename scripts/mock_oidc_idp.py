@@ -0,0 +1,242 @@
+#!/usr/bin/env python3
+"""Minimal OpenID Connect provider for trying SSO locally. Python 3 stdlib only.
+
+Approves every authorization request at once (no login screen) and signs ID
+tokens with an RSA key generated at startup. Not for anything but local tests.
+
+  python3 scripts/mock_oidc_idp.py --port 9000 --email alice@example.com --groups sec-team,devs
+
+Backend settings to match:
+
+  OIDC_ISSUER=http://localhost:9000
+  OIDC_CLIENT_ID=securascan
+  OIDC_CLIENT_SECRET=dev-secret
+  OIDC_REDIRECT_URI=http://localhost:8080/api/auth/oidc/callback
+
+The authorization request may override the signed-in user with the query
+parameters `login_hint` (email), `mock_groups` (comma separated) and
+`mock_email_verified` (`true`/`false`), e.g. by editing the URL in the browser.
+"""
+
+import argparse
+import base64
+import hashlib
+import json
+import secrets
+import time
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+from urllib.parse import parse_qs, urlencode, urlparse
+
+# --- RSA (key generation and PKCS#1 v1.5 SHA-256 signatures) ---
+
+SMALL_PRIMES = [p for p in range(3, 2000, 2) if all(p % d for d in range(3, int(p**0.5) + 1, 2))]
+
+
+def is_probable_prime(n, rounds=40):
+    if any(n % p == 0 for p in SMALL_PRIMES):
+        return n in SMALL_PRIMES
+    d, r = n - 1, 0
+    while d % 2 == 0:
+        d //= 2
+        r += 1
+    for _ in range(rounds):
+        x = pow(secrets.randbelow(n - 3) + 2, d, n)
+        if x in (1, n - 1):
+            continue
+        for _ in range(r - 1):
+            x = pow(x, 2, n)
+            if x == n - 1:
+                break
+        else:
+            return False
+    return True
+
+
+def random_prime(bits):
+    while True:
+        candidate = secrets.randbits(bits) | (1 << (bits - 1)) | (1 << (bits - 2)) | 1
+        if is_probable_prime(candidate):
+            return candidate
+
+
+def generate_rsa_key(bits=2048, e=65537):
+    while True:
+        p, q = random_prime(bits // 2), random_prime(bits // 2)
+        phi = (p - 1) * (q - 1)
+        if p != q and phi % e:
+            n = p * q
+            return n, e, pow(e, -1, phi)
+
+
+SHA256_DIGEST_INFO = bytes.fromhex("3031300d060960864801650304020105000420")
+
+
+def rsa_sign(message, n, d):
+    k = (n.bit_length() + 7) // 8
+    t = SHA256_DIGEST_INFO + hashlib.sha256(message).digest()
+    em = b"\x00\x01" + b"\xff" * (k - len(t) - 3) + b"\x00" + t
+    return pow(int.from_bytes(em, "big"), d, n).to_bytes(k, "big")
+
+
+def b64url(data):
+    return base64.urlsafe_b64encode(data).rstrip(b"=").decode()
+
+
+def int_b64url(value):
+    return b64url(value.to_bytes((value.bit_length() + 7) // 8, "big"))
+
+
+# --- Provider ---
+
+
+class Provider:
+    def __init__(self, args):
+        self.args = args
+        self.issuer = args.issuer or f"http://localhost:{args.port}"
+        print("generating RSA key...", flush=True)
+        self.n, self.e, self.d = generate_rsa_key()
+        self.kid = secrets.token_hex(8)
+        self.codes = {}
+        self.access_tokens = {}
+
+    def user_claims(self, email, groups, email_verified):
+        return {
+            "sub": "mock|" + hashlib.sha1(email.lower().encode()).hexdigest()[:16],
+            "email": email,
+            "email_verified": email_verified,
+            "name": email.split("@")[0],
+            self.args.groups_claim: groups,
+        }
+
+    def id_token(self, claims, nonce):
+        now = int(time.time())
+        payload = dict(claims, iss=self.issuer, aud=self.args.client_id, iat=now, exp=now + 300)
+        if nonce:
+            payload["nonce"] = nonce
+        header = {"alg": "RS256", "typ": "JWT", "kid": self.kid}
+        signing_input = f"{b64url(json.dumps(header).encode())}.{b64url(json.dumps(payload).encode())}"
+        return f"{signing_input}.{b64url(rsa_sign(signing_input.encode(), self.n, self.d))}"
+
+
+class Handler(BaseHTTPRequestHandler):
+    provider: Provider
+
+    def send_json(self, status, body):
+        data = json.dumps(body).encode()
+        self.send_response(status)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(data)))
+        self.end_headers()
+        self.wfile.write(data)
+
+    def do_GET(self):
+        p = self.provider
+        url = urlparse(self.path)
+        query = {k: v[0] for k, v in parse_qs(url.query, keep_blank_values=True).items()}
+
+        if url.path == "/.well-known/openid-configuration":
+            return self.send_json(200, {
+                "issuer": p.issuer,
+                "authorization_endpoint": f"{p.issuer}/authorize",
+                "token_endpoint": f"{p.issuer}/token",
+                "userinfo_endpoint": f"{p.issuer}/userinfo",
+                "jwks_uri": f"{p.issuer}/jwks",
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["RS256"],
+                "code_challenge_methods_supported": ["S256"],
+            })
+
+        if url.path == "/jwks":
+            return self.send_json(200, {"keys": [{
+                "kty": "RSA", "use": "sig", "alg": "RS256", "kid": p.kid,
+                "n": int_b64url(p.n), "e": int_b64url(p.e),
+            }]})
+
+        if url.path == "/authorize":
+            if query.get("client_id") != p.args.client_id or "redirect_uri" not in query:
+                return self.send_json(400, {"error": "invalid_request"})
+            if query.get("code_challenge_method") != "S256" or "code_challenge" not in query:
+                return self.send_json(400, {"error": "invalid_request", "error_description": "PKCE S256 required"})
+            groups = query.get("mock_groups", p.args.groups)
+            claims = p.user_claims(
+                query.get("login_hint", p.args.email),
+                [g for g in groups.split(",") if g],
+                query.get("mock_email_verified", "true") != "false",
+            )
+            code = secrets.token_urlsafe(24)
+            p.codes[code] = {
+                "claims": claims,
+                "nonce": query.get("nonce"),
+                "redirect_uri": query["redirect_uri"],
+                "code_challenge": query["code_challenge"],
+                "expires": time.time() + 60,
+            }
+            location = query["redirect_uri"] + "?" + urlencode({"code": code, "state": query.get("state", "")})
+            self.send_response(302)
+            self.send_header("Location", location)
+            self.end_headers()
+            return
+
+        if url.path == "/userinfo":
+            token = self.headers.get("Authorization", "").removeprefix("Bearer ")
+            claims = p.access_tokens.get(token)
+            if not claims:
+                return self.send_json(401, {"error": "invalid_token"})
+            return self.send_json(200, claims)
+
+        self.send_json(404, {"error": "not_found"})
+
+    def do_POST(self):
+        p = self.provider
+        if urlparse(self.path).path != "/token":
+            return self.send_json(404, {"error": "not_found"})
+
+        length = int(self.headers.get("Content-Length", 0))
+        form = {k: v[0] for k, v in parse_qs(self.rfile.read(length).decode()).items()}
+
+        client_id, client_secret = form.get("client_id"), form.get("client_secret")
+        auth = self.headers.get("Authorization", "")
+        if auth.startswith("Basic "):
+            client_id, _, client_secret = base64.b64decode(auth[6:]).decode().partition(":")
+        if client_id != p.args.client_id or (p.args.client_secret and client_secret != p.args.client_secret):
+            return self.send_json(401, {"error": "invalid_client"})
+
+        grant = p.codes.pop(form.get("code", ""), None)
+        if form.get("grant_type") != "authorization_code" or not grant or grant["expires"] < time.time():
+            return self.send_json(400, {"error": "invalid_grant"})
+        if form.get("redirect_uri") != grant["redirect_uri"]:
+            return self.send_json(400, {"error": "invalid_grant", "error_description": "redirect_uri mismatch"})
+        challenge = b64url(hashlib.sha256(form.get("code_verifier", "").encode()).digest())
+        if challenge != grant["code_challenge"]:
+            return self.send_json(400, {"error": "invalid_grant", "error_description": "PKCE verification failed"})
+
+        access_token = secrets.token_urlsafe(24)
+        p.access_tokens[access_token] = grant["claims"]
+        self.send_json(200, {
+            "token_type": "Bearer",
+            "access_token": access_token,
+            "expires_in": 300,
+            "id_token": p.id_token(grant["claims"], grant["nonce"]),
+        })
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("--port", type=int, default=9000)
+    parser.add_argument("--issuer", help="issuer URL (default http://localhost:PORT)")
+    parser.add_argument("--client-id", default="securascan")
+    parser.add_argument("--client-secret", default="dev-secret", help="empty for a public client")
+    parser.add_argument("--email", default="alice@example.com")
+    parser.add_argument("--groups", default="", help="comma separated")
+    parser.add_argument("--groups-claim", default="groups")
+    args = parser.parse_args()
+
+    Handler.provider = Provider(args)
+    server = ThreadingHTTPServer(("127.0.0.1", args.port), Handler)
+    print(f"mock OIDC provider at {Handler.provider.issuer}", flush=True)
+    server.serve_forever()
+
+
+if __name__ == "__main__":
+    main()
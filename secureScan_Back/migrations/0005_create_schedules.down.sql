@@ -0,0 +1,3 @@
+ALTER TABLE scans DROP COLUMN IF EXISTS options;
+ALTER TABLE scans DROP COLUMN IF EXISTS schedule_id;
+DROP TABLE IF EXISTS schedules;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS targets (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  url TEXT NOT NULL UNIQUE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE scans ADD COLUMN IF NOT EXISTS target_id UUID REFERENCES targets(id) ON DELETE CASCADE;
+
+-- Backfill: one target per distinct scanned URL. A bare origin gets the
+-- trailing slash the backend's URL normalization adds.
+INSERT INTO targets (url)
+SELECT DISTINCT CASE WHEN url ~ '^[A-Za-z]+://[^/?#]+$' THEN url || '/' ELSE url END
+FROM scans
+ON CONFLICT (url) DO NOTHING;
+
+UPDATE scans s
+SET target_id = t.id
+FROM targets t
+WHERE s.target_id IS NULL
+  AND t.url = CASE WHEN s.url ~ '^[A-Za-z]+://[^/?#]+$' THEN s.url || '/' ELSE s.url END;
+
+CREATE INDEX IF NOT EXISTS idx_scans_target_id_created_at ON scans(target_id, created_at DESC);
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS suppressions (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  rule_id TEXT NOT NULL,
+  -- NULL applies the suppression to every target
+  target_id UUID REFERENCES targets(id) ON DELETE CASCADE,
+  -- Glob matched against the finding location; '*' matches any run of characters
+  location_pattern TEXT NOT NULL DEFAULT '*',
+  state TEXT NOT NULL CHECK (state IN ('false_positive','accepted_risk','wont_fix')),
+  reason TEXT NOT NULL,
+  author TEXT NOT NULL,
+  expires_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_suppressions_rule_id ON suppressions(rule_id);
+CREATE INDEX IF NOT EXISTS idx_suppressions_target_id ON suppressions(target_id);
@@ -0,0 +1,8 @@
+ALTER TABLE scans
+  DROP COLUMN IF EXISTS grade,
+  DROP COLUMN IF EXISTS risk_score,
+  DROP COLUMN IF EXISTS info_count,
+  DROP COLUMN IF EXISTS low_count,
+  DROP COLUMN IF EXISTS medium_count,
+  DROP COLUMN IF EXISTS high_count,
+  DROP COLUMN IF EXISTS critical_count;
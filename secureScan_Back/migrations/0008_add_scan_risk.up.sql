@@ -0,0 +1,12 @@
+ALTER TABLE scans
+  ADD COLUMN IF NOT EXISTS critical_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS high_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS medium_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS low_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS info_count INTEGER NOT NULL DEFAULT 0,
+  -- NULL until the scan has results; filled in by the backend (see services::risk)
+  ADD COLUMN IF NOT EXISTS risk_score INTEGER,
+  ADD COLUMN IF NOT EXISTS grade TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_scans_risk_score ON scans(risk_score DESC NULLS LAST);
+CREATE INDEX IF NOT EXISTS idx_scans_grade ON scans(grade);
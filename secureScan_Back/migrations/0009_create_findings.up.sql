@@ -0,0 +1,104 @@
+CREATE TABLE IF NOT EXISTS findings (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  scan_id UUID NOT NULL REFERENCES scans(id) ON DELETE CASCADE,
+  scan_result_id UUID NOT NULL REFERENCES scan_results(id) ON DELETE CASCADE,
+  -- Order of the finding within its result
+  position INTEGER NOT NULL DEFAULT 0,
+  rule_id TEXT NOT NULL,
+  severity TEXT NOT NULL CHECK (severity IN ('info','low','medium','high','critical')),
+  title TEXT NOT NULL,
+  location TEXT NOT NULL,
+  evidence TEXT,
+  -- hex(sha256(rule_id || '\n' || location)): identity of the finding across scans
+  fingerprint TEXT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_findings_scan_id ON findings(scan_id);
+CREATE INDEX IF NOT EXISTS idx_findings_scan_result_id ON findings(scan_result_id, position);
+CREATE INDEX IF NOT EXISTS idx_findings_rule_id ON findings(rule_id);
+CREATE INDEX IF NOT EXISTS idx_findings_fingerprint ON findings(fingerprint);
+CREATE INDEX IF NOT EXISTS idx_findings_severity ON findings(severity);
+
+-- Backfill from scan_results.issues. Structured entries are copied as-is; plain
+-- strings from before findings were structured are mapped onto rule IDs the
+-- same way domain::findings::Finding::from_legacy does.
+WITH elems AS (
+  SELECT
+    r.id AS scan_result_id,
+    r.scan_id,
+    COALESCE(r.completed_at, NOW()) AS created_at,
+    s.url AS scan_url,
+    e.value AS elem,
+    (e.ordinality - 1)::int AS position
+  FROM scan_results r
+  JOIN scans s ON s.id = r.scan_id
+  CROSS JOIN LATERAL jsonb_array_elements(
+    CASE WHEN jsonb_typeof(r.issues) = 'array' THEN r.issues ELSE '[]'::jsonb END
+  ) WITH ORDINALITY AS e(value, ordinality)
+),
+legacy AS (
+  SELECT
+    scan_result_id, scan_id, created_at, position, scan_url AS location,
+    btrim(elem #>> '{}') AS text
+  FROM elems
+  WHERE jsonb_typeof(elem) = 'string'
+),
+legacy_mapped AS (
+  SELECT
+    scan_result_id, scan_id, created_at, position, location,
+    CASE
+      WHEN text LIKE 'Missing header: %' THEN 'missing-header-' || lower(btrim(substr(text, 17)))
+      WHEN text LIKE 'Found %HTML form%' THEN 'html-forms'
+      WHEN text LIKE 'Technology info: %' THEN 'technology-disclosure'
+      WHEN text LIKE 'Non-OK HTTP status: %' THEN 'http-error-status'
+      WHEN text LIKE 'Target is not using HTTPS%' THEN 'no-https'
+      ELSE 'unclassified'
+    END AS rule_id,
+    CASE
+      WHEN text LIKE 'Missing header: %' THEN 'Missing header: ' || lower(btrim(substr(text, 17)))
+      ELSE text
+    END AS title,
+    CASE
+      WHEN text LIKE 'Technology info: %' THEN substr(text, 18)
+      WHEN text LIKE 'Non-OK HTTP status: %' THEN substr(text, 21)
+    END AS evidence
+  FROM legacy
+),
+normalized AS (
+  SELECT
+    scan_result_id, scan_id, created_at, position, rule_id,
+    CASE
+      WHEN rule_id = 'no-https' THEN 'high'
+      WHEN rule_id IN ('missing-header-content-security-policy',
+                       'missing-header-strict-transport-security',
+                       'missing-header-x-frame-options') THEN 'medium'
+      WHEN rule_id IN ('missing-header-x-content-type-options',
+                       'missing-header-referrer-policy',
+                       'technology-disclosure',
+                       'http-error-status') THEN 'low'
+      ELSE 'info'
+    END AS severity,
+    title, location, evidence
+  FROM legacy_mapped
+  UNION ALL
+  SELECT
+    scan_result_id, scan_id, created_at, position,
+    elem->>'rule_id',
+    lower(elem->>'severity'),
+    elem->>'title',
+    elem->>'location',
+    elem->>'evidence'
+  FROM elems
+  WHERE jsonb_typeof(elem) = 'object'
+    AND elem ? 'rule_id' AND elem ? 'severity' AND elem ? 'title' AND elem ? 'location'
+    AND lower(elem->>'severity') IN ('info','low','medium','high','critical')
+)
+INSERT INTO findings
+  (scan_id, scan_result_id, position, rule_id, severity, title, location, evidence, fingerprint, created_at)
+SELECT
+  n.scan_id, n.scan_result_id, n.position, n.rule_id, n.severity, n.title, n.location, n.evidence,
+  encode(digest(n.rule_id || E'\n' || n.location, 'sha256'), 'hex'),
+  n.created_at
+FROM normalized n
+WHERE NOT EXISTS (SELECT 1 FROM findings f WHERE f.scan_result_id = n.scan_result_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_scans_target_status_created_at;
+DROP INDEX IF EXISTS idx_findings_evidence_trgm;
+DROP INDEX IF EXISTS idx_findings_title_trgm;
+DROP INDEX IF EXISTS idx_findings_created_at_id;
+DROP FUNCTION IF EXISTS glob_to_like(TEXT);
@@ -0,0 +1,14 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Translate a suppression location glob ('*' any run, '?' one character) into
+-- a LIKE pattern, escaping LIKE's own wildcards. Mirrors suppressions_repo::glob_match.
+CREATE OR REPLACE FUNCTION glob_to_like(pattern TEXT) RETURNS TEXT
+LANGUAGE SQL IMMUTABLE STRICT AS $$
+  SELECT replace(replace(replace(replace(replace(pattern,
+    '\', '\\'), '%', '\%'), '_', '\_'), '*', '%'), '?', '_')
+$$;
+
+CREATE INDEX IF NOT EXISTS idx_findings_created_at_id ON findings(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_findings_title_trgm ON findings USING GIN (title gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_findings_evidence_trgm ON findings USING GIN (evidence gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_scans_target_status_created_at ON scans(target_id, status, created_at DESC);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_scans_url_trgm;
+DROP INDEX IF EXISTS idx_scans_created_at_id;
+DROP INDEX IF EXISTS idx_scans_owner_created_at;
+ALTER TABLE scans DROP COLUMN IF EXISTS owner_id;
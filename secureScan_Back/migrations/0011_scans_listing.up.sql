@@ -0,0 +1,6 @@
+-- Owner of a scan; NULL for scans started anonymously or before ownership existed.
+ALTER TABLE scans ADD COLUMN IF NOT EXISTS owner_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_scans_owner_created_at ON scans(owner_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_scans_created_at_id ON scans(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_scans_url_trgm ON scans USING GIN (url gin_trgm_ops);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_schedules_owner_id;
+ALTER TABLE schedules DROP COLUMN IF EXISTS owner_id;
@@ -0,0 +1,3 @@
+-- Scans started by a schedule belong to the schedule's owner.
+ALTER TABLE schedules ADD COLUMN IF NOT EXISTS owner_id UUID REFERENCES users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS idx_schedules_owner_id ON schedules(owner_id);
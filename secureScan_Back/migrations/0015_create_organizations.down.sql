@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS idx_suppressions_org_id;
+DROP INDEX IF EXISTS idx_schedules_org_id;
+DROP INDEX IF EXISTS idx_scans_org_target_created_at;
+DROP INDEX IF EXISTS idx_scans_org_created_at;
+ALTER TABLE suppressions DROP COLUMN IF EXISTS org_id;
+ALTER TABLE schedules DROP COLUMN IF EXISTS org_id;
+ALTER TABLE scans DROP COLUMN IF EXISTS org_id;
+DROP TABLE IF EXISTS organization_invitations;
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
@@ -0,0 +1,60 @@
+CREATE TABLE IF NOT EXISTS organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS organization_members (
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('owner','admin','analyst','viewer')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (org_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_members_user_id ON organization_members(user_id, created_at);
+
+-- Invitations are accepted with a token sent to the invitee; only its SHA-256 is stored.
+CREATE TABLE IF NOT EXISTS organization_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner','admin','analyst','viewer')),
+    token_hash TEXT NOT NULL UNIQUE,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organization_invitations_org_id ON organization_invitations(org_id, created_at DESC);
+
+-- Scans and schedules belong to an organization; owner_id keeps who created them.
+ALTER TABLE scans ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+ALTER TABLE schedules ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+-- NULL keeps a suppression global (suppressions created before organizations existed).
+ALTER TABLE suppressions ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+
+-- Backfill: a personal organization for every existing user, owning their scans and schedules.
+ALTER TABLE organizations ADD COLUMN personal_of UUID;
+
+INSERT INTO organizations (name, personal_of)
+SELECT u.email, u.id FROM users u;
+
+INSERT INTO organization_members (org_id, user_id, role)
+SELECT o.id, o.personal_of, 'owner' FROM organizations o WHERE o.personal_of IS NOT NULL;
+
+UPDATE scans s SET org_id = o.id
+FROM organizations o
+WHERE o.personal_of = s.owner_id AND s.org_id IS NULL;
+
+UPDATE schedules sc SET org_id = o.id
+FROM organizations o
+WHERE o.personal_of = sc.owner_id AND sc.org_id IS NULL;
+
+ALTER TABLE organizations DROP COLUMN personal_of;
+
+CREATE INDEX IF NOT EXISTS idx_scans_org_created_at ON scans(org_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_scans_org_target_created_at ON scans(org_id, target_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_schedules_org_id ON schedules(org_id);
+CREATE INDEX IF NOT EXISTS idx_suppressions_org_id ON suppressions(org_id);
@@ -0,0 +1,16 @@
+-- Audit trail of password and second-factor attempts, also used to throttle
+-- logins per account and per client IP. Unknown emails are recorded too so
+-- they are throttled exactly like registered ones.
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email TEXT NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ip TEXT,
+    success BOOLEAN NOT NULL,
+    -- password | mfa | password_reset
+    method TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_attempts_email ON login_attempts(email, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_ip ON login_attempts(ip, created_at DESC) WHERE NOT success;
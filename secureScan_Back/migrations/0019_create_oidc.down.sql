@@ -0,0 +1,4 @@
+ALTER TABLE organization_members DROP COLUMN IF EXISTS sso_managed;
+DROP TABLE IF EXISTS oidc_group_mappings;
+DROP TABLE IF EXISTS oidc_login_states;
+DROP TABLE IF EXISTS user_identities;
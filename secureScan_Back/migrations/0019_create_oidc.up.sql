@@ -0,0 +1,41 @@
+-- Accounts signed in through an OpenID Connect provider, keyed by the
+-- provider's issuer and subject (the email of an IdP account may change).
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    email TEXT,
+    last_login_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (issuer, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user_id ON user_identities(user_id);
+
+-- Authorization requests in flight. `state` travels through the browser, so
+-- only its SHA-256 is stored; the PKCE verifier and nonce never leave the server.
+CREATE TABLE IF NOT EXISTS oidc_login_states (
+    state_hash TEXT PRIMARY KEY,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    redirect_to TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- IdP groups that grant a role in an organization on every SSO login.
+CREATE TABLE IF NOT EXISTS oidc_group_mappings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    group_name TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('admin','analyst','viewer')),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (org_id, group_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oidc_group_mappings_group_name ON oidc_group_mappings(group_name);
+
+-- Memberships granted by a group mapping follow the IdP groups; manual ones are left alone.
+ALTER TABLE organization_members ADD COLUMN IF NOT EXISTS sso_managed BOOLEAN NOT NULL DEFAULT FALSE;
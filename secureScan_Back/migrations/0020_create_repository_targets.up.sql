@@ -0,0 +1,37 @@
+-- Repositories whose webhook events start scans in an organization.
+CREATE TABLE IF NOT EXISTS repository_targets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Scans started by the mapping are attributed to its creator.
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    provider TEXT NOT NULL DEFAULT 'github',
+    -- `owner/name`, lower case.
+    repository TEXT NOT NULL,
+    -- Scanned on pushes and pull requests, and on deployments without a usable URL.
+    target_url TEXT,
+    events TEXT[] NOT NULL DEFAULT ARRAY['push','pull_request','deployment_status'],
+    -- Empty means every branch / environment.
+    branches TEXT[] NOT NULL DEFAULT '{}',
+    environments TEXT[] NOT NULL DEFAULT '{}',
+    -- Scan the URL a successful deployment reports (e.g. a preview) instead of `target_url`.
+    use_deployment_url BOOLEAN NOT NULL DEFAULT TRUE,
+    options JSONB NOT NULL DEFAULT '{}'::jsonb,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_repository_targets_repository ON repository_targets(provider, repository) WHERE enabled;
+
+-- Webhook deliveries already handled, so a redelivered event does not scan twice.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    provider TEXT NOT NULL,
+    delivery_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    repository TEXT,
+    scan_ids UUID[] NOT NULL DEFAULT '{}',
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, delivery_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_received_at ON webhook_deliveries(received_at);
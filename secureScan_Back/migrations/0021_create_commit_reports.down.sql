@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS commit_reports;
+ALTER TABLE repository_targets DROP COLUMN IF EXISTS fail_on_severity;
+ALTER TABLE repository_targets DROP COLUMN IF EXISTS report_to_provider;
@@ -0,0 +1,23 @@
+-- Whether scans of a repository target are reported back on the commit, and
+-- the least severe open finding that fails the report.
+ALTER TABLE repository_targets ADD COLUMN IF NOT EXISTS report_to_provider BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE repository_targets ADD COLUMN IF NOT EXISTS fail_on_severity TEXT NOT NULL DEFAULT 'high'
+    CHECK (fail_on_severity IN ('critical','high','medium','low','info'));
+
+-- The commit a webhook-triggered scan reports its result on.
+CREATE TABLE IF NOT EXISTS commit_reports (
+    scan_id UUID PRIMARY KEY REFERENCES scans(id) ON DELETE CASCADE,
+    repository_target_id UUID REFERENCES repository_targets(id) ON DELETE SET NULL,
+    provider TEXT NOT NULL,
+    repository TEXT NOT NULL,
+    commit_sha TEXT NOT NULL,
+    fail_on_severity TEXT NOT NULL,
+    -- `status` (commit status) or `check_run`.
+    kind TEXT NOT NULL,
+    check_run_id BIGINT,
+    -- pending, success, failure or error; NULL until first reported.
+    state TEXT,
+    last_error TEXT,
+    reported_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+ALTER TABLE repository_targets DROP CONSTRAINT IF EXISTS repository_targets_provider_check;
+DELETE FROM webhook_deliveries WHERE integration_id IS NOT NULL;
+DROP INDEX IF EXISTS idx_webhook_deliveries_unique;
+ALTER TABLE webhook_deliveries DROP COLUMN IF EXISTS integration_id;
+ALTER TABLE webhook_deliveries ADD PRIMARY KEY (provider, delivery_id);
+DROP TABLE IF EXISTS webhook_integrations;
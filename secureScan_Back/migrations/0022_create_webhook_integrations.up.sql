@@ -0,0 +1,27 @@
+-- Inbound webhook endpoints of an organization, one per source (a GitHub
+-- webhook, a GitLab project or group hook, a generic sender). The secret must
+-- be readable to verify signatures, so it is stored as is.
+CREATE TABLE IF NOT EXISTS webhook_integrations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL CHECK (provider IN ('github','gitlab','generic')),
+    name TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_delivery_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_integrations_org_id ON webhook_integrations(org_id);
+
+-- Delivery IDs are only unique per sender; generic senders choose their own.
+ALTER TABLE webhook_deliveries ADD COLUMN IF NOT EXISTS integration_id UUID REFERENCES webhook_integrations(id) ON DELETE CASCADE;
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS webhook_deliveries_pkey;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webhook_deliveries_unique
+    ON webhook_deliveries (provider, delivery_id, COALESCE(integration_id, '00000000-0000-0000-0000-000000000000'::uuid));
+
+ALTER TABLE repository_targets DROP CONSTRAINT IF EXISTS repository_targets_provider_check;
+ALTER TABLE repository_targets ADD CONSTRAINT repository_targets_provider_check
+    CHECK (provider IN ('github','gitlab','generic'));
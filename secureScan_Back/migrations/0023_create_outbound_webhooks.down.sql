@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS outbound_webhook_deliveries;
+DROP TABLE IF EXISTS outbound_webhooks;
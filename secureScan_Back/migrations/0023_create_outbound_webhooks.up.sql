@@ -0,0 +1,39 @@
+-- Endpoints of an organization that receive signed JSON deliveries on scan
+-- lifecycle events. The secret signs deliveries, so it is stored as is.
+CREATE TABLE IF NOT EXISTS outbound_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    description TEXT,
+    events TEXT[] NOT NULL,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_webhooks_org_id ON outbound_webhooks(org_id);
+
+-- One row per delivery of an event to an endpoint, retried with backoff until
+-- it succeeds or runs out of attempts. A manual redelivery is a new row with
+-- the same `event_id`.
+CREATE TABLE IF NOT EXISTS outbound_webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES outbound_webhooks(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending','succeeded','failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ,
+    last_status_code INT,
+    last_error TEXT,
+    last_response TEXT,
+    redelivery_of UUID REFERENCES outbound_webhook_deliveries(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_webhook_deliveries_webhook ON outbound_webhook_deliveries(webhook_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_outbound_webhook_deliveries_due ON outbound_webhook_deliveries(next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,25 @@
+-- Chat channels (Slack / Microsoft Teams incoming webhooks) that get a message
+-- when a scan of the organization matches their routing rules.
+CREATE TABLE IF NOT EXISTS notification_channels (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('slack','teams')),
+    name TEXT NOT NULL,
+    -- The incoming webhook URL is a credential; it is never returned by the API.
+    webhook_url TEXT NOT NULL,
+    -- Notify when a completed scan has open findings at or above this severity.
+    min_severity TEXT NOT NULL DEFAULT 'critical' CHECK (min_severity IN ('critical','high','medium','low','info')),
+    -- Only count findings that the target's previous completed scan did not have.
+    only_new BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Empty means every target of the organization.
+    target_ids UUID[] NOT NULL DEFAULT '{}',
+    notify_on_failure BOOLEAN NOT NULL DEFAULT FALSE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_sent_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_channels_org_id ON notification_channels(org_id);
@@ -0,0 +1,17 @@
+-- Pending second login steps. The client only holds the random challenge token;
+-- its hash is stored so each challenge is used once and allows a few attempts.
+CREATE TABLE IF NOT EXISTS mfa_challenges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mfa_challenges_user_id ON mfa_challenges(user_id);
+
+-- TOTP secrets are now stored encrypted ("v1:..."); rows written before are
+-- encrypted by the server at startup.
+COMMENT ON COLUMN user_mfa.totp_secret IS 'AES-256-GCM sealed with SECRETS_KEY';
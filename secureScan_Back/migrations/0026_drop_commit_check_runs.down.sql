@@ -0,0 +1,2 @@
+ALTER TABLE commit_reports ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT 'status';
+ALTER TABLE commit_reports ADD COLUMN IF NOT EXISTS check_run_id BIGINT;
@@ -0,0 +1,4 @@
+-- Results are only posted as commit statuses; check runs need GitHub App
+-- installation tokens, which are not supported.
+ALTER TABLE commit_reports DROP COLUMN IF EXISTS check_run_id;
+ALTER TABLE commit_reports DROP COLUMN IF EXISTS kind;
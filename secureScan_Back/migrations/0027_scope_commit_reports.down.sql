@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_commit_reports_commit;
+ALTER TABLE commit_reports DROP COLUMN IF EXISTS context;
+ALTER TABLE commit_reports DROP COLUMN IF EXISTS org_id;
+ALTER TABLE commit_reports DROP COLUMN IF EXISTS integration_id;
+ALTER TABLE webhook_integrations DROP COLUMN IF EXISTS report_token;
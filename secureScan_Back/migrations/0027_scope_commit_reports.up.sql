@@ -0,0 +1,14 @@
+-- A GitHub integration's own token for posting commit statuses, so an organization
+-- only reports on repositories its token can write to. Stored encrypted.
+ALTER TABLE webhook_integrations ADD COLUMN IF NOT EXISTS report_token TEXT;
+
+-- Who a commit report is posted for: the integration whose token is used (NULL
+-- for the legacy endpoint, which uses GITHUB_TOKEN) and the organization.
+ALTER TABLE commit_reports ADD COLUMN IF NOT EXISTS integration_id UUID REFERENCES webhook_integrations(id) ON DELETE CASCADE;
+ALTER TABLE commit_reports ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+-- Status context, named after the organization so organizations never overwrite each other.
+ALTER TABLE commit_reports ADD COLUMN IF NOT EXISTS context TEXT;
+
+UPDATE commit_reports c SET org_id = s.org_id FROM scans s WHERE s.id = c.scan_id AND c.org_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_commit_reports_commit ON commit_reports(provider, repository, commit_sha);
@@ -0,0 +1,2 @@
+ALTER TABLE webhook_deliveries DROP COLUMN IF EXISTS failed_at;
+ALTER TABLE webhook_deliveries DROP COLUMN IF EXISTS target_ids;
@@ -0,0 +1,5 @@
+-- Repository targets a delivery already started scans for, recorded as each scan
+-- is queued, and when its handling failed part way. A redelivery of a failed
+-- delivery resumes it and skips the targets already scanned.
+ALTER TABLE webhook_deliveries ADD COLUMN IF NOT EXISTS target_ids UUID[] NOT NULL DEFAULT '{}';
+ALTER TABLE webhook_deliveries ADD COLUMN IF NOT EXISTS failed_at TIMESTAMPTZ;
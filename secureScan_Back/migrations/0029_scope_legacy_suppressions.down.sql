@@ -0,0 +1 @@
+ALTER TABLE suppressions ALTER COLUMN org_id DROP NOT NULL;
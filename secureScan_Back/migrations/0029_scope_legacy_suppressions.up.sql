@@ -0,0 +1,14 @@
+-- Suppressions from before organizations existed have no org_id and hid findings
+-- in every organization. A suppression of one target moves to each organization
+-- that scanned that target; one without a target cannot be attributed and is dropped.
+INSERT INTO suppressions
+    (org_id, rule_id, target_id, location_pattern, state, reason, author, expires_at, created_at)
+SELECT DISTINCT s.org_id, sp.rule_id, sp.target_id, sp.location_pattern, sp.state, sp.reason,
+       sp.author, sp.expires_at, sp.created_at
+FROM suppressions sp
+JOIN scans s ON s.target_id = sp.target_id AND s.org_id IS NOT NULL
+WHERE sp.org_id IS NULL;
+
+DELETE FROM suppressions WHERE org_id IS NULL;
+
+ALTER TABLE suppressions ALTER COLUMN org_id SET NOT NULL;
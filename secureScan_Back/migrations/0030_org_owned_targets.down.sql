@@ -0,0 +1,3 @@
+-- Copies made for other organizations are kept; they cannot share a URL again.
+ALTER TABLE targets DROP CONSTRAINT IF EXISTS targets_org_id_url_key;
+ALTER TABLE targets DROP COLUMN IF EXISTS org_id;
@@ -0,0 +1,54 @@
+-- Targets belong to an organization, like scans, schedules and suppressions.
+-- A target several organizations scanned is split: the organization that
+-- scanned it first keeps the row, the others get a copy their scans,
+-- suppressions and notification channels are moved to.
+ALTER TABLE targets ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+ALTER TABLE targets DROP CONSTRAINT IF EXISTS targets_url_key;
+
+CREATE TEMP TABLE target_orgs AS
+SELECT s.target_id AS old_id, s.org_id, MIN(s.created_at) AS first_scan_at, NULL::uuid AS new_id
+FROM scans s
+WHERE s.target_id IS NOT NULL AND s.org_id IS NOT NULL
+GROUP BY s.target_id, s.org_id;
+
+UPDATE targets t
+SET org_id = first.org_id
+FROM (
+    SELECT DISTINCT ON (old_id) old_id, org_id
+    FROM target_orgs
+    ORDER BY old_id, first_scan_at, org_id
+) first
+WHERE t.id = first.old_id;
+
+UPDATE target_orgs o
+SET new_id = CASE WHEN t.org_id = o.org_id THEN o.old_id ELSE gen_random_uuid() END
+FROM targets t
+WHERE t.id = o.old_id;
+
+INSERT INTO targets (id, url, org_id, created_at)
+SELECT o.new_id, t.url, o.org_id, t.created_at
+FROM target_orgs o
+JOIN targets t ON t.id = o.old_id
+WHERE o.new_id <> o.old_id;
+
+UPDATE scans s
+SET target_id = o.new_id
+FROM target_orgs o
+WHERE s.target_id = o.old_id AND s.org_id = o.org_id AND o.new_id <> o.old_id;
+
+UPDATE suppressions sp
+SET target_id = o.new_id
+FROM target_orgs o
+WHERE sp.target_id = o.old_id AND sp.org_id = o.org_id AND o.new_id <> o.old_id;
+
+UPDATE notification_channels c
+SET target_ids = ARRAY(
+    SELECT COALESCE(o.new_id, id)
+    FROM unnest(c.target_ids) AS id
+    LEFT JOIN target_orgs o ON o.old_id = id AND o.org_id = c.org_id
+);
+
+DROP TABLE target_orgs;
+
+-- Targets only scanned outside any organization keep a NULL org_id.
+ALTER TABLE targets ADD CONSTRAINT targets_org_id_url_key UNIQUE NULLS NOT DISTINCT (org_id, url);
@@ -0,0 +1,11 @@
+-- Failed-login counters per account (`email:<address>`) and client IP
+-- (`ip:<address>`). An attempt is counted before the password is checked, in
+-- one statement that also refuses it while the key is throttled, so parallel
+-- requests cannot all pass the check before any failure is recorded.
+CREATE TABLE IF NOT EXISTS login_throttle (
+    key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL,
+    last_failure TIMESTAMPTZ NOT NULL,
+    -- earliest time the next attempt is accepted
+    allowed_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_suppressions_pending_expiry;
+ALTER TABLE suppressions DROP COLUMN IF EXISTS expiry_rescored_at;
@@ -0,0 +1,5 @@
+-- When the scans a suppression covered were re-scored after it expired, so
+-- their stored counts, risk score and grade count its findings again.
+ALTER TABLE suppressions ADD COLUMN IF NOT EXISTS expiry_rescored_at TIMESTAMPTZ;
+CREATE INDEX IF NOT EXISTS idx_suppressions_pending_expiry
+    ON suppressions(expires_at) WHERE expiry_rescored_at IS NULL AND expires_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Tie each authorization request to the browser that started it: the SHA-256
+-- of a random value kept in that browser's cookie. Requests in flight
+-- without one can no longer complete.
+DELETE FROM oidc_login_states;
+ALTER TABLE oidc_login_states ADD COLUMN IF NOT EXISTS browser_hash TEXT NOT NULL;
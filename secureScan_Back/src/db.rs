@@ -6,6 +6,15 @@ use sqlx::Postgres;
 
 pub type DbPool = Pool<Postgres>;
 
+/// Size of the connection pool, from `DATABASE_MAX_CONNECTIONS` (default 5).
+pub fn max_connections() -> u32 {
+    std::env::var("DATABASE_MAX_CONNECTIONS")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(5u32)
+        .max(2)
+}
+
 pub async fn init_pool() -> Result<DbPool> {
     // Load local .env during development if present
     let _ = dotenvy::dotenv();
@@ -13,7 +22,7 @@ pub async fn init_pool() -> Result<DbPool> {
     let url = std::env::var("DATABASE_URL").context("DATABASE_URL is not set")?;
 
     let pool = PgPoolOptions::new()
-        .max_connections(5)
+        .max_connections(max_connections())
         .connect(&url)
         .await
         .context("failed to connect to database")?;
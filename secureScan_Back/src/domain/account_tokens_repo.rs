@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// What a mailed account token can be redeemed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPurpose {
+    VerifyEmail,
+    ResetPassword,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::VerifyEmail => "verify_email",
+            TokenPurpose::ResetPassword => "reset_password",
+        }
+    }
+}
+
+/// A single-use token sent to a user by email. Only its hash is stored.
+#[derive(Debug, Clone, FromRow)]
+pub struct AccountTokenRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const COLUMNS: &str = "id, user_id, purpose, expires_at, used_at, created_at";
+
+pub async fn insert_token<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    purpose: TokenPurpose,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<AccountTokenRow, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let sql = format!(
+        r#"
+        INSERT INTO account_tokens (user_id, purpose, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, AccountTokenRow>(&sql)
+        .bind(user_id)
+        .bind(purpose.as_str())
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(executor)
+        .await
+}
+
+/// The unused, unexpired token with this hash and purpose, without redeeming it.
+pub async fn find_token<'e, E>(
+    executor: E,
+    purpose: TokenPurpose,
+    token_hash: &str,
+) -> Result<Option<AccountTokenRow>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let sql = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM account_tokens
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+        "#
+    );
+    sqlx::query_as::<_, AccountTokenRow>(&sql)
+        .bind(token_hash)
+        .bind(purpose.as_str())
+        .fetch_optional(executor)
+        .await
+}
+
+/// Mark the unused, unexpired token with this hash and purpose as used and
+/// return it. Returns `None` if there is no such token, so each token can be
+/// redeemed only once even under concurrent requests.
+pub async fn consume_token<'e, E>(
+    executor: E,
+    purpose: TokenPurpose,
+    token_hash: &str,
+) -> Result<Option<AccountTokenRow>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let sql = format!(
+        r#"
+        UPDATE account_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, AccountTokenRow>(&sql)
+        .bind(token_hash)
+        .bind(purpose.as_str())
+        .fetch_optional(executor)
+        .await
+}
+
+/// Invalidate every outstanding token of a user for `purpose`, e.g. older
+/// reset links once a new one is issued or the password was changed.
+pub async fn invalidate_tokens<'e, E>(executor: E, user_id: Uuid, purpose: TokenPurpose) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let res = sqlx::query(
+        "UPDATE account_tokens SET used_at = NOW() WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .execute(executor)
+    .await?;
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,130 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A personal API key as shown to its owner; the secret itself is never stored.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApiKeyRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// First characters of the key, for recognising it in listings.
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An active key resolved from a presented secret, with its owner's email.
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKeyAuthRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub scopes: Vec<String>,
+}
+
+const COLUMNS: &str = "id, user_id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at";
+
+pub async fn create_api_key(
+    pool: &DbPool,
+    user_id: Uuid,
+    name: &str,
+    prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ApiKeyRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, ApiKeyRow>(&sql)
+        .bind(user_id)
+        .bind(name)
+        .bind(prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await
+}
+
+/// Keys of a user, newest first, including revoked and expired ones.
+pub async fn list_api_keys(pool: &DbPool, user_id: Uuid) -> Result<Vec<ApiKeyRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC");
+    sqlx::query_as::<_, ApiKeyRow>(&sql)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Revoke a key of `user_id`. Returns the key, or `None` if it does not exist
+/// or belongs to someone else.
+pub async fn revoke_api_key(pool: &DbPool, id: Uuid, user_id: Uuid) -> Result<Option<ApiKeyRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE api_keys
+        SET revoked_at = COALESCE(revoked_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, ApiKeyRow>(&sql)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Revoke every key of `user_id`; returns how many were still active.
+pub async fn revoke_user_keys<'e, E>(executor: E, user_id: Uuid) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let res = sqlx::query("UPDATE api_keys SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+/// Resolve an unrevoked, unexpired key by hash and record that it was used.
+/// `last_used_at` is written at most once a minute per key.
+pub async fn authenticate(pool: &DbPool, key_hash: &str) -> Result<Option<ApiKeyAuthRow>, sqlx::Error> {
+    let row = sqlx::query_as::<_, ApiKeyAuthRow>(
+        r#"
+        SELECT k.id, k.user_id, u.email, k.scopes
+        FROM api_keys k
+        JOIN users u ON u.id = k.user_id
+        WHERE k.key_hash = $1
+          AND k.revoked_at IS NULL
+          AND (k.expires_at IS NULL OR k.expires_at > NOW())
+        "#,
+    )
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(key) = &row {
+        sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+            "#,
+        )
+        .bind(key.id)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(row)
+}
@@ -0,0 +1,123 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// The commit a webhook-triggered scan reports its result on.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CommitReportRow {
+    pub scan_id: Uuid,
+    pub repository_target_id: Option<Uuid>,
+    pub provider: String,
+    pub repository: String,
+    pub commit_sha: String,
+    pub fail_on_severity: String,
+    /// Integration whose token posts the report; `None` for the legacy endpoint.
+    pub integration_id: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    /// Commit status context; `None` for reports created before it was stored.
+    pub context: Option<String>,
+    pub state: Option<String>,
+    pub last_error: Option<String>,
+    pub reported_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fields of a commit report to create.
+#[derive(Debug, Clone)]
+pub struct NewCommitReport {
+    pub scan_id: Uuid,
+    pub repository_target_id: Uuid,
+    pub provider: String,
+    pub repository: String,
+    pub commit_sha: String,
+    pub fail_on_severity: String,
+    pub integration_id: Option<Uuid>,
+    pub org_id: Uuid,
+    pub context: String,
+}
+
+const COLUMNS: &str = "scan_id, repository_target_id, provider, repository, commit_sha, fail_on_severity, \
+    integration_id, org_id, context, state, last_error, reported_at, created_at";
+
+pub async fn create_commit_report(pool: &DbPool, new: &NewCommitReport) -> Result<CommitReportRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO commit_reports
+            (scan_id, repository_target_id, provider, repository, commit_sha, fail_on_severity,
+             integration_id, org_id, context)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, CommitReportRow>(&sql)
+        .bind(new.scan_id)
+        .bind(new.repository_target_id)
+        .bind(&new.provider)
+        .bind(&new.repository)
+        .bind(&new.commit_sha)
+        .bind(&new.fail_on_severity)
+        .bind(new.integration_id)
+        .bind(new.org_id)
+        .bind(&new.context)
+        .fetch_one(pool)
+        .await
+}
+
+/// Whether an organization other than `org_id` reports on this commit.
+pub async fn reported_by_other_org(
+    pool: &DbPool,
+    provider: &str,
+    repository: &str,
+    commit_sha: &str,
+    org_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM commit_reports
+            WHERE provider = $1 AND repository = $2 AND commit_sha = $3 AND org_id IS DISTINCT FROM $4
+        )
+        "#,
+    )
+    .bind(provider)
+    .bind(repository)
+    .bind(commit_sha)
+    .bind(org_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_commit_report(pool: &DbPool, scan_id: Uuid) -> Result<Option<CommitReportRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM commit_reports WHERE scan_id = $1");
+    sqlx::query_as::<_, CommitReportRow>(&sql)
+        .bind(scan_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Remember a state posted to the provider.
+pub async fn mark_reported(pool: &DbPool, scan_id: Uuid, state: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE commit_reports
+        SET state = $2, last_error = NULL, reported_at = NOW()
+        WHERE scan_id = $1
+        "#,
+    )
+    .bind(scan_id)
+    .bind(state)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_failed(pool: &DbPool, scan_id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE commit_reports SET last_error = $2 WHERE scan_id = $1")
+        .bind(scan_id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use uuid::Uuid;
+
+/// Opaque keyset-pagination cursor: the sort key of the last returned row plus
+/// its id as a tie-breaker. Encoded as hex so it is URL-safe without escaping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: String,
+    pub id: Uuid,
+}
+
+const SEPARATOR: char = '\u{1f}';
+
+impl Cursor {
+    pub fn new(key: impl Into<String>, id: Uuid) -> Self {
+        Self { key: key.into(), id }
+    }
+
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}{}{}", self.key, SEPARATOR, self.id))
+    }
+
+    pub fn decode(raw: &str) -> Option<Cursor> {
+        let bytes = hex::decode(raw.trim()).ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (key, id) = text.rsplit_once(SEPARATOR)?;
+        Some(Cursor {
+            key: key.to_string(),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
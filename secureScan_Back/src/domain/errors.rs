@@ -6,6 +6,8 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 #[derive(Debug)]
 pub enum ApiError {
     BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
     Internal(String),
 }
@@ -14,6 +16,8 @@ impl Display for ApiError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             ApiError::BadRequest(m) => write!(f, "{}", m),
+            ApiError::Unauthorized(m) => write!(f, "{}", m),
+            ApiError::Forbidden(m) => write!(f, "{}", m),
             ApiError::NotFound(m) => write!(f, "{}", m),
             ApiError::Internal(m) => write!(f, "{}", m),
         }
@@ -24,6 +28,8 @@ impl ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match self {
             ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
             ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::domain::suppressions_repo::SuppressionRow;
+
+/// Finding severity, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Severity> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "info" | "informational" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// Rule identifiers produced by the scanner.
+pub mod rules {
+    pub const MISSING_HEADER_PREFIX: &str = "missing-header-";
+    pub const HTML_FORMS: &str = "html-forms";
+    pub const TECHNOLOGY_DISCLOSURE: &str = "technology-disclosure";
+    pub const HTTP_ERROR_STATUS: &str = "http-error-status";
+    pub const NO_HTTPS: &str = "no-https";
+    /// Free-text finding that could not be mapped to a known rule.
+    pub const UNCLASSIFIED: &str = "unclassified";
+
+    /// Headers whose absence the scanner reports as `missing-header-<name>`.
+    pub const SECURITY_HEADERS: [&str; 6] = [
+        "content-security-policy",
+        "x-frame-options",
+        "x-xss-protection",
+        "strict-transport-security",
+        "x-content-type-options",
+        "referrer-policy",
+    ];
+
+    /// Every rule the scanner checks, in report order.
+    pub fn checks() -> Vec<String> {
+        let mut checks = vec![NO_HTTPS.to_string(), HTTP_ERROR_STATUS.to_string()];
+        checks.extend(SECURITY_HEADERS.iter().map(|h| format!("{}{}", MISSING_HEADER_PREFIX, h)));
+        checks.push(TECHNOLOGY_DISCLOSURE.to_string());
+        checks.push(HTML_FORMS.to_string());
+        checks
+    }
+}
+
+/// Default severity of a rule.
+pub fn severity_for_rule(rule_id: &str) -> Severity {
+    match rule_id {
+        rules::NO_HTTPS => Severity::High,
+        "missing-header-content-security-policy"
+        | "missing-header-strict-transport-security"
+        | "missing-header-x-frame-options" => Severity::Medium,
+        "missing-header-x-content-type-options" | "missing-header-referrer-policy" => Severity::Low,
+        rules::TECHNOLOGY_DISCLOSURE | rules::HTTP_ERROR_STATUS => Severity::Low,
+        _ => Severity::Info,
+    }
+}
+
+/// A single scanner finding, stored in `scan_results.issues`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub title: String,
+    /// Where the finding was observed (URL, header name, ...).
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+}
+
+impl Finding {
+    pub fn new(rule_id: impl Into<String>, title: impl Into<String>, location: impl Into<String>) -> Self {
+        let rule_id = rule_id.into();
+        Self {
+            severity: severity_for_rule(&rule_id),
+            rule_id,
+            title: title.into(),
+            location: location.into(),
+            evidence: None,
+        }
+    }
+
+    pub fn with_evidence(mut self, evidence: impl Into<String>) -> Self {
+        self.evidence = Some(evidence.into());
+        self
+    }
+
+    /// Identity of a finding across runs: same rule at the same location.
+    pub fn key(&self) -> (&str, &str) {
+        (&self.rule_id, &self.location)
+    }
+
+    /// Stable hash of [`Finding::key`], stored as `findings.fingerprint`.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.rule_id.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.location.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Map a free-text finding from before findings were structured onto a rule.
+    pub fn from_legacy(text: &str, location: &str) -> Self {
+        let text = text.trim();
+        if let Some(header) = text.strip_prefix("Missing header: ") {
+            let header = header.trim().to_ascii_lowercase();
+            return Finding::new(
+                format!("{}{}", rules::MISSING_HEADER_PREFIX, header),
+                format!("Missing header: {}", header),
+                location,
+            );
+        }
+        if text.starts_with("Found ") && text.contains("HTML form") {
+            return Finding::new(rules::HTML_FORMS, text, location);
+        }
+        if let Some(tech) = text.strip_prefix("Technology info: ") {
+            return Finding::new(rules::TECHNOLOGY_DISCLOSURE, text, location).with_evidence(tech);
+        }
+        if let Some(status) = text.strip_prefix("Non-OK HTTP status: ") {
+            return Finding::new(rules::HTTP_ERROR_STATUS, text, location).with_evidence(status);
+        }
+        if text.starts_with("Target is not using HTTPS") {
+            return Finding::new(rules::NO_HTTPS, text, location);
+        }
+        Finding::new(rules::UNCLASSIFIED, text, location)
+    }
+
+    /// Parse one element of an `issues` array, accepting both the structured
+    /// form and legacy plain strings.
+    pub fn from_json(value: &JsonValue, fallback_location: &str) -> Option<Self> {
+        match value {
+            JsonValue::String(s) => Some(Finding::from_legacy(s, fallback_location)),
+            JsonValue::Object(_) => serde_json::from_value(value.clone()).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `scan_results.issues` JSON array into findings.
+pub fn parse_issues(issues: Option<&JsonValue>, fallback_location: &str) -> Vec<Finding> {
+    issues
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| Finding::from_json(v, fallback_location))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Result of comparing the findings of two scans.
+#[derive(Debug, Serialize)]
+pub struct FindingsDiff {
+    /// Present now, absent in the baseline.
+    pub new: Vec<Finding>,
+    /// Present in the baseline, absent now.
+    pub fixed: Vec<Finding>,
+    /// Present in both.
+    pub unchanged: Vec<Finding>,
+}
+
+/// Compare `current` findings against a `baseline`, keyed by rule ID and location.
+pub fn diff(current: &[Finding], baseline: &[Finding]) -> FindingsDiff {
+    let baseline_keys: HashSet<_> = baseline.iter().map(Finding::key).collect();
+    let current_keys: HashSet<_> = current.iter().map(Finding::key).collect();
+
+    let mut seen = HashSet::new();
+    let mut new = Vec::new();
+    let mut unchanged = Vec::new();
+    for f in current {
+        if !seen.insert(f.key()) {
+            continue;
+        }
+        if baseline_keys.contains(&f.key()) {
+            unchanged.push(f.clone());
+        } else {
+            new.push(f.clone());
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let fixed = baseline
+        .iter()
+        .filter(|f| seen.insert(f.key()) && !current_keys.contains(&f.key()))
+        .cloned()
+        .collect();
+
+    FindingsDiff { new, fixed, unchanged }
+}
+
+/// Number of findings per severity.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SeverityCounts {
+    pub critical: i32,
+    pub high: i32,
+    pub medium: i32,
+    pub low: i32,
+    pub info: i32,
+    pub total: i32,
+}
+
+impl SeverityCounts {
+    pub fn add(&mut self, severity: Severity) {
+        match severity {
+            Severity::Critical => self.critical += 1,
+            Severity::High => self.high += 1,
+            Severity::Medium => self.medium += 1,
+            Severity::Low => self.low += 1,
+            Severity::Info => self.info += 1,
+        }
+        self.total += 1;
+    }
+}
+
+/// Suppression details attached to a suppressed finding.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressionInfo {
+    pub id: Uuid,
+    pub state: String,
+    pub reason: String,
+    pub author: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A finding together with the suppression covering it, if any.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotatedFinding {
+    #[serde(flatten)]
+    pub finding: Finding,
+    /// [`Finding::fingerprint`], for clients to tell findings apart.
+    pub fingerprint: String,
+    pub suppression: Option<SuppressionInfo>,
+}
+
+/// Findings of one scan after applying suppressions.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotatedFindings {
+    pub findings: Vec<AnnotatedFinding>,
+    /// Counts of findings that are not suppressed.
+    pub counts: SeverityCounts,
+    pub suppressed_count: i32,
+}
+
+/// Attach matching suppressions to `findings`. Suppressed findings stay in the
+/// list but are left out of `counts`.
+pub fn annotate(
+    findings: Vec<Finding>,
+    suppressions: &[SuppressionRow],
+    target_id: Option<Uuid>,
+    now: DateTime<Utc>,
+) -> AnnotatedFindings {
+    let mut counts = SeverityCounts::default();
+    let mut suppressed_count = 0;
+
+    let findings = findings
+        .into_iter()
+        .map(|finding| {
+            let suppression = suppressions
+                .iter()
+                .find(|s| s.matches(&finding, target_id, now))
+                .map(|s| SuppressionInfo {
+                    id: s.id,
+                    state: s.state.clone(),
+                    reason: s.reason.clone(),
+                    author: s.author.clone(),
+                    expires_at: s.expires_at,
+                });
+            if suppression.is_some() {
+                suppressed_count += 1;
+            } else {
+                counts.add(finding.severity);
+            }
+            AnnotatedFinding {
+                fingerprint: finding.fingerprint(),
+                finding,
+                suppression,
+            }
+        })
+        .collect();
+
+    AnnotatedFindings {
+        findings,
+        counts,
+        suppressed_count,
+    }
+}
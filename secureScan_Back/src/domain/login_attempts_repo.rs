@@ -0,0 +1,146 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LoginAttemptRow {
+    pub id: Uuid,
+    pub email: String,
+    pub ip: Option<String>,
+    pub success: bool,
+    pub method: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Failed attempts counted toward throttling, and the time of the latest one.
+#[derive(Debug, Clone, FromRow)]
+pub struct FailureStats {
+    pub failures: i64,
+    pub last_failure: Option<DateTime<Utc>>,
+}
+
+pub async fn record_attempt(
+    pool: &DbPool,
+    email: &str,
+    user_id: Option<Uuid>,
+    ip: Option<&str>,
+    success: bool,
+    method: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO login_attempts (email, user_id, ip, success, method)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(email)
+    .bind(user_id)
+    .bind(ip)
+    .bind(success)
+    .bind(method)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Failures for `email` since `since` and since its latest successful login.
+pub async fn account_failures(pool: &DbPool, email: &str, since: DateTime<Utc>) -> Result<FailureStats, sqlx::Error> {
+    sqlx::query_as::<_, FailureStats>(
+        r#"
+        SELECT COUNT(*) AS failures, MAX(created_at) AS last_failure
+        FROM login_attempts
+        WHERE email = $1
+          AND NOT success
+          AND created_at > $2
+          AND created_at > COALESCE(
+              (SELECT MAX(created_at) FROM login_attempts WHERE email = $1 AND success),
+              '-infinity'::timestamptz)
+        "#,
+    )
+    .bind(email)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+/// Count an attempt against a throttling `key` unless the key is throttled, and
+/// return its failure count including this attempt; `None` when throttled.
+/// `delays_secs[n - 1]` is how long after the n-th failure the next attempt is
+/// accepted, the last entry applying beyond. The count starts over once the
+/// latest failure is not after `since`.
+pub async fn claim_attempt<'e, E>(
+    executor: E,
+    key: &str,
+    now: DateTime<Utc>,
+    since: DateTime<Utc>,
+    delays_secs: &[i64],
+) -> Result<Option<i32>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO login_throttle AS t (key, failures, last_failure, allowed_at)
+        VALUES ($1, 1, $2, $2 + make_interval(secs => $4[1]))
+        ON CONFLICT (key) DO UPDATE
+        SET failures = CASE WHEN t.last_failure <= $3 THEN 1 ELSE t.failures + 1 END,
+            last_failure = $2,
+            allowed_at = $2 + make_interval(secs => $4[LEAST(
+                CASE WHEN t.last_failure <= $3 THEN 1 ELSE t.failures + 1 END,
+                cardinality($4))])
+        WHERE t.allowed_at <= $2 OR t.last_failure <= $3
+        RETURNING failures
+        "#,
+    )
+    .bind(key)
+    .bind(now)
+    .bind(since)
+    .bind(delays_secs)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Take back an attempt counted by [`claim_attempt`] that did not fail.
+pub async fn release_attempt(pool: &DbPool, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE login_throttle SET failures = failures - 1 WHERE key = $1 AND failures > 0")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Forget a key's failures, lifting its throttling.
+pub async fn clear_failures(pool: &DbPool, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM login_throttle WHERE key = $1")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Latest time any of `keys` accepts its next attempt.
+pub async fn allowed_at(pool: &DbPool, keys: &[String]) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MAX(allowed_at) FROM login_throttle WHERE key = ANY($1)")
+        .bind(keys)
+        .fetch_one(pool)
+        .await
+}
+
+/// Recent attempts on a user's account, newest first.
+pub async fn list_for_user(pool: &DbPool, user_id: Uuid, limit: i64) -> Result<Vec<LoginAttemptRow>, sqlx::Error> {
+    sqlx::query_as::<_, LoginAttemptRow>(
+        r#"
+        SELECT id, email, ip, success, method, created_at
+        FROM login_attempts
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,226 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// TOTP enrollment of a user; `enabled_at` is `None` while pending confirmation.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserMfaRow {
+    pub user_id: Uuid,
+    pub totp_secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+const COLUMNS: &str = "user_id, totp_secret, enabled_at, last_used_step, created_at";
+
+pub async fn get_mfa(pool: &DbPool, user_id: Uuid) -> Result<Option<UserMfaRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM user_mfa WHERE user_id = $1");
+    sqlx::query_as::<_, UserMfaRow>(&sql)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Whether the user has a confirmed second factor.
+pub async fn is_enabled(pool: &DbPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM user_mfa WHERE user_id = $1 AND enabled_at IS NOT NULL)",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Start (or restart) an enrollment with a new secret. Never touches a confirmed enrollment.
+pub async fn upsert_pending(pool: &DbPool, user_id: Uuid, totp_secret: &str) -> Result<Option<UserMfaRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO user_mfa (user_id, totp_secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+            SET totp_secret = EXCLUDED.totp_secret, last_used_step = NULL, created_at = NOW()
+            WHERE user_mfa.enabled_at IS NULL
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, UserMfaRow>(&sql)
+        .bind(user_id)
+        .bind(totp_secret)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Record `step` as used. Returns false if it (or a later step) was already
+/// used, which means the code is being replayed.
+pub async fn claim_step<'e, E>(executor: E, user_id: Uuid, step: i64) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let res = sqlx::query(
+        r#"
+        UPDATE user_mfa SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(executor)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+pub async fn mark_enabled<'e, E>(executor: E, user_id: Uuid) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("UPDATE user_mfa SET enabled_at = NOW() WHERE user_id = $1")
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Remove the second factor and its recovery codes.
+pub async fn delete_mfa(pool: &DbPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM user_mfa WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Replace all recovery codes of a user.
+pub async fn replace_recovery_codes<'e, E>(executor: E, user_id: Uuid, code_hashes: &[String]) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        WITH gone AS (DELETE FROM mfa_recovery_codes WHERE user_id = $1)
+        INSERT INTO mfa_recovery_codes (user_id, code_hash)
+        SELECT $1, h FROM UNNEST($2::text[]) AS h
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hashes)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Use up a recovery code. Returns false if it is unknown or already used.
+pub async fn consume_recovery_code(pool: &DbPool, user_id: Uuid, code_hash: &str) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        r#"
+        UPDATE mfa_recovery_codes SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+pub async fn count_unused_recovery_codes(pool: &DbPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Enrollments whose secret was stored before secrets were encrypted.
+pub async fn list_unsealed(pool: &DbPool) -> Result<Vec<UserMfaRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM user_mfa WHERE totp_secret NOT LIKE 'v1:%'");
+    sqlx::query_as::<_, UserMfaRow>(&sql).fetch_all(pool).await
+}
+
+/// Replace a stored secret by its encrypted form, unless it changed meanwhile.
+pub async fn replace_secret(pool: &DbPool, user_id: Uuid, old: &str, new: &str) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("UPDATE user_mfa SET totp_secret = $3 WHERE user_id = $1 AND totp_secret = $2")
+        .bind(user_id)
+        .bind(old)
+        .bind(new)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// A second login step waiting for a code; only the token hash is stored.
+#[derive(Debug, Clone, FromRow)]
+pub struct MfaChallengeRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const CHALLENGE_COLUMNS: &str = "id, user_id, attempts, expires_at, consumed_at, created_at";
+
+/// Store a new challenge and drop the user's finished or expired ones.
+pub async fn insert_challenge(
+    pool: &DbPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<MfaChallengeRow, sqlx::Error> {
+    sqlx::query("DELETE FROM mfa_challenges WHERE user_id = $1 AND (consumed_at IS NOT NULL OR expires_at <= NOW())")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    let sql = format!(
+        r#"
+        INSERT INTO mfa_challenges (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING {CHALLENGE_COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, MfaChallengeRow>(&sql)
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await
+}
+
+/// Count an attempt against the open challenge with this hash and return it.
+/// Returns `None` once the challenge is used, expired or out of attempts.
+pub async fn start_challenge_attempt(
+    pool: &DbPool,
+    token_hash: &str,
+    max_attempts: i32,
+) -> Result<Option<MfaChallengeRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE mfa_challenges SET attempts = attempts + 1
+        WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() AND attempts < $2
+        RETURNING {CHALLENGE_COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, MfaChallengeRow>(&sql)
+        .bind(token_hash)
+        .bind(max_attempts)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Mark a challenge as used. Returns false if it already was.
+pub async fn consume_challenge(pool: &DbPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("UPDATE mfa_challenges SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
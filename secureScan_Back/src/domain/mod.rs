@@ -1,5 +1,23 @@
+pub mod account_tokens_repo;
+pub mod api_keys_repo;
+pub mod commit_reports_repo;
+pub mod cursor;
 pub mod errors;
+pub mod findings;
+pub mod login_attempts_repo;
+pub mod mfa_repo;
+pub mod notification_channels_repo;
+pub mod oidc_repo;
+pub mod orgs_repo;
+pub mod outbound_webhooks_repo;
+pub mod refresh_tokens_repo;
+pub mod repository_targets_repo;
 pub mod scan_service;
 pub mod scans_repo;
+pub mod schedules_repo;
+pub mod suppressions_repo;
+pub mod targets_repo;
 pub mod users_repo;
+pub mod webhook_deliveries_repo;
+pub mod webhook_integrations_repo;
 pub mod user;
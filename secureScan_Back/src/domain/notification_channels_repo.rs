@@ -0,0 +1,163 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A Slack or Microsoft Teams channel notified about scans.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct NotificationChannelRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    /// `slack` or `teams`.
+    pub kind: String,
+    pub name: String,
+    /// Incoming webhook URL; a credential, never serialized.
+    #[serde(skip_serializing)]
+    pub webhook_url: String,
+    pub min_severity: String,
+    pub only_new: bool,
+    pub target_ids: Vec<Uuid>,
+    pub notify_on_failure: bool,
+    pub enabled: bool,
+    pub created_by: Option<Uuid>,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationChannelRow {
+    /// Whether scans of `target_id` are routed to this channel.
+    pub fn covers_target(&self, target_id: Option<Uuid>) -> bool {
+        self.target_ids.is_empty() || target_id.is_some_and(|id| self.target_ids.contains(&id))
+    }
+}
+
+/// Fields accepted when creating or replacing a channel.
+#[derive(Debug, Clone)]
+pub struct NotificationChannelInput {
+    pub kind: String,
+    pub name: String,
+    /// `None` on update keeps the stored URL.
+    pub webhook_url: Option<String>,
+    pub min_severity: String,
+    pub only_new: bool,
+    pub target_ids: Vec<Uuid>,
+    pub notify_on_failure: bool,
+    pub enabled: bool,
+}
+
+const COLUMNS: &str = "id, org_id, kind, name, webhook_url, min_severity, only_new, target_ids, notify_on_failure, \
+    enabled, created_by, last_sent_at, last_error, created_at, updated_at";
+
+pub async fn create_channel(
+    pool: &DbPool,
+    org_id: Uuid,
+    created_by: Uuid,
+    input: &NotificationChannelInput,
+) -> Result<NotificationChannelRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO notification_channels
+            (org_id, kind, name, webhook_url, min_severity, only_new, target_ids, notify_on_failure, enabled, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, NotificationChannelRow>(&sql)
+        .bind(org_id)
+        .bind(&input.kind)
+        .bind(&input.name)
+        .bind(&input.webhook_url)
+        .bind(&input.min_severity)
+        .bind(input.only_new)
+        .bind(&input.target_ids)
+        .bind(input.notify_on_failure)
+        .bind(input.enabled)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_channels(pool: &DbPool, org_id: Uuid) -> Result<Vec<NotificationChannelRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM notification_channels WHERE org_id = $1 ORDER BY created_at");
+    sqlx::query_as::<_, NotificationChannelRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Enabled channels of the organization.
+pub async fn list_enabled_channels(pool: &DbPool, org_id: Uuid) -> Result<Vec<NotificationChannelRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM notification_channels WHERE org_id = $1 AND enabled ORDER BY created_at");
+    sqlx::query_as::<_, NotificationChannelRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_channel(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<Option<NotificationChannelRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM notification_channels WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, NotificationChannelRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn update_channel(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    input: &NotificationChannelInput,
+) -> Result<Option<NotificationChannelRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE notification_channels
+        SET kind = $3, name = $4, webhook_url = COALESCE($5, webhook_url), min_severity = $6, only_new = $7,
+            target_ids = $8, notify_on_failure = $9, enabled = $10, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, NotificationChannelRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(&input.kind)
+        .bind(&input.name)
+        .bind(&input.webhook_url)
+        .bind(&input.min_severity)
+        .bind(input.only_new)
+        .bind(&input.target_ids)
+        .bind(input.notify_on_failure)
+        .bind(input.enabled)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn delete_channel(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM notification_channels WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Remember the outcome of the latest message; `error` is `None` when it was accepted.
+pub async fn record_send(pool: &DbPool, id: Uuid, error: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE notification_channels
+        SET last_sent_at = CASE WHEN $2::TEXT IS NULL THEN NOW() ELSE last_sent_at END,
+            last_error = $2
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use crate::db::DbPool;
+use crate::domain::orgs_repo::Role;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct IdentityRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const IDENTITY_COLUMNS: &str = "id, user_id, issuer, subject, email, last_login_at, created_at";
+
+/// A pending authorization request, consumed by the callback.
+#[derive(Debug, Clone, FromRow)]
+pub struct LoginStateRow {
+    pub nonce: String,
+    pub code_verifier: String,
+    pub redirect_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GroupMappingRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub group_name: String,
+    pub role: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GroupMappingRow {
+    pub fn role(&self) -> Role {
+        Role::parse(&self.role).unwrap_or(Role::Viewer)
+    }
+}
+
+const MAPPING_COLUMNS: &str = "id, org_id, group_name, role, created_by, created_at";
+
+/// Store a new authorization request; expired ones are purged on the way.
+pub async fn insert_login_state(
+    pool: &DbPool,
+    state_hash: &str,
+    browser_hash: &str,
+    nonce: &str,
+    code_verifier: &str,
+    redirect_to: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM oidc_login_states WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO oidc_login_states (state_hash, browser_hash, nonce, code_verifier, redirect_to, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(state_hash)
+    .bind(browser_hash)
+    .bind(nonce)
+    .bind(code_verifier)
+    .bind(redirect_to)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Take an unexpired request by its state, if the same browser started it;
+/// each state is usable once.
+pub async fn consume_login_state(
+    pool: &DbPool,
+    state_hash: &str,
+    browser_hash: &str,
+) -> Result<Option<LoginStateRow>, sqlx::Error> {
+    sqlx::query_as::<_, LoginStateRow>(
+        r#"
+        DELETE FROM oidc_login_states
+        WHERE state_hash = $1 AND browser_hash = $2 AND expires_at > NOW()
+        RETURNING nonce, code_verifier, redirect_to
+        "#,
+    )
+    .bind(state_hash)
+    .bind(browser_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_identity(pool: &DbPool, issuer: &str, subject: &str) -> Result<Option<IdentityRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM user_identities WHERE issuer = $1 AND subject = $2",
+        IDENTITY_COLUMNS
+    );
+    sqlx::query_as::<_, IdentityRow>(&sql)
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Link an IdP account to `user_id`, or refresh the link's email and login time.
+pub async fn upsert_identity<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    issuer: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<IdentityRow, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let sql = format!(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email, last_login_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (issuer, subject) DO UPDATE SET email = EXCLUDED.email, last_login_at = NOW()
+        RETURNING {}
+        "#,
+        IDENTITY_COLUMNS
+    );
+    sqlx::query_as::<_, IdentityRow>(&sql)
+        .bind(user_id)
+        .bind(issuer)
+        .bind(subject)
+        .bind(email)
+        .fetch_one(executor)
+        .await
+}
+
+pub async fn list_identities_for_user(pool: &DbPool, user_id: Uuid) -> Result<Vec<IdentityRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM user_identities WHERE user_id = $1 ORDER BY created_at",
+        IDENTITY_COLUMNS
+    );
+    sqlx::query_as::<_, IdentityRow>(&sql).bind(user_id).fetch_all(pool).await
+}
+
+pub async fn list_group_mappings(pool: &DbPool, org_id: Uuid) -> Result<Vec<GroupMappingRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM oidc_group_mappings WHERE org_id = $1 ORDER BY group_name",
+        MAPPING_COLUMNS
+    );
+    sqlx::query_as::<_, GroupMappingRow>(&sql).bind(org_id).fetch_all(pool).await
+}
+
+/// Map `group_name` to `role` in the organization, replacing an earlier mapping of the group.
+pub async fn upsert_group_mapping(
+    pool: &DbPool,
+    org_id: Uuid,
+    group_name: &str,
+    role: Role,
+    created_by: Uuid,
+) -> Result<GroupMappingRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO oidc_group_mappings (org_id, group_name, role, created_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (org_id, group_name) DO UPDATE SET role = EXCLUDED.role
+        RETURNING {}
+        "#,
+        MAPPING_COLUMNS
+    );
+    sqlx::query_as::<_, GroupMappingRow>(&sql)
+        .bind(org_id)
+        .bind(group_name)
+        .bind(role.as_str())
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn delete_group_mapping(pool: &DbPool, org_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM oidc_group_mappings WHERE org_id = $1 AND id = $2")
+        .bind(org_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Every mapping of any of `groups` in the organizations `org_ids`.
+pub async fn mappings_for_groups(
+    pool: &DbPool,
+    groups: &[String],
+    org_ids: &[Uuid],
+) -> Result<Vec<GroupMappingRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM oidc_group_mappings WHERE group_name = ANY($1) AND org_id = ANY($2)",
+        MAPPING_COLUMNS
+    );
+    sqlx::query_as::<_, GroupMappingRow>(&sql)
+        .bind(groups)
+        .bind(org_ids)
+        .fetch_all(pool)
+        .await
+}
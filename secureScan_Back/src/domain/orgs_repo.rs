@@ -0,0 +1,375 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Role of a member within an organization, from most to least privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,
+    Analyst,
+    Admin,
+    Owner,
+}
+
+/// Actions guarded by role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// See scans, targets, findings, schedules and suppressions.
+    View,
+    /// Start scans and manage schedules.
+    RunScans,
+    ManageSuppressions,
+    /// Create inbound webhook integrations and see or rotate their secrets.
+    ManageIntegrations,
+    /// Invite, remove and change the role of members below owner.
+    ManageMembers,
+    /// Grant or revoke the owner role, rename the organization.
+    ManageOwners,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Analyst => "analyst",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Role> {
+        match s {
+            "viewer" => Some(Role::Viewer),
+            "analyst" => Some(Role::Analyst),
+            "admin" => Some(Role::Admin),
+            "owner" => Some(Role::Owner),
+            _ => None,
+        }
+    }
+
+    /// Least privileged role allowed to perform `permission`.
+    fn minimum_for(permission: Permission) -> Role {
+        match permission {
+            Permission::View => Role::Viewer,
+            Permission::RunScans => Role::Analyst,
+            Permission::ManageSuppressions | Permission::ManageIntegrations | Permission::ManageMembers => Role::Admin,
+            Permission::ManageOwners => Role::Owner,
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        *self >= Role::minimum_for(permission)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OrgRow {
+    pub id: Uuid,
+    pub name: String,
+    /// Members must have two-factor authentication enabled to access the organization.
+    pub require_mfa: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An organization together with the caller's role in it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MembershipRow {
+    pub org_id: Uuid,
+    pub name: String,
+    pub require_mfa: bool,
+    pub role: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+impl MembershipRow {
+    pub fn role(&self) -> Role {
+        Role::parse(&self.role).unwrap_or(Role::Viewer)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MemberRow {
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub mfa_enabled: bool,
+    /// Granted by an SSO group mapping; follows the member's IdP groups.
+    pub sso_managed: bool,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct InvitationRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub invited_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const INVITATION_COLUMNS: &str = "id, org_id, email, role, invited_by, expires_at, accepted_at, created_at";
+
+/// Create an organization with `owner_id` as its first owner.
+pub async fn create_org<'e, E>(executor: E, name: &str, owner_id: Uuid) -> Result<OrgRow, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, OrgRow>(
+        r#"
+        WITH o AS (
+            INSERT INTO organizations (name) VALUES ($1)
+            RETURNING id, name, require_mfa, created_at
+        ), m AS (
+            INSERT INTO organization_members (org_id, user_id, role)
+            SELECT o.id, $2, 'owner' FROM o
+        )
+        SELECT id, name, require_mfa, created_at FROM o
+        "#,
+    )
+    .bind(name)
+    .bind(owner_id)
+    .fetch_one(executor)
+    .await
+}
+
+pub async fn get_org(pool: &DbPool, org_id: Uuid) -> Result<Option<OrgRow>, sqlx::Error> {
+    sqlx::query_as::<_, OrgRow>("SELECT id, name, require_mfa, created_at FROM organizations WHERE id = $1")
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn rename_org(pool: &DbPool, org_id: Uuid, name: &str) -> Result<Option<OrgRow>, sqlx::Error> {
+    sqlx::query_as::<_, OrgRow>(
+        "UPDATE organizations SET name = $2 WHERE id = $1 RETURNING id, name, require_mfa, created_at",
+    )
+    .bind(org_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_require_mfa(pool: &DbPool, org_id: Uuid, require_mfa: bool) -> Result<Option<OrgRow>, sqlx::Error> {
+    sqlx::query_as::<_, OrgRow>(
+        "UPDATE organizations SET require_mfa = $2 WHERE id = $1 RETURNING id, name, require_mfa, created_at",
+    )
+    .bind(org_id)
+    .bind(require_mfa)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Organizations `user_id` belongs to, oldest membership first.
+pub async fn list_memberships(pool: &DbPool, user_id: Uuid) -> Result<Vec<MembershipRow>, sqlx::Error> {
+    sqlx::query_as::<_, MembershipRow>(
+        r#"
+        SELECT o.id AS org_id, o.name, o.require_mfa, m.role, m.created_at AS joined_at
+        FROM organization_members m
+        JOIN organizations o ON o.id = m.org_id
+        WHERE m.user_id = $1
+        ORDER BY m.created_at, o.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// The membership of `user_id` in `org_id`, or `None` if they are not a member.
+pub async fn get_membership<'e, E>(
+    executor: E,
+    org_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<MembershipRow>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, MembershipRow>(
+        r#"
+        SELECT o.id AS org_id, o.name, o.require_mfa, m.role, m.created_at AS joined_at
+        FROM organization_members m
+        JOIN organizations o ON o.id = m.org_id
+        WHERE m.org_id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+}
+
+pub async fn list_members(pool: &DbPool, org_id: Uuid) -> Result<Vec<MemberRow>, sqlx::Error> {
+    sqlx::query_as::<_, MemberRow>(
+        r#"
+        SELECT u.id AS user_id, u.email, m.role, (f.enabled_at IS NOT NULL) AS mfa_enabled,
+               m.sso_managed, m.created_at AS joined_at
+        FROM organization_members m
+        JOIN users u ON u.id = m.user_id
+        LEFT JOIN user_mfa f ON f.user_id = m.user_id
+        WHERE m.org_id = $1
+        ORDER BY m.created_at, u.email
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add a member, or change their role if they already belong to the organization.
+/// A membership set this way is no longer managed by SSO group mappings.
+pub async fn upsert_member<'e, E>(executor: E, org_id: Uuid, user_id: Uuid, role: Role) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO organization_members (org_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (org_id, user_id) DO UPDATE SET role = EXCLUDED.role, sso_managed = FALSE
+        "#,
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .bind(role.as_str())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Make the SSO-managed memberships of `user_id` exactly `roles`: add or update
+/// them and drop the ones no longer granted. Manual memberships are left as they are.
+pub async fn sync_sso_memberships(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    roles: &[(Uuid, Role)],
+) -> Result<(), sqlx::Error> {
+    for (org_id, role) in roles {
+        sqlx::query(
+            r#"
+            INSERT INTO organization_members (org_id, user_id, role, sso_managed)
+            VALUES ($1, $2, $3, TRUE)
+            ON CONFLICT (org_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            WHERE organization_members.sso_managed
+            "#,
+        )
+        .bind(org_id)
+        .bind(user_id)
+        .bind(role.as_str())
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    let keep: Vec<Uuid> = roles.iter().map(|(org_id, _)| *org_id).collect();
+    sqlx::query("DELETE FROM organization_members WHERE user_id = $1 AND sso_managed AND NOT (org_id = ANY($2))")
+        .bind(user_id)
+        .bind(&keep)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+pub async fn remove_member(pool: &DbPool, org_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM organization_members WHERE org_id = $1 AND user_id = $2")
+        .bind(org_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+pub async fn count_owners(pool: &DbPool, org_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM organization_members WHERE org_id = $1 AND role = 'owner'",
+    )
+    .bind(org_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn create_invitation(
+    pool: &DbPool,
+    org_id: Uuid,
+    email: &str,
+    role: Role,
+    token_hash: &str,
+    invited_by: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<InvitationRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO organization_invitations (org_id, email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {INVITATION_COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, InvitationRow>(&sql)
+        .bind(org_id)
+        .bind(email)
+        .bind(role.as_str())
+        .bind(token_hash)
+        .bind(invited_by)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await
+}
+
+/// Invitations of an organization that were neither accepted nor expired.
+pub async fn list_pending_invitations(pool: &DbPool, org_id: Uuid) -> Result<Vec<InvitationRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {INVITATION_COLUMNS}
+        FROM organization_invitations
+        WHERE org_id = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        ORDER BY created_at DESC
+        "#
+    );
+    sqlx::query_as::<_, InvitationRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn delete_invitation(pool: &DbPool, org_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM organization_invitations WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Lock a pending, unexpired invitation by token hash.
+pub async fn lock_pending_invitation(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    token_hash: &str,
+) -> Result<Option<InvitationRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {INVITATION_COLUMNS}
+        FROM organization_invitations
+        WHERE token_hash = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        FOR UPDATE
+        "#
+    );
+    sqlx::query_as::<_, InvitationRow>(&sql)
+        .bind(token_hash)
+        .fetch_optional(&mut **tx)
+        .await
+}
+
+pub async fn mark_invitation_accepted(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE organization_invitations SET accepted_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
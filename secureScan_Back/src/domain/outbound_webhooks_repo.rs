@@ -0,0 +1,293 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An endpoint that receives deliveries of an organization's events.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutboundWebhookRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub url: String,
+    pub description: Option<String>,
+    pub events: Vec<String>,
+    /// Only returned when created or rotated.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields accepted when creating or replacing an endpoint.
+#[derive(Debug, Clone)]
+pub struct OutboundWebhookInput {
+    pub url: String,
+    pub description: Option<String>,
+    pub events: Vec<String>,
+    pub enabled: bool,
+}
+
+const COLUMNS: &str = "id, org_id, url, description, events, secret, enabled, created_by, created_at, updated_at";
+
+/// One delivery of an event to an endpoint.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeliveryRow {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_id: Uuid,
+    pub event: String,
+    pub payload: JsonValue,
+    /// `pending`, `succeeded` or `failed` (out of attempts).
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub last_response: Option<String>,
+    pub redelivery_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event_id, event, payload, status, attempts, next_attempt_at, \
+    last_status_code, last_error, last_response, redelivery_of, created_at, delivered_at";
+
+pub async fn create_webhook(
+    pool: &DbPool,
+    org_id: Uuid,
+    created_by: Uuid,
+    input: &OutboundWebhookInput,
+    secret: &str,
+) -> Result<OutboundWebhookRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO outbound_webhooks (org_id, url, description, events, enabled, secret, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(org_id)
+        .bind(&input.url)
+        .bind(&input.description)
+        .bind(&input.events)
+        .bind(input.enabled)
+        .bind(secret)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_webhooks(pool: &DbPool, org_id: Uuid) -> Result<Vec<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM outbound_webhooks WHERE org_id = $1 ORDER BY created_at");
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_webhook(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<Option<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM outbound_webhooks WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn update_webhook(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    input: &OutboundWebhookInput,
+) -> Result<Option<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE outbound_webhooks
+        SET url = $3, description = $4, events = $5, enabled = $6, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(&input.url)
+        .bind(&input.description)
+        .bind(&input.events)
+        .bind(input.enabled)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn set_secret(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    secret: &str,
+) -> Result<Option<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE outbound_webhooks
+        SET secret = $3, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(secret)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn delete_webhook(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM outbound_webhooks WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Enabled endpoints of the organization subscribed to `event`.
+pub async fn subscribers(pool: &DbPool, org_id: Uuid, event: &str) -> Result<Vec<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM outbound_webhooks WHERE org_id = $1 AND enabled AND $2 = ANY(events)");
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(org_id)
+        .bind(event)
+        .fetch_all(pool)
+        .await
+}
+
+/// Queue a delivery for its first attempt right away.
+pub async fn create_delivery(
+    pool: &DbPool,
+    webhook_id: Uuid,
+    event_id: Uuid,
+    event: &str,
+    payload: &JsonValue,
+    redelivery_of: Option<Uuid>,
+) -> Result<DeliveryRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO outbound_webhook_deliveries (webhook_id, event_id, event, payload, redelivery_of, next_attempt_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        RETURNING {DELIVERY_COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, DeliveryRow>(&sql)
+        .bind(webhook_id)
+        .bind(event_id)
+        .bind(event)
+        .bind(payload)
+        .bind(redelivery_of)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_deliveries(pool: &DbPool, webhook_id: Uuid, limit: i64) -> Result<Vec<DeliveryRow>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {DELIVERY_COLUMNS} FROM outbound_webhook_deliveries WHERE webhook_id = $1 ORDER BY created_at DESC LIMIT $2"
+    );
+    sqlx::query_as::<_, DeliveryRow>(&sql)
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_delivery(pool: &DbPool, id: Uuid, webhook_id: Uuid) -> Result<Option<DeliveryRow>, sqlx::Error> {
+    let sql = format!("SELECT {DELIVERY_COLUMNS} FROM outbound_webhook_deliveries WHERE id = $1 AND webhook_id = $2");
+    sqlx::query_as::<_, DeliveryRow>(&sql)
+        .bind(id)
+        .bind(webhook_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Claim up to `limit` due deliveries for one attempt.
+///
+/// Rows are locked with `SKIP LOCKED` and their next attempt is pushed back by
+/// `lease`, so another instance does not send them again while this one does.
+pub async fn claim_due_deliveries(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    now: DateTime<Utc>,
+    lease: chrono::Duration,
+    limit: i64,
+) -> Result<Vec<DeliveryRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE outbound_webhook_deliveries
+        SET next_attempt_at = $2
+        WHERE id IN (
+            SELECT id FROM outbound_webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= $1
+            ORDER BY next_attempt_at
+            LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING {DELIVERY_COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, DeliveryRow>(&sql)
+        .bind(now)
+        .bind(now + lease)
+        .bind(limit)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+/// An endpoint by ID alone, for sending its deliveries.
+pub async fn find_webhook(pool: &DbPool, id: Uuid) -> Result<Option<OutboundWebhookRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM outbound_webhooks WHERE id = $1");
+    sqlx::query_as::<_, OutboundWebhookRow>(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Outcome of one attempt.
+pub struct AttemptResult<'a> {
+    pub status_code: Option<i32>,
+    pub error: Option<&'a str>,
+    pub response: Option<&'a str>,
+    /// `None` when the delivery is finished (succeeded, or failed for good).
+    pub retry_at: Option<DateTime<Utc>>,
+    pub succeeded: bool,
+}
+
+pub async fn record_attempt(pool: &DbPool, id: Uuid, result: &AttemptResult<'_>) -> Result<(), sqlx::Error> {
+    let status = match (result.succeeded, result.retry_at) {
+        (true, _) => "succeeded",
+        (false, Some(_)) => "pending",
+        (false, None) => "failed",
+    };
+    sqlx::query(
+        r#"
+        UPDATE outbound_webhook_deliveries
+        SET status = $2,
+            attempts = attempts + 1,
+            next_attempt_at = $3,
+            last_status_code = $4,
+            last_error = $5,
+            last_response = $6,
+            delivered_at = CASE WHEN $2 = 'succeeded' THEN NOW() ELSE delivered_at END
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(result.retry_at)
+    .bind(result.status_code)
+    .bind(result.error)
+    .bind(result.response)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Server-side record of an issued refresh token. Only the hash of the token is stored.
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshTokenRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub replaced_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+const COLUMNS: &str = "id, user_id, family_id, token_hash, expires_at, revoked_at, replaced_by, created_at";
+
+pub async fn insert_refresh_token<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    family_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<RefreshTokenRow, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let sql = format!(
+        r#"
+        INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, RefreshTokenRow>(&sql)
+        .bind(user_id)
+        .bind(family_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(executor)
+        .await
+}
+
+/// Look up a token by hash, locking the row so concurrent refreshes of the
+/// same token cannot both succeed.
+pub async fn lock_by_hash(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    token_hash: &str,
+) -> Result<Option<RefreshTokenRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE");
+    sqlx::query_as::<_, RefreshTokenRow>(&sql)
+        .bind(token_hash)
+        .fetch_optional(&mut **tx)
+        .await
+}
+
+pub async fn find_by_hash(pool: &DbPool, token_hash: &str) -> Result<Option<RefreshTokenRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM refresh_tokens WHERE token_hash = $1");
+    sqlx::query_as::<_, RefreshTokenRow>(&sql)
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Mark a token as used and point it at its successor.
+pub async fn mark_rotated(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    replaced_by: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW(), replaced_by = $2 WHERE id = $1")
+        .bind(id)
+        .bind(replaced_by)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Revoke every still-valid token of a login family.
+pub async fn revoke_family<'e, E>(executor: E, family_id: Uuid) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let res = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(family_id)
+    .execute(executor)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Revoke every still-valid token of a user (all sessions).
+pub async fn revoke_user_tokens(pool: &DbPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query(
+        "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,193 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A repository whose webhook events start scans in an organization.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RepositoryTargetRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    /// User who created the mapping; its scans are attributed to them.
+    pub created_by: Option<Uuid>,
+    pub provider: String,
+    /// `owner/name`, lower case.
+    pub repository: String,
+    pub target_url: Option<String>,
+    pub events: Vec<String>,
+    pub branches: Vec<String>,
+    pub environments: Vec<String>,
+    pub use_deployment_url: bool,
+    pub options: JsonValue,
+    pub enabled: bool,
+    /// Post the result of each scan as a commit status.
+    pub report_to_provider: bool,
+    /// Open findings of this severity or worse fail the report.
+    pub fail_on_severity: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields accepted when creating or replacing a repository target.
+#[derive(Debug, Clone)]
+pub struct RepositoryTargetInput {
+    pub provider: String,
+    pub repository: String,
+    pub target_url: Option<String>,
+    pub events: Vec<String>,
+    pub branches: Vec<String>,
+    pub environments: Vec<String>,
+    pub use_deployment_url: bool,
+    pub options: JsonValue,
+    pub enabled: bool,
+    pub report_to_provider: bool,
+    pub fail_on_severity: String,
+}
+
+const COLUMNS: &str = "id, org_id, created_by, provider, repository, target_url, events, branches, environments, \
+    use_deployment_url, options, enabled, report_to_provider, fail_on_severity, created_at, updated_at";
+
+pub async fn create_repository_target(
+    pool: &DbPool,
+    org_id: Uuid,
+    created_by: Uuid,
+    input: &RepositoryTargetInput,
+) -> Result<RepositoryTargetRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO repository_targets
+            (provider, repository, target_url, events, branches, environments, use_deployment_url, options,
+             enabled, report_to_provider, fail_on_severity, org_id, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, RepositoryTargetRow>(&sql)
+        .bind(&input.provider)
+        .bind(&input.repository)
+        .bind(&input.target_url)
+        .bind(&input.events)
+        .bind(&input.branches)
+        .bind(&input.environments)
+        .bind(input.use_deployment_url)
+        .bind(&input.options)
+        .bind(input.enabled)
+        .bind(input.report_to_provider)
+        .bind(&input.fail_on_severity)
+        .bind(org_id)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_repository_targets(pool: &DbPool, org_id: Uuid) -> Result<Vec<RepositoryTargetRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM repository_targets WHERE org_id = $1 ORDER BY repository, created_at");
+    sqlx::query_as::<_, RepositoryTargetRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_repository_target(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+) -> Result<Option<RepositoryTargetRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM repository_targets WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, RepositoryTargetRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn update_repository_target(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    input: &RepositoryTargetInput,
+) -> Result<Option<RepositoryTargetRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE repository_targets
+        SET provider = $2,
+            repository = $3,
+            target_url = $4,
+            events = $5,
+            branches = $6,
+            environments = $7,
+            use_deployment_url = $8,
+            options = $9,
+            enabled = $10,
+            report_to_provider = $11,
+            fail_on_severity = $12,
+            updated_at = NOW()
+        WHERE id = $1 AND org_id = $13
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, RepositoryTargetRow>(&sql)
+        .bind(id)
+        .bind(&input.provider)
+        .bind(&input.repository)
+        .bind(&input.target_url)
+        .bind(&input.events)
+        .bind(&input.branches)
+        .bind(&input.environments)
+        .bind(input.use_deployment_url)
+        .bind(&input.options)
+        .bind(input.enabled)
+        .bind(input.report_to_provider)
+        .bind(&input.fail_on_severity)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn delete_repository_target(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM repository_targets WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// How many organizations have an enabled mapping of `repository`.
+pub async fn count_orgs_mapping(pool: &DbPool, provider: &str, repository: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(DISTINCT org_id) FROM repository_targets WHERE enabled AND provider = $1 AND repository = $2",
+    )
+    .bind(provider)
+    .bind(repository.to_lowercase())
+    .fetch_one(pool)
+    .await
+}
+
+/// Enabled mappings of `repository` that listen to `event` in the organization `org_id`.
+pub async fn find_for_event(
+    pool: &DbPool,
+    org_id: Uuid,
+    provider: &str,
+    repository: &str,
+    event: &str,
+) -> Result<Vec<RepositoryTargetRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM repository_targets
+        WHERE enabled AND provider = $1 AND repository = $2 AND $3 = ANY(events)
+          AND org_id = $4
+        ORDER BY created_at
+        "#
+    );
+    sqlx::query_as::<_, RepositoryTargetRow>(&sql)
+        .bind(provider)
+        .bind(repository.to_lowercase())
+        .bind(event)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
@@ -1,4 +1,5 @@
 use crate::jobs::queue::{ScanJob, ScanQueue};
+use crate::scanner::ScanOptions;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -70,7 +71,11 @@ impl ScanService {
         self.state.write().unwrap().insert(id, rec);
 
         // IMPORTANT: use `send` (not `push`)
-        self.queue.send(ScanJob { id, target })?;
+        self.queue.send(ScanJob {
+            id,
+            target,
+            options: ScanOptions::default(),
+        })?;
 
         Ok(id)
     }
@@ -99,7 +104,7 @@ impl ScanService {
 
     pub fn list(&self) -> Vec<ScanRecord> {
         let mut v: Vec<_> = self.state.read().unwrap().values().cloned().collect();
-        v.sort_by(|a, b| b.created_at.cmp(&a.created_at)); // newest first
+        v.sort_by_key(|r| std::cmp::Reverse(r.created_at)); // newest first
         v
     }
 }
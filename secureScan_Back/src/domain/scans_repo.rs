@@ -1,16 +1,53 @@
 use crate::db::DbPool;
-use chrono::{DateTime, Utc};
+use crate::domain::cursor::Cursor;
+use crate::domain::findings::{Finding, Severity, SeverityCounts};
+use crate::domain::targets_repo;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::Serialize;
 use serde_json::Value as JsonValue;
-use sqlx::FromRow;
+use sqlx::{FromRow, Postgres, QueryBuilder};
 use uuid::Uuid;
 
+/// Columns of `scans` selected into [`ScanRow`].
+const SCAN_COLUMNS: &str = "id, url, status, created_at, schedule_id, target_id, owner_id, org_id, options, \
+    critical_count, high_count, medium_count, low_count, info_count, risk_score, grade";
+
 #[derive(Debug, Serialize, FromRow)]
 pub struct ScanRow {
     pub id: Uuid,
     pub url: String,
     pub status: String,
     pub created_at: DateTime<Utc>,
+    pub schedule_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    pub options: JsonValue,
+    pub critical_count: i32,
+    pub high_count: i32,
+    pub medium_count: i32,
+    pub low_count: i32,
+    pub info_count: i32,
+    pub risk_score: Option<i32>,
+    pub grade: Option<String>,
+}
+
+impl ScanRow {
+    /// Stored counts of open (unsuppressed) findings.
+    pub fn counts(&self) -> SeverityCounts {
+        severity_counts(self.critical_count, self.high_count, self.medium_count, self.low_count, self.info_count)
+    }
+}
+
+fn severity_counts(critical: i32, high: i32, medium: i32, low: i32, info: i32) -> SeverityCounts {
+    SeverityCounts {
+        critical,
+        high,
+        medium,
+        low,
+        info,
+        total: critical + high + medium + low + info,
+    }
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -23,6 +60,34 @@ pub struct ScanResultRow {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// One row of the normalized `findings` table.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FindingRow {
+    pub id: Uuid,
+    pub scan_id: Uuid,
+    pub scan_result_id: Uuid,
+    pub position: i32,
+    pub rule_id: String,
+    pub severity: String,
+    pub title: String,
+    pub location: String,
+    pub evidence: Option<String>,
+    pub fingerprint: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FindingRow {
+    pub fn to_finding(&self) -> Finding {
+        Finding {
+            rule_id: self.rule_id.clone(),
+            severity: Severity::parse(&self.severity).unwrap_or(Severity::Info),
+            title: self.title.clone(),
+            location: self.location.clone(),
+            evidence: self.evidence.clone(),
+        }
+    }
+}
+
 /// Row type برای لیست اسکن‌ها + خلاصه‌ی آخرین نتیجه
 #[derive(Debug, Serialize, FromRow)]
 pub struct ScanListRow {
@@ -30,71 +95,461 @@ pub struct ScanListRow {
     pub url: String,
     pub status: String,
     pub created_at: DateTime<Utc>,
+    pub schedule_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    pub critical_count: i32,
+    pub high_count: i32,
+    pub medium_count: i32,
+    pub low_count: i32,
+    pub info_count: i32,
+    pub risk_score: Option<i32>,
+    pub grade: Option<String>,
     pub completed_at: Option<DateTime<Utc>>,
-    pub issues: Option<JsonValue>,
+    /// Findings of the latest result as a JSON array (from the `findings` table);
+    /// always NULL when the listing was built without findings.
+    pub findings: Option<JsonValue>,
+}
+
+impl ScanListRow {
+    pub fn counts(&self) -> SeverityCounts {
+        severity_counts(self.critical_count, self.high_count, self.medium_count, self.low_count, self.info_count)
+    }
+}
+
+/// Sort key for [`list_scans`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanSort {
+    #[default]
+    CreatedAt,
+    RiskScore,
+    Grade,
+    CriticalCount,
+    HighCount,
+}
+
+// NULL risk scores and grades sort last in both directions. Keyset pagination
+// needs a total order without NULLs, so scores are replaced by a value past the
+// end of the range for the chosen direction. Grades are text, where no value
+// sorts after every other under all collations; they are ordered by whether
+// they are missing first, and a missing grade has the empty cursor key.
+const NULL_SCORE_DESC: i32 = -1;
+const NULL_SCORE_ASC: i32 = i32::MAX;
+
+impl ScanSort {
+    pub fn parse(s: &str) -> Option<ScanSort> {
+        match s {
+            "created_at" => Some(ScanSort::CreatedAt),
+            "risk_score" => Some(ScanSort::RiskScore),
+            "grade" => Some(ScanSort::Grade),
+            "critical_count" => Some(ScanSort::CriticalCount),
+            "high_count" => Some(ScanSort::HighCount),
+            _ => None,
+        }
+    }
+
+    /// SQL expressions ordered on, all in the same direction; never NULL.
+    fn exprs(&self, descending: bool) -> Vec<String> {
+        match self {
+            ScanSort::CreatedAt => vec!["s.created_at".to_string()],
+            ScanSort::RiskScore => vec![format!(
+                "COALESCE(s.risk_score, {})",
+                if descending { NULL_SCORE_DESC } else { NULL_SCORE_ASC }
+            )],
+            ScanSort::Grade => vec![
+                if descending { "(s.grade IS NOT NULL)" } else { "(s.grade IS NULL)" }.to_string(),
+                "COALESCE(s.grade, '')".to_string(),
+            ],
+            ScanSort::CriticalCount => vec!["s.critical_count".to_string()],
+            ScanSort::HighCount => vec!["s.high_count".to_string()],
+        }
+    }
+
+    /// Push the values of [`ScanSort::exprs`] a cursor `key` stands for.
+    fn push_key(&self, qb: &mut QueryBuilder<'static, Postgres>, key: &str, descending: bool) {
+        if *self == ScanSort::Grade {
+            qb.push("(CAST(")
+                .push_bind(key.to_string())
+                .push(" AS TEXT) ")
+                .push(if descending { "<>" } else { "=" })
+                .push(" ''), CAST(")
+                .push_bind(key.to_string())
+                .push(" AS TEXT)");
+        } else {
+            qb.push("CAST(")
+                .push_bind(key.to_string())
+                .push(format!(" AS {})", self.sql_type()));
+        }
+    }
+
+    fn sql_type(&self) -> &'static str {
+        match self {
+            ScanSort::CreatedAt => "TIMESTAMPTZ",
+            ScanSort::Grade => "TEXT",
+            _ => "INTEGER",
+        }
+    }
+
+    /// Cursor key of `row`: the value of [`ScanSort::exprs`] for it, as text.
+    pub fn cursor_key(&self, row: &ScanListRow, descending: bool) -> String {
+        match self {
+            ScanSort::CreatedAt => row.created_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            ScanSort::RiskScore => row
+                .risk_score
+                .unwrap_or(if descending { NULL_SCORE_DESC } else { NULL_SCORE_ASC })
+                .to_string(),
+            ScanSort::Grade => row.grade.clone().unwrap_or_default(),
+            ScanSort::CriticalCount => row.critical_count.to_string(),
+            ScanSort::HighCount => row.high_count.to_string(),
+        }
+    }
+
+    /// Whether `key` could have been produced by [`ScanSort::cursor_key`].
+    pub fn is_valid_key(&self, key: &str) -> bool {
+        match self {
+            ScanSort::CreatedAt => DateTime::parse_from_rfc3339(key).is_ok(),
+            ScanSort::Grade => true,
+            _ => key.parse::<i32>().is_ok(),
+        }
+    }
+}
+
+/// Filters, ordering and paging for [`list_scans`].
+#[derive(Debug, Clone)]
+pub struct ScanListFilter {
+    pub org_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    /// Only scans in one of these statuses (empty = any).
+    pub statuses: Vec<String>,
+    /// Case-insensitive substring of the scanned URL.
+    pub url_contains: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    /// Only scans with one of these grades (empty = any).
+    pub grades: Vec<String>,
+    pub min_risk_score: Option<i32>,
+    pub max_risk_score: Option<i32>,
+    pub sort: ScanSort,
+    pub descending: bool,
+    /// Continue after this row (see [`ScanSort::cursor_key`]).
+    pub after: Option<Cursor>,
+    pub limit: Option<i64>,
+    /// Aggregate the findings of each scan's latest result; skipped in summary listings.
+    pub include_findings: bool,
+}
+
+impl Default for ScanListFilter {
+    fn default() -> Self {
+        Self {
+            org_id: None,
+            target_id: None,
+            owner_id: None,
+            statuses: Vec::new(),
+            url_contains: None,
+            created_from: None,
+            created_to: None,
+            grades: Vec::new(),
+            min_risk_score: None,
+            max_risk_score: None,
+            sort: ScanSort::CreatedAt,
+            descending: true,
+            after: None,
+            limit: None,
+            include_findings: true,
+        }
+    }
 }
 
-pub async fn create_scan(pool: &DbPool, url: &str) -> Result<ScanRow, sqlx::Error> {
-    let rec = sqlx::query_as::<_, ScanRow>(
+/// Fields of a scan to create.
+#[derive(Debug, Clone)]
+pub struct NewScan {
+    pub url: String,
+    pub options: JsonValue,
+    pub schedule_id: Option<Uuid>,
+    /// User who started the scan (or owns the schedule that did).
+    pub owner_id: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+}
+
+/// Create a queued scan, registering its target in the organization on first sight.
+pub async fn create_scan(pool: &DbPool, new: &NewScan) -> Result<ScanRow, sqlx::Error> {
+    let sql = format!(
         r#"
-        INSERT INTO scans (url, status)
-        VALUES ($1, 'queued')
-        RETURNING id, url, status, created_at
-        "#,
-    )
-    .bind(url)
-    .fetch_one(pool)
-    .await?;
+        WITH t AS (
+            INSERT INTO targets (url, org_id)
+            VALUES ($4, $6)
+            ON CONFLICT (org_id, url) DO UPDATE SET url = EXCLUDED.url
+            RETURNING id
+        )
+        INSERT INTO scans (url, status, options, schedule_id, target_id, owner_id, org_id)
+        SELECT $1, 'queued', $2, $3, t.id, $5, $6 FROM t
+        RETURNING {SCAN_COLUMNS}
+        "#
+    );
+    let rec = sqlx::query_as::<_, ScanRow>(&sql)
+        .bind(&new.url)
+        .bind(&new.options)
+        .bind(new.schedule_id)
+        .bind(targets_repo::normalize_url(&new.url))
+        .bind(new.owner_id)
+        .bind(new.org_id)
+        .fetch_one(pool)
+        .await?;
 
     Ok(rec)
 }
 
 /// لیست اسکن‌ها + آخرین نتیجه‌ی هر اسکن (اگر باشد)
-pub async fn list_scans(pool: &DbPool) -> Result<Vec<ScanListRow>, sqlx::Error> {
-    let rows = sqlx::query_as::<_, ScanListRow>(
+pub async fn list_scans(pool: &DbPool, filter: &ScanListFilter) -> Result<Vec<ScanListRow>, sqlx::Error> {
+    list_scans_query(filter).build_query_as::<ScanListRow>().fetch_all(pool).await
+}
+
+/// The query behind [`list_scans`], for callers that stream its rows with `fetch`.
+pub fn list_scans_query(filter: &ScanListFilter) -> QueryBuilder<'static, Postgres> {
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
         r#"
-        WITH latest AS (
-            SELECT
-                r.scan_id,
-                r.completed_at,
-                r.issues,
-                ROW_NUMBER() OVER (
-                    PARTITION BY r.scan_id
-                    ORDER BY r.completed_at DESC NULLS LAST, r.id DESC
-                ) AS rn
-            FROM scan_results r
-        )
         SELECT
             s.id,
             s.url,
             s.status,
             s.created_at,
+            s.schedule_id,
+            s.target_id,
+            s.owner_id,
+            s.org_id,
+            s.critical_count,
+            s.high_count,
+            s.medium_count,
+            s.low_count,
+            s.info_count,
+            s.risk_score,
+            s.grade,
             l.completed_at,
-            l.issues
+        "#,
+    );
+
+    if filter.include_findings {
+        qb.push(
+            r#"
+            fa.findings
+        FROM scans s
+        LEFT JOIN LATERAL (
+            SELECT r.id, r.completed_at
+            FROM scan_results r
+            WHERE r.scan_id = s.id
+            ORDER BY r.completed_at DESC NULLS LAST, r.id DESC
+            LIMIT 1
+        ) l ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT jsonb_agg(
+                jsonb_strip_nulls(jsonb_build_object(
+                    'rule_id', f.rule_id,
+                    'severity', f.severity,
+                    'title', f.title,
+                    'location', f.location,
+                    'evidence', f.evidence
+                ))
+                ORDER BY f.position
+            ) AS findings
+            FROM findings f
+            WHERE f.scan_result_id = l.id
+        ) fa ON TRUE
+        WHERE TRUE
+            "#,
+        );
+    } else {
+        qb.push(
+            r#"
+            NULL::jsonb AS findings
+        FROM scans s
+        LEFT JOIN LATERAL (
+            SELECT MAX(r.completed_at) AS completed_at
+            FROM scan_results r
+            WHERE r.scan_id = s.id
+        ) l ON TRUE
+        WHERE TRUE
+            "#,
+        );
+    }
+
+    if let Some(org_id) = filter.org_id {
+        qb.push(" AND s.org_id = ").push_bind(org_id);
+    }
+    if let Some(target_id) = filter.target_id {
+        qb.push(" AND s.target_id = ").push_bind(target_id);
+    }
+    if let Some(owner_id) = filter.owner_id {
+        qb.push(" AND s.owner_id = ").push_bind(owner_id);
+    }
+    if !filter.statuses.is_empty() {
+        qb.push(" AND s.status = ANY(").push_bind(filter.statuses.clone()).push(")");
+    }
+    if let Some(text) = filter.url_contains.as_deref().filter(|t| !t.is_empty()) {
+        qb.push(" AND s.url ILIKE ")
+            .push_bind(format!("%{}%", like_escape(text)))
+            .push(" ESCAPE '\\'");
+    }
+    if let Some(from) = filter.created_from {
+        qb.push(" AND s.created_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.created_to {
+        qb.push(" AND s.created_at < ").push_bind(to);
+    }
+    if !filter.grades.is_empty() {
+        qb.push(" AND s.grade = ANY(").push_bind(filter.grades.clone()).push(")");
+    }
+    if let Some(min) = filter.min_risk_score {
+        qb.push(" AND s.risk_score >= ").push_bind(min);
+    }
+    if let Some(max) = filter.max_risk_score {
+        qb.push(" AND s.risk_score <= ").push_bind(max);
+    }
+
+    let sort_exprs = filter.sort.exprs(filter.descending);
+    if let Some(cursor) = &filter.after {
+        qb.push(format!(" AND ({}, s.id) ", sort_exprs.join(", ")))
+            .push(if filter.descending { "<" } else { ">" })
+            .push(" (");
+        filter.sort.push_key(&mut qb, &cursor.key, filter.descending);
+        qb.push(", ").push_bind(cursor.id).push(")");
+    }
+
+    let direction = if filter.descending { " DESC" } else { " ASC" };
+    qb.push(" ORDER BY ");
+    for expr in &sort_exprs {
+        qb.push(expr).push(direction).push(", ");
+    }
+    qb.push("s.id").push(direction);
+
+    if let Some(limit) = filter.limit {
+        qb.push(" LIMIT ").push_bind(limit);
+    }
+    qb
+}
+
+/// Scan history of one target within an organization, newest first.
+pub async fn list_scans_for_target(
+    pool: &DbPool,
+    target_id: Uuid,
+    org_id: Uuid,
+) -> Result<Vec<ScanListRow>, sqlx::Error> {
+    let filter = ScanListFilter {
+        org_id: Some(org_id),
+        target_id: Some(target_id),
+        ..ScanListFilter::default()
+    };
+    list_scans(pool, &filter).await
+}
+
+/// Ids of scans that have at least one result, optionally only for one
+/// organization, one target or only those not scored yet.
+pub async fn list_scorable_scan_ids(
+    pool: &DbPool,
+    org_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    only_unscored: bool,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT s.id
         FROM scans s
-        LEFT JOIN latest l
-            ON l.scan_id = s.id AND l.rn = 1
+        WHERE EXISTS (SELECT 1 FROM scan_results r WHERE r.scan_id = s.id)
+          AND ($1::uuid IS NULL OR s.org_id = $1)
+          AND ($2::uuid IS NULL OR s.target_id = $2)
+          AND (NOT $3 OR s.risk_score IS NULL)
         ORDER BY s.created_at DESC
         "#,
     )
+    .bind(org_id)
+    .bind(target_id)
+    .bind(only_unscored)
     .fetch_all(pool)
-    .await?;
-
-    Ok(rows)
+    .await
 }
 
-pub async fn get_scan(pool: &DbPool, id: Uuid) -> Result<Option<ScanRow>, sqlx::Error> {
-    let row = sqlx::query_as::<_, ScanRow>(
+/// Store severity counts, risk score and grade on a scan.
+pub async fn update_scan_risk(
+    pool: &DbPool,
+    id: Uuid,
+    counts: &SeverityCounts,
+    risk_score: i32,
+    grade: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
         r#"
-        SELECT id, url, status, created_at
-        FROM scans
+        UPDATE scans
+        SET critical_count = $2,
+            high_count = $3,
+            medium_count = $4,
+            low_count = $5,
+            info_count = $6,
+            risk_score = $7,
+            grade = $8
         WHERE id = $1
         "#,
     )
     .bind(id)
-    .fetch_optional(pool)
+    .bind(counts.critical)
+    .bind(counts.high)
+    .bind(counts.medium)
+    .bind(counts.low)
+    .bind(counts.info)
+    .bind(risk_score)
+    .bind(grade)
+    .execute(pool)
     .await?;
 
+    Ok(())
+}
+
+pub async fn get_scan(pool: &DbPool, id: Uuid) -> Result<Option<ScanRow>, sqlx::Error> {
+    let sql = format!("SELECT {SCAN_COLUMNS} FROM scans WHERE id = $1");
+    let row = sqlx::query_as::<_, ScanRow>(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row)
+}
+
+/// A scan, only if it belongs to `org_id`.
+pub async fn get_org_scan(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<Option<ScanRow>, sqlx::Error> {
+    let sql = format!("SELECT {SCAN_COLUMNS} FROM scans WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, ScanRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Most recent completed scan of the same target and organization created before `scan`.
+pub async fn get_previous_completed_scan(
+    pool: &DbPool,
+    scan: &ScanRow,
+) -> Result<Option<ScanRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {SCAN_COLUMNS}
+        FROM scans
+        WHERE target_id = $1
+          AND id <> $2
+          AND status = 'completed'
+          AND created_at < $3
+          AND org_id IS NOT DISTINCT FROM $4
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    );
+    let row = sqlx::query_as::<_, ScanRow>(&sql)
+        .bind(scan.target_id)
+        .bind(scan.id)
+        .bind(scan.created_at)
+        .bind(scan.org_id)
+        .fetch_optional(pool)
+        .await?;
+
     Ok(row)
 }
 
@@ -118,14 +573,17 @@ pub async fn update_scan_status(
     Ok(())
 }
 
-pub async fn insert_scan_result(
-    pool: &DbPool,
+pub async fn insert_scan_result<'e, E>(
+    executor: E,
     scan_id: Uuid,
     headers: Option<JsonValue>,
     ssl_grade: Option<String>,
     issues: JsonValue,
     completed_at: DateTime<Utc>,
-) -> Result<ScanResultRow, sqlx::Error> {
+) -> Result<ScanResultRow, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
     let row = sqlx::query_as::<_, ScanResultRow>(
         r#"
         INSERT INTO scan_results (scan_id, headers, ssl_grade, issues, completed_at)
@@ -138,7 +596,7 @@ pub async fn insert_scan_result(
     .bind(ssl_grade)
     .bind(issues)
     .bind(completed_at)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(row)
@@ -163,3 +621,341 @@ pub async fn get_latest_scan_result(
 
     Ok(row)
 }
+
+/// Write the findings of a scan result into the normalized `findings` table.
+pub async fn insert_findings<'e, E>(
+    executor: E,
+    scan_id: Uuid,
+    scan_result_id: Uuid,
+    findings: &[Finding],
+) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    if findings.is_empty() {
+        return Ok(0);
+    }
+
+    let positions: Vec<i32> = (0..findings.len() as i32).collect();
+    let rule_ids: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+    let severities: Vec<&str> = findings.iter().map(|f| f.severity.as_str()).collect();
+    let titles: Vec<&str> = findings.iter().map(|f| f.title.as_str()).collect();
+    let locations: Vec<&str> = findings.iter().map(|f| f.location.as_str()).collect();
+    let evidence: Vec<Option<&str>> = findings.iter().map(|f| f.evidence.as_deref()).collect();
+    let fingerprints: Vec<String> = findings.iter().map(Finding::fingerprint).collect();
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO findings
+            (scan_id, scan_result_id, position, rule_id, severity, title, location, evidence, fingerprint)
+        SELECT $1, $2, *
+        FROM UNNEST($3::int[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[], $9::text[])
+        "#,
+    )
+    .bind(scan_id)
+    .bind(scan_result_id)
+    .bind(&positions)
+    .bind(&rule_ids)
+    .bind(&severities)
+    .bind(&titles)
+    .bind(&locations)
+    .bind(&evidence)
+    .bind(&fingerprints)
+    .execute(executor)
+    .await?;
+
+    Ok(res.rows_affected())
+}
+
+/// Findings of the latest result of a scan, in the order the scanner reported them.
+pub async fn list_findings(pool: &DbPool, scan_id: Uuid) -> Result<Vec<FindingRow>, sqlx::Error> {
+    sqlx::query_as::<_, FindingRow>(
+        r#"
+        SELECT id, scan_id, scan_result_id, position, rule_id, severity, title, location,
+               evidence, fingerprint, created_at
+        FROM findings
+        WHERE scan_result_id = (
+            SELECT id
+            FROM scan_results
+            WHERE scan_id = $1
+            ORDER BY completed_at DESC NULLS LAST, id DESC
+            LIMIT 1
+        )
+        ORDER BY position
+        "#,
+    )
+    .bind(scan_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Findings of the latest result of a scan as domain values.
+pub async fn load_findings(pool: &DbPool, scan_id: Uuid) -> Result<Vec<Finding>, sqlx::Error> {
+    Ok(list_findings(pool, scan_id)
+        .await?
+        .iter()
+        .map(FindingRow::to_finding)
+        .collect())
+}
+
+/// Filters for [`search_findings`] and [`aggregate_findings`].
+#[derive(Debug, Clone, Default)]
+pub struct FindingSearch {
+    /// Only findings of this organization's scans; open/fixed is then judged
+    /// against its own latest scan of each target.
+    pub org_id: Option<Uuid>,
+    pub rule_ids: Vec<String>,
+    pub severities: Vec<String>,
+    /// Target hosts, lower-case (e.g. `example.com`).
+    pub hosts: Vec<String>,
+    /// `open`, `fixed` and/or `suppressed`.
+    pub statuses: Vec<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Case-insensitive substring matched against title and evidence.
+    pub text: Option<String>,
+    /// Only the most recent occurrence of each fingerprint per target, instead
+    /// of one row per scan that reported it.
+    pub latest_only: bool,
+}
+
+/// A finding returned by the cross-scan search.
+#[derive(Debug, Serialize, FromRow)]
+pub struct FindingSearchRow {
+    pub id: Uuid,
+    pub scan_id: Uuid,
+    pub target_id: Option<Uuid>,
+    pub target_url: Option<String>,
+    pub host: Option<String>,
+    pub rule_id: String,
+    pub severity: String,
+    pub title: String,
+    pub location: String,
+    pub evidence: Option<String>,
+    pub fingerprint: String,
+    /// `open` (still reported by the target's latest completed scan), `fixed`
+    /// (no longer reported) or `suppressed` (covered by an active suppression).
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One bucket of [`aggregate_findings`].
+#[derive(Debug, Serialize, FromRow)]
+pub struct FindingAggregateRow {
+    /// `total`, `severity`, `status`, `rule_id` or `host`.
+    pub dimension: String,
+    pub value: Option<String>,
+    pub count: i64,
+}
+
+/// Escape LIKE wildcards in user input.
+fn like_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Lower-cased host of `t.url`.
+const TARGET_HOST: &str = "lower(substring(t.url from '^[A-Za-z][A-Za-z0-9+.-]*://(?:[^@/]*@)?([^/:?#]+)'))";
+
+/// `WITH ... base AS (...)`: the findings matching `search`'s row filters, with
+/// their target, host and status. Filters go into the first pass over
+/// `findings` so statuses are only worked out for rows that can match.
+fn push_findings_base(qb: &mut QueryBuilder<'_, Postgres>, search: &FindingSearch) {
+    qb.push(
+        r#"
+        WITH latest_scan AS (
+            SELECT DISTINCT ON (s.target_id) s.target_id, s.id AS scan_id
+            FROM scans s
+            JOIN targets t ON t.id = s.target_id
+            WHERE s.status = 'completed'
+        "#,
+    );
+    push_scope_filters(qb, search);
+    qb.push(" ORDER BY s.target_id, s.created_at DESC), found AS (SELECT ");
+    if search.latest_only {
+        qb.push("DISTINCT ON (s.target_id, f.fingerprint) ");
+    }
+    qb.push(
+        r#"
+                f.id,
+                f.scan_id,
+                s.org_id,
+                s.target_id,
+                t.url AS target_url,
+                "#,
+    );
+    qb.push(TARGET_HOST).push(
+        r#" AS host,
+                f.rule_id,
+                f.severity,
+                f.title,
+                f.location,
+                f.evidence,
+                f.fingerprint,
+                f.created_at
+            FROM findings f
+            JOIN scans s ON s.id = f.scan_id
+            LEFT JOIN targets t ON t.id = s.target_id
+            WHERE TRUE
+        "#,
+    );
+    push_scope_filters(qb, search);
+    if search.latest_only {
+        // The other filters apply to the latest occurrence, after DISTINCT ON.
+        qb.push(" ORDER BY s.target_id, f.fingerprint, f.created_at DESC, f.id DESC");
+    } else {
+        push_finding_filters(qb, search, "f.");
+    }
+    qb.push(
+        r#"
+        ),
+        base AS (
+            SELECT
+                fd.id, fd.scan_id, fd.target_id, fd.target_url, fd.host, fd.rule_id, fd.severity,
+                fd.title, fd.location, fd.evidence, fd.fingerprint, fd.created_at,
+                CASE
+                    WHEN EXISTS (
+                        SELECT 1 FROM suppressions sp
+                        WHERE sp.rule_id = fd.rule_id
+                          AND sp.org_id = fd.org_id
+                          AND (sp.target_id IS NULL OR sp.target_id = fd.target_id)
+                          AND (sp.expires_at IS NULL OR sp.expires_at > NOW())
+                          AND fd.location LIKE glob_to_like(sp.location_pattern)
+                    ) THEN 'suppressed'
+                    WHEN EXISTS (
+                        SELECT 1
+                        FROM latest_scan ls
+                        JOIN findings lf ON lf.scan_id = ls.scan_id
+                        WHERE ls.target_id = fd.target_id AND lf.fingerprint = fd.fingerprint
+                    ) THEN 'open'
+                    ELSE 'fixed'
+                END AS status
+            FROM found fd
+        )
+        "#,
+    );
+}
+
+/// ` AND ...` conditions on the organization and target host, over `s` and `t`.
+/// They do not change which occurrence of a finding is the latest.
+fn push_scope_filters(qb: &mut QueryBuilder<'_, Postgres>, search: &FindingSearch) {
+    if let Some(org_id) = search.org_id {
+        qb.push(" AND s.org_id = ").push_bind(org_id);
+    }
+    if !search.hosts.is_empty() {
+        qb.push(" AND ")
+            .push(TARGET_HOST)
+            .push(" = ANY(")
+            .push_bind(search.hosts.clone())
+            .push(")");
+    }
+}
+
+/// ` AND ...` conditions on the finding itself; `col` prefixes its columns.
+fn push_finding_filters(qb: &mut QueryBuilder<'_, Postgres>, search: &FindingSearch, col: &str) {
+    if !search.rule_ids.is_empty() {
+        qb.push(format!(" AND {col}rule_id = ANY(")).push_bind(search.rule_ids.clone()).push(")");
+    }
+    if !search.severities.is_empty() {
+        qb.push(format!(" AND {col}severity = ANY(")).push_bind(search.severities.clone()).push(")");
+    }
+    if let Some(from) = search.from {
+        qb.push(format!(" AND {col}created_at >= ")).push_bind(from);
+    }
+    if let Some(to) = search.to {
+        qb.push(format!(" AND {col}created_at < ")).push_bind(to);
+    }
+    if let Some(text) = search.text.as_deref().filter(|t| !t.is_empty()) {
+        let pattern = format!("%{}%", like_escape(text));
+        qb.push(format!(" AND ({col}title ILIKE "))
+            .push_bind(pattern.clone())
+            .push(format!(" OR {col}evidence ILIKE "))
+            .push_bind(pattern)
+            .push(")");
+    }
+}
+
+/// ` AND ...` conditions over `base` columns not already applied by
+/// [`push_findings_base`].
+fn push_findings_filters(qb: &mut QueryBuilder<'_, Postgres>, search: &FindingSearch) {
+    if search.latest_only {
+        push_finding_filters(qb, search, "");
+    }
+    if !search.statuses.is_empty() {
+        qb.push(" AND status = ANY(").push_bind(search.statuses.clone()).push(")");
+    }
+}
+
+/// Search findings across all scans, newest first, using keyset pagination on
+/// `(created_at, id)`. Returns up to `limit` rows.
+pub async fn search_findings(
+    pool: &DbPool,
+    search: &FindingSearch,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<FindingSearchRow>, sqlx::Error> {
+    search_findings_query(search, after, Some(limit))
+        .build_query_as::<FindingSearchRow>()
+        .fetch_all(pool)
+        .await
+}
+
+/// The query behind [`search_findings`]; `limit: None` selects every match, for
+/// callers that stream its rows with `fetch`.
+pub fn search_findings_query(
+    search: &FindingSearch,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: Option<i64>,
+) -> QueryBuilder<'static, Postgres> {
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new("");
+    push_findings_base(&mut qb, search);
+    qb.push(
+        r#"
+        SELECT id, scan_id, target_id, target_url, host, rule_id, severity, title, location,
+               evidence, fingerprint, status, created_at
+        FROM base
+        WHERE TRUE
+        "#,
+    );
+    push_findings_filters(&mut qb, search);
+    if let Some((created_at, id)) = after {
+        qb.push(" AND (created_at, id) < (")
+            .push_bind(created_at)
+            .push(", ")
+            .push_bind(id)
+            .push(")");
+    }
+    qb.push(" ORDER BY created_at DESC, id DESC");
+    if let Some(limit) = limit {
+        qb.push(" LIMIT ").push_bind(limit);
+    }
+    qb
+}
+
+/// Counts over every finding matching `search` (ignoring pagination): the
+/// total, per severity, per status, and the 20 most frequent rules and hosts.
+pub async fn aggregate_findings(
+    pool: &DbPool,
+    search: &FindingSearch,
+) -> Result<Vec<FindingAggregateRow>, sqlx::Error> {
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new("");
+    push_findings_base(&mut qb, search);
+    // Read once and shared by every bucket below.
+    qb.push(", filtered AS MATERIALIZED (SELECT * FROM base WHERE TRUE");
+    push_findings_filters(&mut qb, search);
+    qb.push(
+        r#"
+        )
+        SELECT 'total' AS dimension, NULL::text AS value, COUNT(*) AS count FROM filtered
+        UNION ALL
+        SELECT 'severity', severity, COUNT(*) FROM filtered GROUP BY severity
+        UNION ALL
+        SELECT 'status', status, COUNT(*) FROM filtered GROUP BY status
+        UNION ALL
+        (SELECT 'rule_id', rule_id, COUNT(*) FROM filtered GROUP BY rule_id ORDER BY 3 DESC, 2 LIMIT 20)
+        UNION ALL
+        (SELECT 'host', host, COUNT(*) FROM filtered WHERE host IS NOT NULL GROUP BY host ORDER BY 3 DESC, 2 LIMIT 20)
+        "#,
+    );
+
+    qb.build_query_as::<FindingAggregateRow>().fetch_all(pool).await
+}
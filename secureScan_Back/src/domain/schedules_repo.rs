@@ -0,0 +1,213 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ScheduleRow {
+    pub id: Uuid,
+    pub org_id: Option<Uuid>,
+    /// User who created the schedule; its scans are attributed to them.
+    pub owner_id: Option<Uuid>,
+    pub name: Option<String>,
+    pub target_url: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub options: JsonValue,
+    pub enabled: bool,
+    pub missed_run_policy: String,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Fields accepted when creating or replacing a schedule.
+#[derive(Debug, Clone)]
+pub struct ScheduleInput {
+    pub name: Option<String>,
+    pub target_url: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub options: JsonValue,
+    pub enabled: bool,
+    pub missed_run_policy: String,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+const COLUMNS: &str = "id, org_id, owner_id, name, target_url, cron_expression, timezone, options, enabled, \
+    missed_run_policy, next_run_at, last_run_at, created_at, updated_at";
+
+pub async fn create_schedule(
+    pool: &DbPool,
+    org_id: Uuid,
+    owner_id: Uuid,
+    input: &ScheduleInput,
+) -> Result<ScheduleRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO schedules
+            (name, target_url, cron_expression, timezone, options, enabled, missed_run_policy, next_run_at,
+             owner_id, org_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, ScheduleRow>(&sql)
+        .bind(&input.name)
+        .bind(&input.target_url)
+        .bind(&input.cron_expression)
+        .bind(&input.timezone)
+        .bind(&input.options)
+        .bind(input.enabled)
+        .bind(&input.missed_run_policy)
+        .bind(input.next_run_at)
+        .bind(owner_id)
+        .bind(org_id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_schedules(pool: &DbPool, org_id: Uuid) -> Result<Vec<ScheduleRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM schedules WHERE org_id = $1 ORDER BY created_at DESC");
+    sqlx::query_as::<_, ScheduleRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_schedule(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<Option<ScheduleRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM schedules WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, ScheduleRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn update_schedule(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    input: &ScheduleInput,
+) -> Result<Option<ScheduleRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE schedules
+        SET name = $2,
+            target_url = $3,
+            cron_expression = $4,
+            timezone = $5,
+            options = $6,
+            enabled = $7,
+            missed_run_policy = $8,
+            next_run_at = $9,
+            updated_at = NOW()
+        WHERE id = $1 AND org_id = $10
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, ScheduleRow>(&sql)
+        .bind(id)
+        .bind(&input.name)
+        .bind(&input.target_url)
+        .bind(&input.cron_expression)
+        .bind(&input.timezone)
+        .bind(&input.options)
+        .bind(input.enabled)
+        .bind(&input.missed_run_policy)
+        .bind(input.next_run_at)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Delete a schedule. Scans it produced keep existing with `schedule_id` cleared.
+pub async fn delete_schedule(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM schedules WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Lock up to `limit` enabled schedules whose `next_run_at` has passed.
+///
+/// Rows are locked with `SKIP LOCKED` so that several backend instances can run
+/// the scheduler concurrently without claiming the same schedule twice.
+pub async fn lock_due_schedules(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ScheduleRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM schedules
+        WHERE enabled AND next_run_at IS NOT NULL AND next_run_at <= $1
+        ORDER BY next_run_at
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#
+    );
+    sqlx::query_as::<_, ScheduleRow>(&sql)
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+/// Advance a claimed schedule to its next occurrence.
+pub async fn mark_schedule_run(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: Uuid,
+    last_run_at: Option<DateTime<Utc>>,
+    next_run_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE schedules
+        SET last_run_at = COALESCE($2, last_run_at),
+            next_run_at = $3,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(last_run_at)
+    .bind(next_run_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Put a schedule back to the run it was just advanced past, so the next tick
+/// retries it. Does nothing if the schedule was changed in the meantime, i.e. its
+/// `next_run_at` is no longer `advanced_to`.
+pub async fn rewind_schedule_run(
+    pool: &DbPool,
+    id: Uuid,
+    scheduled_for: DateTime<Utc>,
+    last_run_at: Option<DateTime<Utc>>,
+    advanced_to: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE schedules
+        SET next_run_at = $2,
+            last_run_at = $3,
+            updated_at = NOW()
+        WHERE id = $1 AND next_run_at IS NOT DISTINCT FROM $4
+        "#,
+    )
+    .bind(id)
+    .bind(scheduled_for)
+    .bind(last_run_at)
+    .bind(advanced_to)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use crate::db::DbPool;
+use crate::domain::findings::Finding;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Why a finding is suppressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionState {
+    FalsePositive,
+    AcceptedRisk,
+    WontFix,
+}
+
+impl SuppressionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuppressionState::FalsePositive => "false_positive",
+            SuppressionState::AcceptedRisk => "accepted_risk",
+            SuppressionState::WontFix => "wont_fix",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SuppressionRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub rule_id: String,
+    pub target_id: Option<Uuid>,
+    pub location_pattern: String,
+    pub state: String,
+    pub reason: String,
+    pub author: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SuppressionRow {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|e| e > now)
+    }
+
+    /// Does this suppression cover `finding` reported on `target_id`?
+    pub fn matches(&self, finding: &Finding, target_id: Option<Uuid>, now: DateTime<Utc>) -> bool {
+        self.is_active(now)
+            && self.rule_id == finding.rule_id
+            && (self.target_id.is_none() || self.target_id == target_id)
+            && glob_match(&self.location_pattern, &finding.location)
+    }
+}
+
+/// Match `text` against a glob where `*` matches any run of characters and
+/// `?` matches exactly one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Fields accepted when creating a suppression.
+#[derive(Debug, Clone)]
+pub struct NewSuppression {
+    pub org_id: Uuid,
+    pub rule_id: String,
+    pub target_id: Option<Uuid>,
+    pub location_pattern: String,
+    pub state: SuppressionState,
+    pub reason: String,
+    pub author: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+const COLUMNS: &str =
+    "id, org_id, rule_id, target_id, location_pattern, state, reason, author, expires_at, created_at";
+
+pub async fn create_suppression(pool: &DbPool, new: &NewSuppression) -> Result<SuppressionRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO suppressions (rule_id, target_id, location_pattern, state, reason, author, expires_at, org_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, SuppressionRow>(&sql)
+        .bind(&new.rule_id)
+        .bind(new.target_id)
+        .bind(&new.location_pattern)
+        .bind(new.state.as_str())
+        .bind(&new.reason)
+        .bind(&new.author)
+        .bind(new.expires_at)
+        .bind(new.org_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Suppressions of `org_id`, optionally including expired ones. `None`, for
+/// scans without an organization, lists none.
+pub async fn list_suppressions(
+    pool: &DbPool,
+    org_id: Option<Uuid>,
+    include_expired: bool,
+) -> Result<Vec<SuppressionRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM suppressions
+        WHERE org_id = $1
+          AND ($2 OR expires_at IS NULL OR expires_at > NOW())
+        ORDER BY created_at DESC
+        "#
+    );
+    sqlx::query_as::<_, SuppressionRow>(&sql)
+        .bind(org_id)
+        .bind(include_expired)
+        .fetch_all(pool)
+        .await
+}
+
+/// Suppressions currently in effect for `org_id`.
+pub async fn list_active(pool: &DbPool, org_id: Option<Uuid>) -> Result<Vec<SuppressionRow>, sqlx::Error> {
+    list_suppressions(pool, org_id, false).await
+}
+
+pub async fn get_suppression(pool: &DbPool, id: Uuid) -> Result<Option<SuppressionRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM suppressions WHERE id = $1");
+    sqlx::query_as::<_, SuppressionRow>(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Scope of a suppression that stopped applying.
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiredSuppressionRow {
+    pub org_id: Uuid,
+    pub target_id: Option<Uuid>,
+}
+
+/// Mark suppressions that expired by `now` and were not yet handled, returning
+/// their scopes. Each expiry is returned once, even with several instances.
+pub async fn claim_expired(pool: &DbPool, now: DateTime<Utc>) -> Result<Vec<ExpiredSuppressionRow>, sqlx::Error> {
+    sqlx::query_as::<_, ExpiredSuppressionRow>(
+        r#"
+        UPDATE suppressions
+        SET expiry_rescored_at = $1
+        WHERE expires_at <= $1 AND expiry_rescored_at IS NULL
+        RETURNING org_id, target_id
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_suppression(pool: &DbPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM suppressions WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (pattern, location, matches). Findings search, counts and risk scores use
+    /// the SQL `glob_to_like` from migration 0010, so both must agree on these.
+    const CASES: &[(&str, &str, bool)] = &[
+        ("*", "https://example.com/a", true),
+        ("*", "", true),
+        ("https://example.com/*", "https://example.com/", true),
+        ("https://example.com/*", "https://example.com/a/b?c=d", true),
+        ("https://example.com/*", "https://example.org/a", false),
+        ("*/admin/*", "https://example.com/admin/users", true),
+        ("*/admin/*", "https://example.com/administrator", false),
+        ("https://example.com/?", "https://example.com/a", true),
+        ("https://example.com/?", "https://example.com/", false),
+        ("https://example.com/?", "https://example.com/ab", false),
+        ("??", "ab", true),
+        ("??", "a", false),
+        ("", "", true),
+        ("", "https://example.com/", false),
+        ("https://example.com/", "https://example.com/", true),
+        ("https://example.com/", "https://example.com/a", false),
+        ("https://example.com/100%", "https://example.com/100%", true),
+        ("https://example.com/100%", "https://example.com/1000", false),
+        ("https://example.com/a_b", "https://example.com/a_b", true),
+        ("https://example.com/a_b", "https://example.com/axb", false),
+        ("*_test", "my_test", true),
+        ("*_test", "mytest", false),
+        ("C:\\*", "C:\\dir", true),
+        ("C:\\*", "C:/dir", false),
+    ];
+
+    #[test]
+    fn glob_match_cases() {
+        for &(pattern, location, expected) in CASES {
+            assert_eq!(glob_match(pattern, location), expected, "{:?} vs {:?}", pattern, location);
+        }
+    }
+
+    /// Runs the migration's `glob_to_like` against the same cases when
+    /// `DATABASE_URL` points at a Postgres server; skipped otherwise.
+    #[tokio::test]
+    async fn glob_to_like_agrees_with_glob_match() {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL not set; skipping glob_to_like check");
+            return;
+        };
+        let migration = include_str!("../../migrations/0010_findings_search.up.sql");
+        let start = migration.find("CREATE OR REPLACE FUNCTION glob_to_like").unwrap();
+        let end = start + migration[start..].find("$$;").unwrap() + 3;
+
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query(&migration[start..end]).execute(&mut *tx).await.unwrap();
+        for &(pattern, location, expected) in CASES {
+            let (matched,): (bool,) = sqlx::query_as("SELECT $2 LIKE glob_to_like($1)")
+                .bind(pattern)
+                .bind(location)
+                .fetch_one(&mut *tx)
+                .await
+                .unwrap();
+            assert_eq!(matched, expected, "{:?} vs {:?}", pattern, location);
+        }
+        tx.rollback().await.unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use url::Url;
+use uuid::Uuid;
+
+/// A scanned site tracked over time; every scan belongs to one target of its
+/// organization.
+#[derive(Debug, Serialize, FromRow)]
+pub struct TargetRow {
+    pub id: Uuid,
+    pub org_id: Option<Uuid>,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    pub scan_count: i64,
+    pub last_scan_at: Option<DateTime<Utc>>,
+}
+
+/// Canonical form of a target URL so that e.g. `https://Example.com` and
+/// `https://example.com/` map to the same target.
+pub fn normalize_url(url: &str) -> String {
+    match Url::parse(url.trim()) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.trim().to_string(),
+    }
+}
+
+/// Targets with scan statistics. With `org_id`, only that organization's targets.
+pub async fn list_targets(pool: &DbPool, org_id: Option<Uuid>) -> Result<Vec<TargetRow>, sqlx::Error> {
+    sqlx::query_as::<_, TargetRow>(
+        r#"
+        SELECT
+            t.id,
+            t.org_id,
+            t.url,
+            t.created_at,
+            COUNT(s.id) AS scan_count,
+            MAX(s.created_at) AS last_scan_at
+        FROM targets t
+        LEFT JOIN scans s ON s.target_id = t.id
+        WHERE $1::uuid IS NULL OR t.org_id = $1
+        GROUP BY t.id
+        ORDER BY last_scan_at DESC NULLS LAST, t.created_at DESC
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// One target; `org_id` scopes it as in [`list_targets`].
+pub async fn get_target(pool: &DbPool, id: Uuid, org_id: Option<Uuid>) -> Result<Option<TargetRow>, sqlx::Error> {
+    sqlx::query_as::<_, TargetRow>(
+        r#"
+        SELECT
+            t.id,
+            t.org_id,
+            t.url,
+            t.created_at,
+            COUNT(s.id) AS scan_count,
+            MAX(s.created_at) AS last_scan_at
+        FROM targets t
+        LEFT JOIN scans s ON s.target_id = t.id
+        WHERE t.id = $1 AND ($2::uuid IS NULL OR t.org_id = $2)
+        GROUP BY t.id
+        "#,
+    )
+    .bind(id)
+    .bind(org_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// A target's identity, as needed to re-normalize its URL.
+#[derive(Debug, FromRow)]
+pub struct TargetUrlRow {
+    pub id: Uuid,
+    pub org_id: Option<Uuid>,
+    pub url: String,
+}
+
+/// Re-apply [`normalize_url`] to stored targets. Targets created before the
+/// backend normalized URLs (backfilled in SQL by an early migration) can differ
+/// in host case, default ports or escaping. A target whose normalized URL the
+/// organization already has is merged into that target. Returns how many
+/// targets were changed.
+pub async fn normalize_stored_urls(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let targets = sqlx::query_as::<_, TargetUrlRow>("SELECT id, org_id, url FROM targets ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+    let mut changed = 0;
+    for target in targets {
+        let url = normalize_url(&target.url);
+        if url != target.url {
+            move_to_url(pool, &target, &url).await?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Give `target` the URL `url`, merging it into the organization's target with
+/// that URL if there is one: its scans, suppressions and notification channel
+/// filters move over and it is deleted.
+async fn move_to_url(pool: &DbPool, target: &TargetUrlRow, url: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let existing = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT id FROM targets
+        WHERE org_id IS NOT DISTINCT FROM $1 AND url = $2 AND id <> $3
+        FOR UPDATE
+        "#,
+    )
+    .bind(target.org_id)
+    .bind(url)
+    .bind(target.id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(keep) = existing else {
+        sqlx::query("UPDATE targets SET url = $2 WHERE id = $1")
+            .bind(target.id)
+            .bind(url)
+            .execute(&mut *tx)
+            .await?;
+        return tx.commit().await;
+    };
+
+    sqlx::query("UPDATE scans SET target_id = $2 WHERE target_id = $1")
+        .bind(target.id)
+        .bind(keep)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE suppressions SET target_id = $2 WHERE target_id = $1")
+        .bind(target.id)
+        .bind(keep)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE notification_channels
+        SET target_ids = ARRAY(SELECT DISTINCT unnest(array_replace(target_ids, $1, $2)))
+        WHERE $1 = ANY(target_ids)
+        "#,
+    )
+    .bind(target.id)
+    .bind(keep)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM targets WHERE id = $1")
+        .bind(target.id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
@@ -7,5 +7,7 @@ pub struct User {
     pub id: Uuid,
     pub email: String,
     pub password_hash: String,
+    /// `None` until the user confirmed their address.
+    pub email_verified_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
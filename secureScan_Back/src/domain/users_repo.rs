@@ -10,7 +10,7 @@ pub async fn find_by_email(
 ) -> Result<Option<User>, sqlx::Error> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, email, password_hash, created_at
+        SELECT id, email, password_hash, email_verified_at, created_at
         FROM users
         WHERE email = $1
         "#,
@@ -22,23 +22,73 @@ pub async fn find_by_email(
     Ok(user)
 }
 
+/// Find user by id, return Option<User>
+pub async fn find_by_id<'e, E>(
+    executor: E,
+    id: Uuid,
+) -> Result<Option<User>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        SELECT id, email, password_hash, email_verified_at, created_at
+        FROM users
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(user)
+}
+
 /// Insert new user and return its id
-pub async fn insert_user(
-    pool: &DbPool,
+pub async fn insert_user<'e, E>(
+    executor: E,
     email: &str,
     password_hash: &str,
-) -> Result<Uuid, sqlx::Error> {
+) -> Result<Uuid, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
     let user = sqlx::query_as::<_, User>(
         r#"
         INSERT INTO users (email, password_hash)
         VALUES ($1, $2)
-        RETURNING id, email, password_hash, created_at
+        RETURNING id, email, password_hash, email_verified_at, created_at
         "#,
     )
     .bind(email)
     .bind(password_hash)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(user.id)
 }
+
+/// Record that the user confirmed their email address (no-op if already done)
+pub async fn mark_email_verified<'e, E>(executor: E, id: Uuid) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Replace the stored password hash
+pub async fn update_password<'e, E>(executor: E, id: Uuid, password_hash: &str) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(id)
+        .bind(password_hash)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DeliveryRow {
+    pub provider: String,
+    /// Integration the delivery came through; `None` for the legacy GitHub endpoint.
+    pub integration_id: Option<Uuid>,
+    pub delivery_id: String,
+    pub event: String,
+    pub repository: Option<String>,
+    pub scan_ids: Vec<Uuid>,
+    /// Repository targets of `scan_ids`, in the same order.
+    pub target_ids: Vec<Uuid>,
+    pub received_at: DateTime<Utc>,
+    /// Set while the handling failed part way; a redelivery then resumes it.
+    pub failed_at: Option<DateTime<Utc>>,
+}
+
+/// Identifies a delivery: delivery IDs are unique per sender only.
+#[derive(Debug, Clone, Copy)]
+pub struct DeliveryKey<'a> {
+    pub provider: &'a str,
+    pub integration_id: Option<Uuid>,
+    pub delivery_id: &'a str,
+}
+
+const COLUMNS: &str =
+    "provider, integration_id, delivery_id, event, repository, scan_ids, target_ids, received_at, failed_at";
+
+const MATCHES_KEY: &str = "provider = $1 AND integration_id IS NOT DISTINCT FROM $2 AND delivery_id = $3";
+
+/// Record a delivery as being handled, or take over one whose handling failed.
+/// Returns the repository targets already scanned for it, or `None` when it was
+/// handled (or is being handled) before.
+pub async fn claim_delivery(
+    pool: &DbPool,
+    key: DeliveryKey<'_>,
+    event: &str,
+    repository: Option<&str>,
+) -> Result<Option<Vec<Uuid>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Vec<Uuid>>(
+        r#"
+        INSERT INTO webhook_deliveries (provider, integration_id, delivery_id, event, repository)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (provider, delivery_id, (COALESCE(integration_id, '00000000-0000-0000-0000-000000000000'::uuid)))
+        DO UPDATE SET failed_at = NULL
+            WHERE webhook_deliveries.failed_at IS NOT NULL
+        RETURNING target_ids
+        "#,
+    )
+    .bind(key.provider)
+    .bind(key.integration_id)
+    .bind(key.delivery_id)
+    .bind(event)
+    .bind(repository)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Remember a scan queued for the delivery, so it is not started again.
+pub async fn record_delivery_scan(
+    pool: &DbPool,
+    key: DeliveryKey<'_>,
+    target_id: Uuid,
+    scan_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!(
+        "UPDATE webhook_deliveries SET scan_ids = array_append(scan_ids, $4), target_ids = array_append(target_ids, $5) \
+         WHERE {MATCHES_KEY}"
+    ))
+    .bind(key.provider)
+    .bind(key.integration_id)
+    .bind(key.delivery_id)
+    .bind(scan_id)
+    .bind(target_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Let a redelivery resume a delivery whose handling failed.
+pub async fn mark_delivery_failed(pool: &DbPool, key: DeliveryKey<'_>) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!("UPDATE webhook_deliveries SET failed_at = NOW() WHERE {MATCHES_KEY}"))
+        .bind(key.provider)
+        .bind(key.integration_id)
+        .bind(key.delivery_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_delivery(pool: &DbPool, key: DeliveryKey<'_>) -> Result<Option<DeliveryRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM webhook_deliveries WHERE {MATCHES_KEY}");
+    sqlx::query_as::<_, DeliveryRow>(&sql)
+        .bind(key.provider)
+        .bind(key.integration_id)
+        .bind(key.delivery_id)
+        .fetch_optional(pool)
+        .await
+}
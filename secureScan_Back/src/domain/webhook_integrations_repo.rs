@@ -0,0 +1,160 @@
+use crate::db::DbPool;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An inbound webhook endpoint of an organization.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct IntegrationRow {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub provider: String,
+    pub name: String,
+    /// Only returned when created or rotated.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// GitHub token posting commit statuses for this integration's scans, sealed.
+    #[serde(skip_serializing)]
+    pub report_token: Option<String>,
+    pub enabled: bool,
+    pub created_by: Option<Uuid>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const COLUMNS: &str =
+    "id, org_id, provider, name, secret, report_token, enabled, created_by, last_delivery_at, created_at, updated_at";
+
+pub async fn create_integration(
+    pool: &DbPool,
+    org_id: Uuid,
+    provider: &str,
+    name: &str,
+    secret: &str,
+    created_by: Uuid,
+) -> Result<IntegrationRow, sqlx::Error> {
+    let sql = format!(
+        r#"
+        INSERT INTO webhook_integrations (org_id, provider, name, secret, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(org_id)
+        .bind(provider)
+        .bind(name)
+        .bind(secret)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn list_integrations(pool: &DbPool, org_id: Uuid) -> Result<Vec<IntegrationRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM webhook_integrations WHERE org_id = $1 ORDER BY created_at");
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(org_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_integration(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<Option<IntegrationRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM webhook_integrations WHERE id = $1 AND org_id = $2");
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// An integration by ID alone, for authenticating its inbound deliveries.
+pub async fn find_integration(pool: &DbPool, id: Uuid) -> Result<Option<IntegrationRow>, sqlx::Error> {
+    let sql = format!("SELECT {COLUMNS} FROM webhook_integrations WHERE id = $1");
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn update_integration(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    name: &str,
+    enabled: bool,
+) -> Result<Option<IntegrationRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE webhook_integrations
+        SET name = $3, enabled = $4, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(name)
+        .bind(enabled)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn set_secret(pool: &DbPool, id: Uuid, org_id: Uuid, secret: &str) -> Result<Option<IntegrationRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE webhook_integrations
+        SET secret = $3, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(secret)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Store (or with `None` remove) the sealed commit status token.
+pub async fn set_report_token(
+    pool: &DbPool,
+    id: Uuid,
+    org_id: Uuid,
+    report_token: Option<&str>,
+) -> Result<Option<IntegrationRow>, sqlx::Error> {
+    let sql = format!(
+        r#"
+        UPDATE webhook_integrations
+        SET report_token = $3, updated_at = NOW()
+        WHERE id = $1 AND org_id = $2
+        RETURNING {COLUMNS}
+        "#
+    );
+    sqlx::query_as::<_, IntegrationRow>(&sql)
+        .bind(id)
+        .bind(org_id)
+        .bind(report_token)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn touch_last_delivery(pool: &DbPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE webhook_integrations SET last_delivery_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_integration(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM webhook_integrations WHERE id = $1 AND org_id = $2")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::findings::Finding;
+
+/// Postgres channel used to fan scan events out to other backend instances.
+pub const NOTIFY_CHANNEL: &str = "scan_events";
+
+/// NOTIFY payloads must stay below 8000 bytes; larger events are only delivered locally.
+const MAX_NOTIFY_PAYLOAD: usize = 7900;
+
+/// A single live update about a running scan.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScanEvent {
+    /// Scan moved to a new status (queued, running, completed, failed).
+    Status { status: String },
+    /// A scanner check started or finished.
+    Progress {
+        check: String,
+        completed: usize,
+        total: usize,
+    },
+    /// The scanner fetched a page.
+    PageCrawled { url: String, pages_crawled: usize },
+    /// A finding was discovered.
+    Finding { finding: Finding },
+}
+
+impl ScanEvent {
+    /// SSE `event:` name for this event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScanEvent::Status { .. } => "status",
+            ScanEvent::Progress { .. } => "progress",
+            ScanEvent::PageCrawled { .. } => "page_crawled",
+            ScanEvent::Finding { .. } => "finding",
+        }
+    }
+
+    /// True when this event is the last one a scan will ever emit.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ScanEvent::Status { status } if is_terminal_status(status))
+    }
+}
+
+pub fn is_terminal_status(status: &str) -> bool {
+    matches!(status, "completed" | "failed")
+}
+
+/// Event tagged with the scan it belongs to and the instance that produced it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanEventMessage {
+    pub scan_id: Uuid,
+    pub origin: Uuid,
+    pub event: ScanEvent,
+}
+
+/// In-process broadcast hub for scan events.
+///
+/// Workers publish into the hub; SSE handlers subscribe and filter by scan id.
+/// Every published event is also sent through `pg_notify` so that subscribers
+/// connected to other instances receive it via [`start_pg_listener`].
+#[derive(Clone)]
+pub struct EventHub {
+    tx: broadcast::Sender<ScanEventMessage>,
+    instance_id: Uuid,
+    pool: Option<DbPool>,
+}
+
+impl EventHub {
+    pub fn new(pool: Option<DbPool>) -> Self {
+        let capacity: usize = std::env::var("SCAN_EVENTS_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1024);
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            instance_id: Uuid::new_v4(),
+            pool,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScanEventMessage> {
+        self.tx.subscribe()
+    }
+
+    /// Publish an event locally and to other instances.
+    pub fn publish(&self, scan_id: Uuid, event: ScanEvent) {
+        let msg = ScanEventMessage {
+            scan_id,
+            origin: self.instance_id,
+            event,
+        };
+
+        // No subscribers is not an error.
+        let _ = self.tx.send(msg.clone());
+
+        let Some(pool) = self.pool.clone() else {
+            return;
+        };
+
+        let payload = match serde_json::to_string(&msg) {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::error!("Failed to serialize scan event for {}: {}", scan_id, e);
+                return;
+            }
+        };
+        if payload.len() > MAX_NOTIFY_PAYLOAD {
+            tracing::warn!("Scan event for {} too large for NOTIFY; delivered locally only", scan_id);
+            return;
+        }
+
+        tokio::spawn(async move {
+            if let Err(e) = sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(NOTIFY_CHANNEL)
+                .bind(payload)
+                .execute(&pool)
+                .await
+            {
+                tracing::error!("Failed to NOTIFY scan event for {}: {}", scan_id, e);
+            }
+        });
+    }
+
+    /// Re-broadcast an event received from another instance.
+    fn forward(&self, msg: ScanEventMessage) {
+        if msg.origin == self.instance_id {
+            return;
+        }
+        let _ = self.tx.send(msg);
+    }
+}
+
+/// Listens on the Postgres notification channel and forwards events produced
+/// by other instances into the local hub.
+pub fn start_pg_listener(pool: DbPool, hub: EventHub) {
+    tokio::spawn(async move {
+        loop {
+            let mut listener = match PgListener::connect_with(&pool).await {
+                Ok(l) => l,
+                Err(e) => {
+                    tracing::error!("Scan event listener failed to connect: {}", e);
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = listener.listen(NOTIFY_CHANNEL).await {
+                tracing::error!("Scan event listener failed to LISTEN: {}", e);
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                continue;
+            }
+
+            loop {
+                match listener.recv().await {
+                    Ok(notification) => {
+                        match serde_json::from_str::<ScanEventMessage>(notification.payload()) {
+                            Ok(msg) => hub.forward(msg),
+                            Err(e) => tracing::warn!("Ignoring malformed scan event: {}", e),
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Scan event listener error: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    });
+}
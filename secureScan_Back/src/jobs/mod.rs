@@ -1 +1,4 @@
+pub mod events;
+pub mod outbound_webhooks;
 pub mod queue;
+pub mod scheduler;
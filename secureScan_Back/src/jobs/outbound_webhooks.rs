@@ -0,0 +1,52 @@
+use chrono::Utc;
+
+use crate::db::DbPool;
+use crate::domain::outbound_webhooks_repo;
+use crate::services::outbound_webhooks::{self, OutboundWebhookConfig};
+
+/// Maximum number of deliveries claimed per tick.
+const BATCH_SIZE: i64 = 50;
+
+/// How long a claimed delivery is hidden from other instances while it is sent.
+const CLAIM_LEASE_SECS: i64 = 120;
+
+/// Starts the background dispatcher that sends due outbound webhook deliveries.
+///
+/// Every `OUTBOUND_WEBHOOK_INTERVAL_SECS` it claims pending deliveries whose next
+/// attempt is due and sends them; non-2xx responses and errors are retried with
+/// exponential backoff until `OUTBOUND_WEBHOOK_MAX_ATTEMPTS` is reached.
+pub fn start_dispatcher(pool: DbPool) {
+    let cfg = OutboundWebhookConfig::global();
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(cfg.interval);
+        loop {
+            ticker.tick().await;
+            match send_due_deliveries(&pool).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Webhook dispatcher: attempted {} delivery(ies)", n),
+                Err(e) => tracing::error!("Webhook dispatcher tick failed: {}", e),
+            }
+        }
+    });
+}
+
+async fn send_due_deliveries(pool: &DbPool) -> anyhow::Result<usize> {
+    let mut tx = pool.begin().await?;
+    let due = outbound_webhooks_repo::claim_due_deliveries(
+        &mut tx,
+        Utc::now(),
+        chrono::Duration::seconds(CLAIM_LEASE_SECS),
+        BATCH_SIZE,
+    )
+    .await?;
+    tx.commit().await?;
+
+    let attempts = due.iter().map(|delivery| outbound_webhooks::attempt(pool, delivery));
+    for (delivery, result) in due.iter().zip(futures_util::future::join_all(attempts).await) {
+        if let Err(e) = result {
+            tracing::error!("Webhook dispatcher: could not record attempt of delivery {}: {}", delivery.id, e);
+        }
+    }
+    Ok(due.len())
+}
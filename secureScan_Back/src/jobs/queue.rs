@@ -4,8 +4,11 @@ use uuid::Uuid;
 use url::Url;
 
 use crate::db::DbPool;
+use crate::domain::findings::Finding;
 use crate::domain::scans_repo;
-use crate::scanner;
+use crate::jobs::events::{EventHub, ScanEvent};
+use crate::scanner::{self, ScanOptions};
+use crate::services::{commit_reports, notifications, outbound_webhooks, risk};
 use chrono::Utc;
 use serde_json::Value as JsonValue;
 
@@ -15,6 +18,7 @@ use serde_json::Value as JsonValue;
 pub struct ScanJob {
     pub id: Uuid,
     pub target: Url,
+    pub options: ScanOptions,
 }
 
 /// Thin wrapper around an mpsc sender to enqueue jobs.
@@ -46,10 +50,38 @@ impl ScanQueue {
     }
 }
 
+/// Persist a status change and broadcast it to live subscribers.
+async fn set_status(pool: &DbPool, hub: &EventHub, id: Uuid, status: &str) -> Result<(), sqlx::Error> {
+    scans_repo::update_scan_status(pool, id, status).await?;
+    hub.publish(id, ScanEvent::Status { status: status.to_string() });
+    Ok(())
+}
+
+/// Write a scan result and its findings in one transaction.
+async fn save_result(
+    pool: &DbPool,
+    id: Uuid,
+    headers: Option<JsonValue>,
+    issues: JsonValue,
+    findings: &[Finding],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let result_row = scans_repo::insert_scan_result(&mut *tx, id, headers, None, issues, Utc::now()).await?;
+    // normalized copy of the findings for cross-scan queries
+    scans_repo::insert_findings(&mut *tx, id, result_row.id, findings).await?;
+    tx.commit().await
+}
+
 /// Starts worker dispatcher that receives jobs and processes them concurrently.
-/// Workers update scan status and insert scan_results into the DB.
+/// Workers update scan status and insert scan_results into the DB, and publish
+/// live progress into the event hub.
 #[allow(dead_code)]
-pub fn start_workers_db(pool: DbPool, mut rx: mpsc::Receiver<ScanJob>, concurrency: usize) {
+pub fn start_workers_db(
+    pool: DbPool,
+    hub: EventHub,
+    mut rx: mpsc::Receiver<ScanJob>,
+    concurrency: usize,
+) {
     let sem = Arc::new(Semaphore::new(concurrency));
 
     // Dispatcher loop in background
@@ -57,17 +89,21 @@ pub fn start_workers_db(pool: DbPool, mut rx: mpsc::Receiver<ScanJob>, concurren
         while let Some(job) = rx.recv().await {
             let permit = sem.clone().acquire_owned().await.unwrap();
             let pool_cloned = pool.clone();
+            let hub = hub.clone();
 
             tokio::spawn(async move {
                 tracing::info!("Worker: starting scan {} -> {}", job.id, job.target);
 
                 // 1) mark as running in DB
-                if let Err(e) = scans_repo::update_scan_status(&pool_cloned, job.id, "running").await {
+                if let Err(e) = set_status(&pool_cloned, &hub, job.id, "running").await {
                     tracing::error!("Failed to set running status for {}: {}", job.id, e);
                 }
+                commit_reports::report_started(&pool_cloned, job.id).await;
 
                 // 2) call real scanner
-                match scanner::scan_target(job.target.as_str()).await {
+                let scan_id = job.id;
+                let on_event = |event: ScanEvent| hub.publish(scan_id, event);
+                match scanner::scan_target_with_events(job.target.as_str(), &job.options, &on_event).await {
                     Ok(res) => {
                         // Convert headers map to JSON
                         let headers_json: Option<JsonValue> = match serde_json::to_value(&res.headers) {
@@ -78,7 +114,7 @@ pub fn start_workers_db(pool: DbPool, mut rx: mpsc::Receiver<ScanJob>, concurren
                             }
                         };
 
-                        // Convert findings (Vec<String>) to JSON value
+                        // Convert findings to JSON value
                         let issues_json: JsonValue = match serde_json::to_value(&res.security_findings) {
                             Ok(v) => v,
                             Err(e) => {
@@ -87,38 +123,40 @@ pub fn start_workers_db(pool: DbPool, mut rx: mpsc::Receiver<ScanJob>, concurren
                             }
                         };
 
-                        let completed_at = Utc::now();
-
-                        // Insert scan result into DB
-                        match scans_repo::insert_scan_result(
-                            &pool_cloned,
-                            job.id,
-                            headers_json,
-                            None,
-                            issues_json,
-                            completed_at,
-                        )
-                        .await
-                        {
-                            Ok(_) => {
+                        // Insert scan result and its findings together: a result without
+                        // findings would read as a clean scan
+                        match save_result(&pool_cloned, job.id, headers_json, issues_json, &res.security_findings).await {
+                            Ok(()) => {
+                                // compute severity counts + risk score before announcing completion
+                                if let Err(e) = risk::score_scan(&pool_cloned, job.id).await {
+                                    tracing::error!("Failed to score scan {}: {}", job.id, e);
+                                }
+
                                 // update status to completed
-                                if let Err(e) = scans_repo::update_scan_status(&pool_cloned, job.id, "completed").await {
+                                if let Err(e) = set_status(&pool_cloned, &hub, job.id, "completed").await {
                                     tracing::error!("Failed to set completed status for {}: {}", job.id, e);
                                 }
                                 tracing::info!("Worker: completed scan {}", job.id);
                             }
                             Err(e) => {
                                 tracing::error!("Failed to insert scan result for {}: {}", job.id, e);
-                                let _ = scans_repo::update_scan_status(&pool_cloned, job.id, "failed").await;
+                                let _ = set_status(&pool_cloned, &hub, job.id, "failed").await;
                             }
                         }
                     }
                     Err(err) => {
                         tracing::error!("Worker: scan {} failed: {}", job.id, err);
-                        let _ = scans_repo::update_scan_status(&pool_cloned, job.id, "failed").await;
+                        let _ = set_status(&pool_cloned, &hub, job.id, "failed").await;
                     }
                 }
 
+                // report the result on the commit that triggered the scan, if any
+                commit_reports::report_finished(&pool_cloned, job.id).await;
+                // notify the organization's outbound webhooks
+                outbound_webhooks::scan_finished(&pool_cloned, job.id).await;
+                // post to the organization's Slack / Teams channels
+                notifications::scan_finished(&pool_cloned, job.id).await;
+
                 drop(permit);
             });
         }
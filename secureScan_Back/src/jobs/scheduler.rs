@@ -0,0 +1,269 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::schedules_repo::{self, ScheduleRow};
+use crate::domain::scans_repo;
+use crate::domain::suppressions_repo;
+use crate::jobs::queue::{ScanJob, ScanQueue};
+use crate::scanner::ScanOptions;
+use crate::services::risk;
+
+/// Maximum number of schedules claimed per tick.
+const BATCH_SIZE: i64 = 100;
+
+/// What to do with a run whose time passed while the scheduler was down.
+pub const MISSED_RUN_POLICIES: [&str; 2] = ["skip", "run_once"];
+
+/// Rewrite one comma-separated item of a classic day-of-week field, where 0 and 7
+/// are Sunday, into the `cron` crate's numbering, where 1 is Sunday and 7 Saturday.
+/// Named days and `*` mean the same in both and are kept.
+fn translate_dow_item(item: &str) -> Result<String, String> {
+    if item.starts_with('*') || item.chars().any(|c| c.is_ascii_alphabetic()) {
+        return Ok(item.to_string());
+    }
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)),
+        None => (item, Some(1)),
+    };
+    let step = step.ok_or_else(|| format!("invalid day-of-week step in {:?}", item))?;
+    let day = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|d| *d <= 7)
+            .ok_or_else(|| format!("day of week must be 0-7 or a name, got {:?}", s))
+    };
+    let (first, last) = match range.split_once('-') {
+        Some((a, b)) => (day(a)?, day(b)?),
+        // `a/n` runs from `a` to the end of the week
+        None if item.contains('/') => (day(range)?, 6),
+        None => (day(range)?, day(range)?),
+    };
+    if first > last {
+        return Err(format!("invalid day-of-week range {:?}", item));
+    }
+    let mut days: Vec<u32> = (first..=last).step_by(step as usize).map(|d| d % 7 + 1).collect();
+    days.sort_unstable();
+    days.dedup();
+    Ok(days.iter().map(u32::to_string).collect::<Vec<_>>().join(","))
+}
+
+/// Parse a cron expression. Standard 5-field expressions (`min hour dom mon dow`)
+/// are accepted as well as the 6/7-field form with seconds (and year). Numeric
+/// days of week follow classic cron: 0 or 7 is Sunday, 1 Monday.
+pub fn parse_cron(expr: &str) -> Result<cron::Schedule, String> {
+    let mut fields: Vec<String> = expr.split_whitespace().map(str::to_string).collect();
+    if fields.len() == 5 {
+        fields.insert(0, "0".to_string());
+    }
+    if let Some(dow) = fields.get_mut(5) {
+        let items = dow.split(',').map(translate_dow_item).collect::<Result<Vec<_>, _>>()?;
+        *dow = items.join(",");
+    }
+    cron::Schedule::from_str(&fields.join(" ")).map_err(|e| format!("invalid cron expression: {}", e))
+}
+
+pub fn parse_timezone(tz: &str) -> Result<Tz, String> {
+    tz.parse::<Tz>().map_err(|_| format!("unknown timezone: {}", tz))
+}
+
+/// Next occurrence strictly after `after`, evaluated in the schedule's timezone.
+pub fn next_run_after(cron_expression: &str, timezone: &str, after: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    let schedule = parse_cron(cron_expression)?;
+    let tz = parse_timezone(timezone)?;
+    Ok(schedule
+        .after(&after.with_timezone(&tz))
+        .next()
+        .map(|t| t.with_timezone(&Utc)))
+}
+
+fn env_secs(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Starts the background scheduler that turns due schedules into queued scans.
+///
+/// Every `SCHEDULER_INTERVAL_SECS` (default 30) it claims due schedules, advances
+/// them to their next occurrence and enqueues a scan for each. A run that is more
+/// than `SCHEDULER_MISSED_GRACE_SECS` (default 300) late is considered missed,
+/// e.g. after downtime; the schedule's `missed_run_policy` then decides whether
+/// it is skipped or run once (multiple missed occurrences never run more than once).
+/// Each tick also re-scores the scans covered by suppressions that expired since.
+pub fn start_scheduler(pool: DbPool, queue: ScanQueue) {
+    let interval = Duration::from_secs(env_secs("SCHEDULER_INTERVAL_SECS", 30).max(1));
+    let grace = ChronoDuration::seconds(env_secs("SCHEDULER_MISSED_GRACE_SECS", 300) as i64);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match run_due_schedules(&pool, &queue, grace).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Scheduler: enqueued {} scheduled scan(s)", n),
+                Err(e) => tracing::error!("Scheduler tick failed: {}", e),
+            }
+            if let Err(e) = rescore_expired_suppressions(&pool).await {
+                tracing::error!("Checking expired suppressions failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Stored counts, risk scores and grades leave out suppressed findings; once a
+/// suppression expires, re-score the scans it covered so they count again.
+async fn rescore_expired_suppressions(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let expired = suppressions_repo::claim_expired(pool, Utc::now()).await?;
+    let mut scopes: Vec<(Uuid, Option<Uuid>)> = expired.into_iter().map(|s| (s.org_id, s.target_id)).collect();
+    scopes.sort();
+    scopes.dedup();
+    for (org_id, target_id) in scopes {
+        risk::spawn_rescore(pool.clone(), Some(org_id), target_id, false);
+    }
+    Ok(())
+}
+
+async fn run_due_schedules(pool: &DbPool, queue: &ScanQueue, grace: ChronoDuration) -> anyhow::Result<usize> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+    let due = schedules_repo::lock_due_schedules(&mut tx, now, BATCH_SIZE).await?;
+
+    let mut to_run = Vec::new();
+    for schedule in due {
+        let next = match next_run_after(&schedule.cron_expression, &schedule.timezone, now) {
+            Ok(next) => next,
+            Err(e) => {
+                // Stop retrying a schedule that can no longer be evaluated.
+                tracing::error!("Scheduler: disabling schedule {}: {}", schedule.id, e);
+                None
+            }
+        };
+
+        let scheduled_for = schedule.next_run_at.unwrap_or(now);
+        let missed = now - scheduled_for > grace;
+        let run = !missed || schedule.missed_run_policy == "run_once";
+        if missed {
+            tracing::warn!(
+                "Scheduler: schedule {} missed run at {} ({})",
+                schedule.id,
+                scheduled_for,
+                if run { "running once now" } else { "skipping" }
+            );
+        }
+
+        schedules_repo::mark_schedule_run(&mut tx, schedule.id, run.then_some(now), next).await?;
+        if run {
+            to_run.push((schedule, scheduled_for, next));
+        }
+    }
+    tx.commit().await?;
+
+    let mut enqueued = 0;
+    for (schedule, scheduled_for, next) in to_run {
+        match enqueue_scheduled_scan(pool, queue, &schedule).await {
+            Ok(()) => enqueued += 1,
+            Err(e) => {
+                // The schedule was already advanced; rewind it so the run is retried
+                // on the next tick instead of being lost.
+                tracing::error!("Scheduler: failed to start scan for schedule {}, retrying: {}", schedule.id, e);
+                if let Err(e) =
+                    schedules_repo::rewind_schedule_run(pool, schedule.id, scheduled_for, schedule.last_run_at, next)
+                        .await
+                {
+                    tracing::error!("Scheduler: failed to rewind schedule {}: {}", schedule.id, e);
+                }
+            }
+        }
+    }
+    Ok(enqueued)
+}
+
+async fn enqueue_scheduled_scan(pool: &DbPool, queue: &ScanQueue, schedule: &ScheduleRow) -> anyhow::Result<()> {
+    let target = Url::parse(&schedule.target_url)?;
+    let options: ScanOptions = serde_json::from_value(schedule.options.clone()).unwrap_or_else(|e| {
+        tracing::warn!("Scheduler: ignoring invalid options on schedule {}: {}", schedule.id, e);
+        ScanOptions::default()
+    });
+
+    let row = scans_repo::create_scan(
+        pool,
+        &scans_repo::NewScan {
+            url: schedule.target_url.clone(),
+            options: schedule.options.clone(),
+            schedule_id: Some(schedule.id),
+            owner_id: schedule.owner_id,
+            org_id: schedule.org_id,
+        },
+    )
+    .await?;
+    if let Err(e) = queue
+        .enqueue(ScanJob {
+            id: row.id,
+            target,
+            options,
+        })
+        .await
+    {
+        // Nothing will pick this scan up; don't leave it pending forever.
+        scans_repo::update_scan_status(pool, row.id, "failed").await?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone, Weekday};
+
+    /// Weekdays of the next seven runs of a daily-at-09:00 expression with `dow`.
+    fn weekdays(dow: &str) -> Vec<Weekday> {
+        let schedule = parse_cron(&format!("0 9 * * {}", dow)).unwrap();
+        // A Monday.
+        let start = Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap();
+        let mut days: Vec<Weekday> = schedule.after(&start).take(7).map(|t| t.weekday()).collect();
+        days.sort_by_key(|d| d.num_days_from_sunday());
+        days.dedup();
+        days
+    }
+
+    #[test]
+    fn numeric_days_follow_classic_cron() {
+        use Weekday::*;
+        assert_eq!(weekdays("0"), vec![Sun]);
+        assert_eq!(weekdays("7"), vec![Sun]);
+        assert_eq!(weekdays("1"), vec![Mon]);
+        assert_eq!(weekdays("6"), vec![Sat]);
+        assert_eq!(weekdays("1-5"), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(weekdays("0,6"), vec![Sun, Sat]);
+        assert_eq!(weekdays("5-7"), vec![Sun, Fri, Sat]);
+        assert_eq!(weekdays("0-6/3"), vec![Sun, Wed, Sat]);
+        assert_eq!(weekdays("1/2"), vec![Mon, Wed, Fri]);
+        assert_eq!(weekdays("MON-FRI"), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(weekdays("*"), vec![Sun, Mon, Tue, Wed, Thu, Fri, Sat]);
+    }
+
+    #[test]
+    fn invalid_days_are_rejected() {
+        for dow in ["8", "5-1", "1-9", "0/0", "x-1"] {
+            assert!(parse_cron(&format!("0 9 * * {}", dow)).is_err(), "{}", dow);
+        }
+    }
+
+    #[test]
+    fn seconds_field_is_optional() {
+        let start = Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap();
+        let five = next_run_after("30 2 * * *", "UTC", start).unwrap();
+        let six = next_run_after("0 30 2 * * *", "UTC", start).unwrap();
+        assert_eq!(five, Some(Utc.with_ymd_and_hms(2026, 10, 19, 2, 30, 0).unwrap()));
+        assert_eq!(five, six);
+        let berlin = next_run_after("30 2 * * *", "Europe/Berlin", start).unwrap();
+        assert_eq!(berlin, Some(Utc.with_ymd_and_hms(2026, 10, 19, 0, 30, 0).unwrap()));
+    }
+}
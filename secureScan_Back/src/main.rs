@@ -6,7 +6,6 @@ use actix_web::{
     middleware,
     web as aw_web,
     App,
-    HttpRequest,
     HttpResponse,
     HttpServer,
 };
@@ -26,11 +25,13 @@ mod scanner;
 mod db;
 mod services;
 
+use crate::jobs::events::{self as scan_events, EventHub};
 use crate::jobs::queue::{ScanQueue, start_workers_db};
-use crate::web::handlers::webhook::github_webhook;
+use crate::jobs::outbound_webhooks::start_dispatcher;
+use crate::jobs::scheduler::start_scheduler;
 use crate::web::handlers::auth as auth_handlers;
 use tokio::sync::mpsc;
-use tracing::{info, error};
+use tracing::{info, warn, error};
 
 #[get("/api/health")]
 async fn health() -> HttpResponse {
@@ -144,6 +145,31 @@ async fn main() -> std::io::Result<()> {
 
     let allowed = allowed_origins.clone();
 
+    // Encrypt TOTP secrets stored in plaintext by earlier versions
+    match services::mfa::seal_stored_secrets(&pool).await {
+        Ok(0) => {}
+        Ok(n) => info!(count = n, "encrypted stored TOTP secrets"),
+        Err(e) => error!(%e, "could not encrypt stored TOTP secrets"),
+    }
+
+    // Merge targets whose URLs were stored before the backend normalized them;
+    // suppressions of merged targets then apply to more scans.
+    match domain::targets_repo::normalize_stored_urls(&pool).await {
+        Ok(0) => {}
+        Ok(n) => {
+            info!(count = n, "normalized stored target URLs");
+            services::risk::spawn_rescore(pool.clone(), None, None, false);
+        }
+        Err(e) => error!(%e, "could not normalize stored target URLs"),
+    }
+
+    // Score scans stored before risk scoring existed
+    services::risk::spawn_rescore(pool.clone(), None, None, true);
+
+    // Live scan event hub, shared across instances via Postgres LISTEN/NOTIFY
+    let event_hub = EventHub::new(Some(pool.clone()));
+    scan_events::start_pg_listener(pool.clone(), event_hub.clone());
+
     // Create job queue and start DB-backed workers
     let (tx, rx) = mpsc::channel(128);
     let scan_queue = ScanQueue::new(tx);
@@ -151,7 +177,43 @@ async fn main() -> std::io::Result<()> {
         .ok()
         .and_then(|s| s.parse().ok())
         .unwrap_or(4);
-    start_workers_db(pool.clone(), rx, concurrency);
+    start_workers_db(pool.clone(), event_hub.clone(), rx, concurrency);
+
+    // Password policy; loads the breached-password list up front instead of on the first signup
+    let policy = tokio::task::spawn_blocking(services::password_policy::PasswordPolicy::global)
+        .await
+        .map_err(|e| std::io::Error::other(format!("password policy init failed: {}", e)))?;
+    info!(min = policy.min_length, max = policy.max_length, "password policy loaded");
+
+    // Behind a reverse proxy without trusted headers, every client shares the proxy's address
+    if !web::auth::trust_proxy_headers() {
+        warn!(
+            "TRUST_PROXY_HEADERS is off: login attempts relayed by a proxy are not throttled per IP. \
+             Set TRUST_PROXY_HEADERS=true behind a reverse proxy that sets X-Forwarded-For"
+        );
+    }
+
+    // Outgoing email (verification, password reset)
+    let mailer = match services::mailer::from_env() {
+        Ok(m) => m,
+        Err(e) => {
+            error!(%e, "mailer initialization failed");
+            return Err(std::io::Error::other(format!("mailer init failed: {}", e)));
+        }
+    };
+
+    // OpenID Connect single sign-on, when OIDC_ISSUER is set
+    let oidc = services::oidc::OidcClient::from_env().map(aw_web::Data::new);
+    match &oidc {
+        Some(o) => info!(issuer = %o.config.issuer, "single sign-on enabled"),
+        None => info!("single sign-on disabled (OIDC_ISSUER not set)"),
+    }
+
+    // Turn due recurring schedules into queued scans
+    start_scheduler(pool.clone(), scan_queue.clone());
+
+    // Send outbound webhook deliveries, retrying failed ones with backoff
+    start_dispatcher(pool.clone());
 
     let server = HttpServer::new(move || {
         let origins: Vec<&str> = allowed
@@ -162,8 +224,15 @@ async fn main() -> std::io::Result<()> {
 
         let mut cors = Cors::default()
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
-            .allowed_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION])
-            .expose_headers(vec![header::CONTENT_TYPE])
+            .allowed_headers(vec![
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                header::HeaderName::from_static("x-org-id"),
+            ])
+            .expose_headers(vec![
+                header::CONTENT_TYPE,
+                header::HeaderName::from_static("x-next-cursor"),
+            ])
             .supports_credentials()
             .max_age(3600);
 
@@ -176,9 +245,17 @@ async fn main() -> std::io::Result<()> {
             .wrap(TimeoutMiddleware { timeout: Duration::from_secs(10) })
             .wrap(cors)
             .app_data(aw_web::JsonConfig::default().limit(5 * 1024 * 1024))
+            .app_data(aw_web::PayloadConfig::new(5 * 1024 * 1024))
             .app_data(ready_flag_data.clone())
             .app_data(aw_web::Data::new(pool.clone()))
             .app_data(aw_web::Data::new(scan_queue.clone()))
+            .app_data(aw_web::Data::from(mailer.clone()))
+            .configure(|cfg| {
+                if let Some(oidc) = &oidc {
+                    cfg.app_data(oidc.clone());
+                }
+            })
+            .app_data(aw_web::Data::new(event_hub.clone()))
             .service(health)
             .service(healthz)
             .service(readiness)
@@ -186,19 +263,118 @@ async fn main() -> std::io::Result<()> {
             // 🔐 AUTH ENDPOINTS
             .service(auth_handlers::register)
             .service(auth_handlers::login)
+            .service(auth_handlers::login_mfa)
+            .service(auth_handlers::oidc_config)
+            .service(auth_handlers::oidc_login)
+            .service(auth_handlers::oidc_callback)
+            .service(auth_handlers::refresh)
+            .service(auth_handlers::logout)
+            .service(auth_handlers::me)
+            .service(auth_handlers::login_attempts)
+            .service(auth_handlers::verify_email)
+            .service(auth_handlers::resend_verification)
+            .service(auth_handlers::request_password_reset)
+            .service(auth_handlers::confirm_password_reset)
+            .service(auth_handlers::mfa_status)
+            .service(auth_handlers::mfa_enroll)
+            .service(auth_handlers::mfa_confirm)
+            .service(auth_handlers::mfa_recovery_codes)
+            .service(auth_handlers::mfa_disable)
+
+            // 🔑 API KEYS
+            .service(web::handlers::api_keys::create_api_key)
+            .service(web::handlers::api_keys::list_api_keys)
+            .service(web::handlers::api_keys::revoke_api_key)
+
+            // 🏢 ORGANIZATIONS
+            .service(web::handlers::orgs::create_org)
+            .service(web::handlers::orgs::list_orgs)
+            .service(web::handlers::orgs::get_org)
+            .service(web::handlers::orgs::rename_org)
+            .service(web::handlers::orgs::set_mfa_policy)
+            .service(web::handlers::orgs::list_members)
+            .service(web::handlers::orgs::update_member)
+            .service(web::handlers::orgs::remove_member)
+            .service(web::handlers::orgs::create_invitation)
+            .service(web::handlers::orgs::list_invitations)
+            .service(web::handlers::orgs::delete_invitation)
+            .service(web::handlers::orgs::list_group_mappings)
+            .service(web::handlers::orgs::upsert_group_mapping)
+            .service(web::handlers::orgs::delete_group_mapping)
+            .service(web::handlers::orgs::accept_invitation)
 
             // 🔥 FULL SCAN ENDPOINTS
             .service(web::handlers::scans::mock_scan)
             .service(web::handlers::scans::start_scan)
             .service(web::handlers::scans::list_scans)
             .service(web::handlers::scans::get_scan)
-
-            .route(
-                "/api/ci/webhook/github",
-                aw_web::post().to(|req: HttpRequest, body: aw_web::Bytes| async move {
-                    github_webhook(req, body).await
-                }),
-            )
+            .service(web::handlers::scans::diff_scan)
+            .service(web::handlers::reports::sarif_report)
+            .service(web::handlers::reports::html_report)
+            .service(web::handlers::reports::junit_report)
+            .service(web::handlers::events::scan_events)
+
+            // 🎯 TARGET ENDPOINTS
+            .service(web::handlers::targets::list_targets)
+            .service(web::handlers::targets::get_target)
+            .service(web::handlers::targets::list_target_scans)
+
+            // 🔎 FINDINGS SEARCH
+            .service(web::handlers::findings::search_findings)
+
+            // 📦 BULK EXPORTS
+            .service(web::handlers::exports::export_scans)
+            .service(web::handlers::exports::export_findings)
+
+            // 🙈 SUPPRESSION ENDPOINTS
+            .service(web::handlers::suppressions::create_suppression)
+            .service(web::handlers::suppressions::list_suppressions)
+            .service(web::handlers::suppressions::get_suppression)
+            .service(web::handlers::suppressions::delete_suppression)
+
+            // ⏰ SCHEDULE ENDPOINTS
+            .service(web::handlers::schedules::create_schedule)
+            .service(web::handlers::schedules::list_schedules)
+            .service(web::handlers::schedules::get_schedule)
+            .service(web::handlers::schedules::update_schedule)
+            .service(web::handlers::schedules::delete_schedule)
+
+            // 🔗 REPOSITORY WEBHOOKS
+            .service(web::handlers::repository_targets::create_repository_target)
+            .service(web::handlers::repository_targets::list_repository_targets)
+            .service(web::handlers::repository_targets::get_repository_target)
+            .service(web::handlers::repository_targets::update_repository_target)
+            .service(web::handlers::repository_targets::delete_repository_target)
+            .service(web::handlers::integrations::create_integration)
+            .service(web::handlers::integrations::list_integrations)
+            .service(web::handlers::integrations::get_integration)
+            .service(web::handlers::integrations::update_integration)
+            .service(web::handlers::integrations::rotate_integration_secret)
+            .service(web::handlers::integrations::set_report_token)
+            .service(web::handlers::integrations::delete_report_token)
+            .service(web::handlers::integrations::delete_integration)
+            .service(web::handlers::webhook::integration_webhook)
+            .service(web::handlers::webhook::github_webhook)
+
+            // 📣 OUTBOUND WEBHOOKS
+            .service(web::handlers::outbound_webhooks::create_outbound_webhook)
+            .service(web::handlers::outbound_webhooks::list_outbound_webhooks)
+            .service(web::handlers::outbound_webhooks::get_outbound_webhook)
+            .service(web::handlers::outbound_webhooks::update_outbound_webhook)
+            .service(web::handlers::outbound_webhooks::rotate_outbound_webhook_secret)
+            .service(web::handlers::outbound_webhooks::delete_outbound_webhook)
+            .service(web::handlers::outbound_webhooks::ping_outbound_webhook)
+            .service(web::handlers::outbound_webhooks::list_outbound_webhook_deliveries)
+            .service(web::handlers::outbound_webhooks::get_outbound_webhook_delivery)
+            .service(web::handlers::outbound_webhooks::redeliver_outbound_webhook_delivery)
+
+            // 💬 CHAT NOTIFICATIONS
+            .service(web::handlers::notification_channels::create_notification_channel)
+            .service(web::handlers::notification_channels::list_notification_channels)
+            .service(web::handlers::notification_channels::get_notification_channel)
+            .service(web::handlers::notification_channels::update_notification_channel)
+            .service(web::handlers::notification_channels::delete_notification_channel)
+            .service(web::handlers::notification_channels::test_notification_channel)
     })
     .bind(bind.clone())?
     .keep_alive(Duration::from_secs(75))
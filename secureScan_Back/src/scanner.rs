@@ -3,6 +3,9 @@ use reqwest::Client;
 use scraper::{Html, Selector};
 use std::collections::HashMap;
 
+use crate::domain::findings::{rules, Finding};
+use crate::jobs::events::ScanEvent;
+
 /// Represents the result of a website scan.
 #[allow(dead_code)]
 #[derive(Debug, serde::Serialize, Clone)]
@@ -10,26 +13,59 @@ pub struct ScanResult {
     pub url: String,
     pub status: u16,
     pub headers: HashMap<String, String>,
-    pub security_findings: Vec<String>,
+    pub security_findings: Vec<Finding>,
+}
+
+/// Per-scan tuning knobs. Unset fields fall back to the process-wide defaults.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ScanOptions {
+    /// HTTP request timeout in milliseconds (default: `HTTP_TIMEOUT_MS` or 10s).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Maximum number of redirects to follow (default: 5).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<usize>,
 }
 
 /// Performs a simple HTTP request and analyzes basic security aspects.
 #[allow(dead_code)]
 pub async fn scan_target(target: &str) -> Result<ScanResult> {
+    scan_target_with_events(target, &ScanOptions::default(), &|_| {}).await
+}
+
+/// Same as [`scan_target`], but reports progress and findings through `on_event`
+/// as they are discovered.
+pub async fn scan_target_with_events(
+    target: &str,
+    options: &ScanOptions,
+    on_event: &(dyn Fn(ScanEvent) + Send + Sync),
+) -> Result<ScanResult> {
+    const CHECKS: [&str; 3] = ["fetch", "headers", "transport"];
+    let progress = |completed: usize| {
+        on_event(ScanEvent::Progress {
+            check: CHECKS[completed.min(CHECKS.len() - 1)].to_string(),
+            completed,
+            total: CHECKS.len(),
+        })
+    };
+
     let url = normalize_target(target);
 
-    let timeout_ms: u64 = std::env::var("HTTP_TIMEOUT_MS")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(10_000);
+    let timeout_ms: u64 = options.timeout_ms.unwrap_or_else(|| {
+        std::env::var("HTTP_TIMEOUT_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10_000)
+    });
 
     let client = Client::builder()
         .user_agent("SecureScan/0.1 (+https://securascan.local)")
         .timeout(std::time::Duration::from_millis(timeout_ms))
-        .redirect(reqwest::redirect::Policy::limited(5))
+        .redirect(reqwest::redirect::Policy::limited(options.max_redirects.unwrap_or(5)))
         .build()
         .context("building reqwest client")?;
 
+    progress(0);
     let resp = client
         .get(&url)
         .send()
@@ -44,15 +80,38 @@ pub async fn scan_target(target: &str) -> Result<ScanResult> {
         .collect::<HashMap<_, _>>();
 
     let body = resp.text().await.unwrap_or_default();
+    on_event(ScanEvent::PageCrawled {
+        url: url.clone(),
+        pages_crawled: 1,
+    });
+
+    progress(1);
     let mut findings = analyze_html(&url, &body, &headers);
+    for f in &findings {
+        on_event(ScanEvent::Finding { finding: f.clone() });
+    }
 
+    progress(2);
+    let mut transport_findings = Vec::new();
     if !(200..=399).contains(&status) {
-        findings.push(format!("Non-OK HTTP status: {}", status));
+        transport_findings.push(
+            Finding::new(rules::HTTP_ERROR_STATUS, format!("Non-OK HTTP status: {}", status), url.as_str())
+                .with_evidence(status.to_string()),
+        );
     }
 
     if !url.to_lowercase().starts_with("https://") {
-        findings.push("Target is not using HTTPS (heuristic)".to_string());
+        transport_findings.push(Finding::new(
+            rules::NO_HTTPS,
+            "Target is not using HTTPS (heuristic)",
+            url.as_str(),
+        ));
+    }
+    for f in transport_findings {
+        on_event(ScanEvent::Finding { finding: f.clone() });
+        findings.push(f);
     }
+    progress(CHECKS.len());
 
     Ok(ScanResult {
         url,
@@ -75,20 +134,16 @@ fn normalize_target(t: &str) -> String {
 
 /// Analyzes HTML and HTTP headers for common security findings.
 #[allow(dead_code)]
-fn analyze_html(_url: &str, body: &str, headers: &HashMap<String, String>) -> Vec<String> {
+fn analyze_html(url: &str, body: &str, headers: &HashMap<String, String>) -> Vec<Finding> {
     let mut findings = Vec::new();
 
-    let security_headers = [
-        "content-security-policy",
-        "x-frame-options",
-        "x-xss-protection",
-        "strict-transport-security",
-        "x-content-type-options",
-        "referrer-policy",
-    ];
-    for h in &security_headers {
+    for h in &rules::SECURITY_HEADERS {
         if !headers.contains_key(&h.to_string()) {
-            findings.push(format!("Missing header: {}", h));
+            findings.push(Finding::new(
+                format!("{}{}", rules::MISSING_HEADER_PREFIX, h),
+                format!("Missing header: {}", h),
+                url,
+            ));
         }
     }
 
@@ -96,7 +151,11 @@ fn analyze_html(_url: &str, body: &str, headers: &HashMap<String, String>) -> Ve
     let form_selector = Selector::parse("form").unwrap();
     let forms_count = document.select(&form_selector).count();
     if forms_count > 0 {
-        findings.push(format!("Found {} HTML form(s).", forms_count));
+        findings.push(Finding::new(
+            rules::HTML_FORMS,
+            format!("Found {} HTML form(s).", forms_count),
+            url,
+        ));
     }
 
     let meta_selector = Selector::parse("meta[name]").unwrap();
@@ -104,7 +163,10 @@ fn analyze_html(_url: &str, body: &str, headers: &HashMap<String, String>) -> Ve
         if let Some(name) = meta.value().attr("name") {
             if name.to_lowercase().contains("generator") {
                 if let Some(content) = meta.value().attr("content") {
-                    findings.push(format!("Technology info: {}", content));
+                    findings.push(
+                        Finding::new(rules::TECHNOLOGY_DISCLOSURE, format!("Technology info: {}", content), url)
+                            .with_evidence(content),
+                    );
                 }
             }
         }
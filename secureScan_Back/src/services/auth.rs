@@ -1,20 +1,31 @@
 use crate::db::DbPool;
+use crate::domain::account_tokens_repo::{self, TokenPurpose};
+use crate::domain::api_keys_repo::{self, ApiKeyAuthRow, ApiKeyRow};
+use crate::domain::mfa_repo;
+use crate::domain::orgs_repo;
+use crate::domain::refresh_tokens_repo;
+use crate::domain::user::User;
 use crate::domain::users_repo;
+use crate::services::login_guard::{self, method, ClientAddr};
+use crate::services::mailer::{self, Email, MailConfig, Mailer};
+use crate::services::mfa;
+use crate::services::password_policy::{PasswordPolicy, PolicyViolation};
 
 use argon2::Argon2;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
-use rand_core::OsRng;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx;
+use std::sync::{Arc, OnceLock};
 use thiserror::Error;
 use uuid::Uuid;
 
 /// Errors that can happen during authentication
 #[derive(Debug, Error)]
 pub enum AuthError {
-    /// Email is already registered
-    #[error("email already in use")]
-    EmailTaken,
-
     /// Email or password is invalid
     #[error("invalid email or password")]
     InvalidCredentials,
@@ -26,6 +37,272 @@ pub enum AuthError {
     /// Password hashing/parsing error
     #[error("password hash error")]
     Hash,
+
+    /// Access or refresh token is missing, malformed, expired or revoked
+    #[error("invalid or expired token")]
+    InvalidToken,
+
+    /// Signing an access token failed
+    #[error("token signing error")]
+    Signing,
+
+    /// Login refused until the user confirms their email address
+    #[error("email address is not verified")]
+    EmailNotVerified,
+
+    /// Wrong, expired or replayed TOTP or recovery code
+    #[error("invalid two-factor code")]
+    InvalidMfaCode,
+
+    /// Enrolling while a confirmed second factor exists
+    #[error("two-factor authentication is already enabled")]
+    MfaAlreadyEnabled,
+
+    /// Second-factor operation without an (pending) enrollment
+    #[error("two-factor authentication is not set up")]
+    MfaNotEnabled,
+
+    /// New password breaks the password policy
+    #[error("password does not meet the policy")]
+    WeakPassword(Vec<PolicyViolation>),
+
+    /// A stored second-factor secret cannot be decrypted with the configured key
+    #[error("stored secret cannot be decrypted")]
+    SecretUnavailable,
+
+    /// Login throttled after repeated failures for the account or client IP
+    #[error("too many failed login attempts; retry in {retry_after_secs}s")]
+    TooManyAttempts { retry_after_secs: i64 },
+}
+
+/// Settings for issued session tokens.
+///
+/// Configured through:
+/// - `JWT_SECRET` HMAC key for access tokens (a random per-process key is used when unset,
+///   which invalidates tokens on restart and across instances)
+/// - `ACCESS_TOKEN_TTL_SECS` (default 900)
+/// - `REFRESH_TOKEN_TTL_SECS` (default 2592000, 30 days)
+/// - `MFA_CHALLENGE_TTL_SECS` time to enter the second factor after the password (default 300)
+pub struct TokenConfig {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    pub access_ttl: Duration,
+    pub refresh_ttl: Duration,
+    pub mfa_ttl: Duration,
+}
+
+const TOKEN_ISSUER: &str = "securascan";
+/// Codes that can be tried against one second-factor challenge.
+const MFA_MAX_ATTEMPTS: i32 = 5;
+
+fn env_secs(name: &str, default: i64) -> Duration {
+    let secs = std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default);
+    Duration::seconds(secs)
+}
+
+impl TokenConfig {
+    pub fn from_env() -> Self {
+        let secret = match std::env::var("JWT_SECRET") {
+            Ok(s) if !s.trim().is_empty() => s.into_bytes(),
+            _ => {
+                tracing::warn!("JWT_SECRET is not set; using a random key, sessions will not survive a restart");
+                let mut key = vec![0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                key
+            }
+        };
+
+        Self {
+            encoding_key: EncodingKey::from_secret(&secret),
+            decoding_key: DecodingKey::from_secret(&secret),
+            access_ttl: env_secs("ACCESS_TOKEN_TTL_SECS", 900),
+            refresh_ttl: env_secs("REFRESH_TOKEN_TTL_SECS", 30 * 24 * 3600),
+            mfa_ttl: env_secs("MFA_CHALLENGE_TTL_SECS", 300),
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static TokenConfig {
+        static CONFIG: OnceLock<TokenConfig> = OnceLock::new();
+        CONFIG.get_or_init(TokenConfig::from_env)
+    }
+}
+
+/// Settings for account emails.
+///
+/// Configured through:
+/// - `EMAIL_VERIFICATION_TTL_SECS` (default 86400, 24 hours)
+/// - `PASSWORD_RESET_TTL_SECS` (default 3600)
+/// - `REQUIRE_EMAIL_VERIFICATION` refuse logins of unverified accounts (default `true`)
+pub struct AccountConfig {
+    pub verification_ttl: Duration,
+    pub reset_ttl: Duration,
+    pub require_verification: bool,
+}
+
+impl AccountConfig {
+    pub fn from_env() -> Self {
+        let require_verification = std::env::var("REQUIRE_EMAIL_VERIFICATION")
+            .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "no" | "off"))
+            .unwrap_or(true);
+        Self {
+            verification_ttl: env_secs("EMAIL_VERIFICATION_TTL_SECS", 24 * 3600),
+            reset_ttl: env_secs("PASSWORD_RESET_TTL_SECS", 3600),
+            require_verification,
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static AccountConfig {
+        static CONFIG: OnceLock<AccountConfig> = OnceLock::new();
+        CONFIG.get_or_init(AccountConfig::from_env)
+    }
+}
+
+/// Claims carried by an access token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// User id.
+    pub sub: Uuid,
+    pub email: String,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Tokens returned by login and refresh.
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Access token lifetime in seconds.
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+fn sign_token(user: &User, issuer: &str, ttl: Duration) -> Result<String, AuthError> {
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user.id,
+        email: user.email.clone(),
+        iss: issuer.to_string(),
+        iat: now.timestamp(),
+        exp: (now + ttl).timestamp(),
+    };
+    jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &TokenConfig::global().encoding_key)
+        .map_err(|_| AuthError::Signing)
+}
+
+fn verify_token(token: &str, issuer: &str) -> Result<Claims, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[issuer]);
+    validation.leeway = 0;
+    jsonwebtoken::decode::<Claims>(token, &TokenConfig::global().decoding_key, &validation)
+        .map(|data| data.claims)
+        .map_err(|_| AuthError::InvalidToken)
+}
+
+/// Sign a short-lived access token for `user`.
+pub fn issue_access_token(user: &User) -> Result<String, AuthError> {
+    sign_token(user, TOKEN_ISSUER, TokenConfig::global().access_ttl)
+}
+
+/// Check the signature, issuer and expiry of an access token.
+pub fn verify_access_token(token: &str) -> Result<Claims, AuthError> {
+    verify_token(token, TOKEN_ISSUER)
+}
+
+/// Random opaque token (256 bits, hex), used for refresh and emailed tokens.
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Refresh tokens are looked up by their SHA-256; the token itself is never stored.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn token_pair(user: &User, refresh_token: String) -> Result<TokenPair, AuthError> {
+    Ok(TokenPair {
+        access_token: issue_access_token(user)?,
+        token_type: "Bearer",
+        expires_in: TokenConfig::global().access_ttl.num_seconds(),
+        refresh_token,
+    })
+}
+
+/// Start a new session (refresh token family) for `user`.
+pub async fn start_session(pool: &DbPool, user: &User) -> Result<TokenPair, AuthError> {
+    let refresh_token = generate_refresh_token();
+    refresh_tokens_repo::insert_refresh_token(
+        pool,
+        user.id,
+        Uuid::new_v4(),
+        &hash_token(&refresh_token),
+        Utc::now() + TokenConfig::global().refresh_ttl,
+    )
+    .await?;
+    token_pair(user, refresh_token)
+}
+
+/// Exchange a refresh token for a new token pair, rotating the refresh token.
+///
+/// Presenting a token that was already rotated or revoked means it leaked or
+/// was replayed, so the whole family is revoked and the caller must log in again.
+pub async fn refresh_session(pool: &DbPool, refresh_token: &str) -> Result<(User, TokenPair), AuthError> {
+    let mut tx = pool.begin().await?;
+
+    let Some(current) = refresh_tokens_repo::lock_by_hash(&mut tx, &hash_token(refresh_token)).await? else {
+        return Err(AuthError::InvalidToken);
+    };
+
+    if current.revoked_at.is_some() {
+        let revoked = refresh_tokens_repo::revoke_family(&mut *tx, current.family_id).await?;
+        tx.commit().await?;
+        tracing::warn!(
+            "Refresh token reuse for user {}; revoked {} token(s) of family {}",
+            current.user_id,
+            revoked,
+            current.family_id
+        );
+        return Err(AuthError::InvalidToken);
+    }
+    if current.expires_at <= Utc::now() {
+        return Err(AuthError::InvalidToken);
+    }
+
+    let Some(user) = users_repo::find_by_id(pool, current.user_id).await? else {
+        return Err(AuthError::InvalidToken);
+    };
+
+    let next_token = generate_refresh_token();
+    let next = refresh_tokens_repo::insert_refresh_token(
+        &mut *tx,
+        user.id,
+        current.family_id,
+        &hash_token(&next_token),
+        Utc::now() + TokenConfig::global().refresh_ttl,
+    )
+    .await?;
+    refresh_tokens_repo::mark_rotated(&mut tx, current.id, next.id).await?;
+    tx.commit().await?;
+
+    let pair = token_pair(&user, next_token)?;
+    Ok((user, pair))
+}
+
+/// Revoke the session a refresh token belongs to. Unknown tokens are ignored.
+pub async fn end_session(pool: &DbPool, refresh_token: &str) -> Result<(), AuthError> {
+    if let Some(row) = refresh_tokens_repo::find_by_hash(pool, &hash_token(refresh_token)).await? {
+        refresh_tokens_repo::revoke_family(pool, row.family_id).await?;
+    }
+    Ok(())
 }
 
 /// Hash a plain text password using Argon2
@@ -50,37 +327,375 @@ pub fn verify_password(stored_hash: &str, password: &str) -> Result<(), AuthErro
         .map_err(|_| AuthError::InvalidCredentials)
 }
 
-/// Register a new user with email + password
+/// Hash verified when the email is unknown, so a login takes as long whether
+/// or not the account exists.
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_password("securascan-dummy-password").expect("argon2 hashing of a constant"))
+}
+
+/// Count the attempt, or refuse it early while the account or client IP is throttled.
+async fn ensure_not_throttled(pool: &DbPool, email: &str, ip: Option<&str>) -> Result<(), AuthError> {
+    match login_guard::claim_attempt(pool, email, ip).await? {
+        Some(retry_after_secs) => Err(AuthError::TooManyAttempts { retry_after_secs }),
+        None => Ok(()),
+    }
+}
+
+/// Reject a new password that breaks [`PasswordPolicy`], before it is hashed.
+async fn ensure_password_allowed(email: &str, password: &str) -> Result<(), AuthError> {
+    let violations = PasswordPolicy::global().check(email, password).await;
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(AuthError::WeakPassword(violations))
+    }
+}
+
+/// Register a new user with email + password and mail them a verification link.
+/// A registered email gets a notice instead, so the caller cannot tell whether
+/// the email had an account.
 pub async fn register_user(
     pool: &DbPool,
+    mailer: Arc<dyn Mailer>,
     email: &str,
     password: &str,
-) -> Result<Uuid, AuthError> {
-    // Check if email already exists
-    if let Some(_) = users_repo::find_by_email(pool, email).await? {
-        return Err(AuthError::EmailTaken);
-    }
+) -> Result<(), AuthError> {
+    ensure_password_allowed(email, password).await?;
 
-    // Hash password and insert into DB
+    // Hashed either way, so both outcomes take as long.
     let password_hash = hash_password(password)?;
-    let user_id = users_repo::insert_user(pool, email, &password_hash).await?;
-    Ok(user_id)
+    if let Some(existing) = users_repo::find_by_email(pool, email).await? {
+        send_already_registered_email(mailer, &existing);
+        return Ok(());
+    }
+
+    // Insert into DB together with the user's personal organization
+    let mut tx = pool.begin().await?;
+    let user_id = match users_repo::insert_user(&mut *tx, email, &password_hash).await {
+        Ok(user_id) => user_id,
+        // Registered concurrently under the same email.
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    orgs_repo::create_org(&mut *tx, email, user_id).await?;
+    tx.commit().await?;
+
+    // The account exists either way; a failed mail can be retried with resend.
+    if let Some(user) = users_repo::find_by_id(pool, user_id).await? {
+        if let Err(e) = send_verification_email(pool, mailer, &user).await {
+            tracing::error!("could not send verification email to {}: {}", email, e);
+        }
+    }
+    Ok(())
+}
+
+/// Tell the owner of `user` that someone tried to register their email again.
+fn send_already_registered_email(mailer: Arc<dyn Mailer>, user: &User) {
+    let cfg = MailConfig::global();
+    mailer::send_in_background(
+        mailer,
+        Email {
+            to: user.email.clone(),
+            subject: "Your SecuraScan account".into(),
+            body: format!(
+                "Someone tried to create a SecuraScan account with this email address, which already has one.\n\n\
+                 Log in here:\n{}\n\n\
+                 Forgot your password? Reset it here:\n{}\n\n\
+                 If this was not you, ignore this email.\n",
+                cfg.app_url("/login"),
+                cfg.app_url("/forgot-password"),
+            ),
+        },
+    );
 }
 
-/// Login a user and return their id if credentials are valid
+/// Outcome of a correct email and password.
+pub enum LoginStep {
+    /// No second factor configured; a session can be started.
+    Complete(User),
+    /// The user must present a second factor with `mfa_token` before a session is started.
+    MfaRequired { mfa_token: String, expires_in: i64 },
+}
+
+/// Login a user with email + password. Users with two-factor authentication
+/// get a challenge token for [`complete_mfa_login`] instead of a session.
 pub async fn login_user(
     pool: &DbPool,
     email: &str,
     password: &str,
-) -> Result<Uuid, AuthError> {
+    client: &ClientAddr,
+) -> Result<LoginStep, AuthError> {
+    let ip = client.ip.as_deref();
+    ensure_not_throttled(pool, email, client.throttle_ip()).await?;
+
     // Find user by email
     let user = users_repo::find_by_email(pool, email).await?;
 
+    // Over-long passwords are never hashed; no account could have one.
+    if !PasswordPolicy::global().within_max_length(password) {
+        login_guard::record(pool, email, user.as_ref().map(|u| u.id), ip, false, method::PASSWORD).await?;
+        return Err(AuthError::InvalidCredentials);
+    }
+
     let Some(user) = user else {
+        let _ = verify_password(dummy_password_hash(), password);
+        login_guard::record(pool, email, None, ip, false, method::PASSWORD).await?;
         return Err(AuthError::InvalidCredentials);
     };
 
     // Verify password against stored hash
-    verify_password(&user.password_hash, password)?;
-    Ok(user.id)
+    if let Err(e) = verify_password(&user.password_hash, password) {
+        login_guard::record(pool, email, Some(user.id), ip, false, method::PASSWORD).await?;
+        return Err(e);
+    }
+
+    // The password was right; only a wrong second factor counts from here on.
+    login_guard::release_attempt(pool, email, client.throttle_ip()).await?;
+    if user.email_verified_at.is_none() && AccountConfig::global().require_verification {
+        return Err(AuthError::EmailNotVerified);
+    }
+
+    let step = second_factor_step(pool, user).await?;
+    if let LoginStep::Complete(user) = &step {
+        login_guard::record(pool, email, Some(user.id), ip, true, method::PASSWORD).await?;
+    }
+    Ok(step)
+}
+
+/// What follows a successful first factor: a challenge for [`complete_mfa_login`]
+/// when the user has two-factor authentication, otherwise a completed login.
+pub async fn second_factor_step(pool: &DbPool, user: User) -> Result<LoginStep, AuthError> {
+    if mfa_repo::is_enabled(pool, user.id).await? {
+        let ttl = TokenConfig::global().mfa_ttl;
+        let mfa_token = generate_refresh_token();
+        mfa_repo::insert_challenge(pool, user.id, &hash_token(&mfa_token), Utc::now() + ttl).await?;
+        return Ok(LoginStep::MfaRequired {
+            mfa_token,
+            expires_in: ttl.num_seconds(),
+        });
+    }
+    Ok(LoginStep::Complete(user))
+}
+
+/// Second login step: check a TOTP or recovery code against the challenge
+/// issued by [`login_user`] and return the user to start a session for.
+/// A challenge is used up by its first correct code or after a few wrong ones;
+/// wrong codes also count toward the same throttling as wrong passwords.
+pub async fn complete_mfa_login(
+    pool: &DbPool,
+    mfa_token: &str,
+    code: &str,
+    client: &ClientAddr,
+) -> Result<User, AuthError> {
+    let ip = client.ip.as_deref();
+    let challenge = mfa_repo::start_challenge_attempt(pool, &hash_token(mfa_token), MFA_MAX_ATTEMPTS)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    let user = users_repo::find_by_id(pool, challenge.user_id)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    ensure_not_throttled(pool, &user.email, client.throttle_ip()).await?;
+
+    if let Err(e) = mfa::verify_second_factor(pool, user.id, code).await {
+        if matches!(e, AuthError::InvalidMfaCode) {
+            login_guard::record(pool, &user.email, Some(user.id), ip, false, method::MFA).await?;
+        }
+        return Err(e);
+    }
+    login_guard::release_attempt(pool, &user.email, client.throttle_ip()).await?;
+    if !mfa_repo::consume_challenge(pool, challenge.id).await? {
+        return Err(AuthError::InvalidToken);
+    }
+    login_guard::record(pool, &user.email, Some(user.id), ip, true, method::MFA).await?;
+    Ok(user)
+}
+
+/// Issue a single-use token for `purpose`, invalidating the user's earlier ones.
+async fn issue_account_token(
+    pool: &DbPool,
+    user_id: Uuid,
+    purpose: TokenPurpose,
+    ttl: Duration,
+) -> Result<String, AuthError> {
+    let token = generate_refresh_token();
+    let mut tx = pool.begin().await?;
+    account_tokens_repo::invalidate_tokens(&mut *tx, user_id, purpose).await?;
+    account_tokens_repo::insert_token(&mut *tx, user_id, purpose, &hash_token(&token), Utc::now() + ttl).await?;
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// Mail `user` a link confirming their email address.
+pub async fn send_verification_email(pool: &DbPool, mailer: Arc<dyn Mailer>, user: &User) -> Result<(), AuthError> {
+    let ttl = AccountConfig::global().verification_ttl;
+    let token = issue_account_token(pool, user.id, TokenPurpose::VerifyEmail, ttl).await?;
+    let link = MailConfig::global().app_url(&format!("/verify-email?token={}", token));
+    mailer::send_in_background(
+        mailer,
+        Email {
+            to: user.email.clone(),
+            subject: "Confirm your SecuraScan email address".into(),
+            body: format!(
+                "Welcome to SecuraScan!\n\nConfirm your email address by opening this link:\n{}\n\n\
+                 The link expires in {} hours. If you did not create an account, ignore this email.\n",
+                link,
+                ttl.num_hours()
+            ),
+        },
+    );
+    Ok(())
+}
+
+/// Send a new verification link if `email` belongs to an unverified account.
+/// Does nothing otherwise, so callers cannot probe which emails are registered.
+pub async fn resend_verification(pool: &DbPool, mailer: Arc<dyn Mailer>, email: &str) -> Result<(), AuthError> {
+    match users_repo::find_by_email(pool, email).await? {
+        Some(user) if user.email_verified_at.is_none() => send_verification_email(pool, mailer, &user).await,
+        _ => Ok(()),
+    }
+}
+
+/// Redeem an email verification token.
+pub async fn verify_email(pool: &DbPool, token: &str) -> Result<(), AuthError> {
+    let mut tx = pool.begin().await?;
+    let row = account_tokens_repo::consume_token(&mut *tx, TokenPurpose::VerifyEmail, &hash_token(token))
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    users_repo::mark_email_verified(&mut *tx, row.user_id).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Mail a password reset link if `email` is registered. Unknown emails are
+/// ignored silently, so callers cannot probe which emails are registered.
+pub async fn request_password_reset(pool: &DbPool, mailer: Arc<dyn Mailer>, email: &str) -> Result<(), AuthError> {
+    let Some(user) = users_repo::find_by_email(pool, email).await? else {
+        return Ok(());
+    };
+
+    let ttl = AccountConfig::global().reset_ttl;
+    let token = issue_account_token(pool, user.id, TokenPurpose::ResetPassword, ttl).await?;
+    let link = MailConfig::global().app_url(&format!("/reset-password?token={}", token));
+    mailer::send_in_background(
+        mailer,
+        Email {
+            to: user.email,
+            subject: "Reset your SecuraScan password".into(),
+            body: format!(
+                "Someone asked to reset the password of your SecuraScan account.\n\n\
+                 Choose a new password here:\n{}\n\n\
+                 The link expires in {} minutes and works once. If this was not you, ignore this email.\n",
+                link,
+                ttl.num_minutes()
+            ),
+        },
+    );
+    Ok(())
+}
+
+/// Set a new password with a reset token. Every session and API key of the
+/// user is revoked, and the email counts as verified since the link reached it.
+pub async fn reset_password(pool: &DbPool, token: &str, new_password: &str) -> Result<(), AuthError> {
+    let token_hash = hash_token(token);
+    // Check and hash the password before taking any locks. A rejected password
+    // leaves the token usable for another try.
+    let pending = account_tokens_repo::find_token(pool, TokenPurpose::ResetPassword, &token_hash)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    let user = users_repo::find_by_id(pool, pending.user_id)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    ensure_password_allowed(&user.email, new_password).await?;
+    let password_hash = hash_password(new_password)?;
+
+    let mut tx = pool.begin().await?;
+    let row = account_tokens_repo::consume_token(&mut *tx, TokenPurpose::ResetPassword, &token_hash)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+
+    users_repo::update_password(&mut *tx, row.user_id, &password_hash).await?;
+    users_repo::mark_email_verified(&mut *tx, row.user_id).await?;
+    account_tokens_repo::invalidate_tokens(&mut *tx, row.user_id, TokenPurpose::ResetPassword).await?;
+    let revoked_keys = api_keys_repo::revoke_user_keys(&mut *tx, row.user_id).await?;
+    tx.commit().await?;
+
+    let revoked = refresh_tokens_repo::revoke_user_tokens(pool, row.user_id).await?;
+    tracing::info!(
+        "password reset for user {}; revoked {} session token(s) and {} API key(s)",
+        row.user_id,
+        revoked,
+        revoked_keys
+    );
+
+    // Proving control of the mailbox lifts a lockout of the account.
+    login_guard::record(pool, &user.email, Some(user.id), None, true, method::PASSWORD_RESET).await?;
+    Ok(())
+}
+
+/// Permissions an API key can be granted. User sessions have all of them.
+pub mod scopes {
+    pub const SCANS_READ: &str = "scans:read";
+    pub const SCANS_WRITE: &str = "scans:write";
+    pub const SCHEDULES_READ: &str = "schedules:read";
+    pub const SCHEDULES_WRITE: &str = "schedules:write";
+    pub const SUPPRESSIONS_READ: &str = "suppressions:read";
+    pub const SUPPRESSIONS_WRITE: &str = "suppressions:write";
+    pub const INTEGRATIONS_READ: &str = "integrations:read";
+    pub const INTEGRATIONS_WRITE: &str = "integrations:write";
+
+    pub const ALL: [&str; 8] = [
+        SCANS_READ,
+        SCANS_WRITE,
+        SCHEDULES_READ,
+        SCHEDULES_WRITE,
+        SUPPRESSIONS_READ,
+        SUPPRESSIONS_WRITE,
+        INTEGRATIONS_READ,
+        INTEGRATIONS_WRITE,
+    ];
+}
+
+/// Prefix of every API key, used to tell them apart from session JWTs.
+pub const API_KEY_PREFIX: &str = "ssk_";
+
+/// Number of leading characters of a key kept in clear for display.
+const API_KEY_DISPLAY_LEN: usize = 12;
+
+/// Whether a bearer token looks like an API key rather than a session token.
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
+
+/// Create an API key for `user_id`. Returns the stored row and the secret,
+/// which is not recoverable afterwards.
+pub async fn create_api_key(
+    pool: &DbPool,
+    user_id: Uuid,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiKeyRow, String), AuthError> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let secret = format!("{}{}", API_KEY_PREFIX, hex::encode(bytes));
+
+    let row = api_keys_repo::create_api_key(
+        pool,
+        user_id,
+        name,
+        &secret[..API_KEY_DISPLAY_LEN],
+        &hash_token(&secret),
+        scopes,
+        expires_at,
+    )
+    .await?;
+    Ok((row, secret))
+}
+
+/// Resolve a presented API key to its owner and scopes.
+pub async fn authenticate_api_key(pool: &DbPool, key: &str) -> Result<ApiKeyAuthRow, AuthError> {
+    api_keys_repo::authenticate(pool, &hash_token(key))
+        .await?
+        .ok_or(AuthError::InvalidToken)
 }
@@ -0,0 +1,309 @@
+use serde_json::json;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::commit_reports_repo::{self, CommitReportRow, NewCommitReport};
+use crate::domain::findings::{Finding, Severity, SeverityCounts};
+use crate::domain::repository_targets_repo::{self, RepositoryTargetRow};
+use crate::domain::{orgs_repo, scans_repo, webhook_integrations_repo};
+use crate::services::mailer::MailConfig;
+use crate::services::scan_findings::OpenFindings;
+use crate::services::secret_box::SecretBox;
+use crate::services::webhooks::{self, provider, RepoEvent};
+
+/// Reporting of webhook-triggered scans back to GitHub. Scans of an integration
+/// are reported with the token stored on that integration, so an organization
+/// can only post on repositories it has write access to.
+///
+/// Configured through:
+/// - `GITHUB_TOKEN` token for scans of the legacy endpoint, which belong to the
+///   `GITHUB_WEBHOOK_ORG_ID` organization
+/// - `GITHUB_API_URL` API base URL (default `https://api.github.com`; GitHub Enterprise uses `https://<host>/api/v3`)
+/// - `GITHUB_STATUS_CONTEXT` name of the commit status, followed by the organization's name (default `SecuraScan`)
+pub struct GithubReportConfig {
+    pub token: Option<String>,
+    pub api_url: String,
+    pub context: String,
+    http: reqwest::Client,
+}
+
+fn env_trimmed(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+impl GithubReportConfig {
+    pub fn from_env() -> Self {
+        Self {
+            token: env_trimmed("GITHUB_TOKEN"),
+            api_url: env_trimmed("GITHUB_API_URL")
+                .unwrap_or_else(|| "https://api.github.com".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            context: env_trimmed("GITHUB_STATUS_CONTEXT").unwrap_or_else(|| "SecuraScan".to_string()),
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .user_agent("SecuraScan")
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static GithubReportConfig {
+        static CONFIG: OnceLock<GithubReportConfig> = OnceLock::new();
+        CONFIG.get_or_init(GithubReportConfig::from_env)
+    }
+
+    async fn call(
+        &self,
+        token: &str,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let res = self
+            .http
+            .request(method, format!("{}{}", self.api_url, path))
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .json(&body)
+            .send()
+            .await?;
+        let status = res.status();
+        if !status.is_success() {
+            let text = res.text().await.unwrap_or_default();
+            anyhow::bail!("GitHub API {} {}: {}", status.as_u16(), path, text.chars().take(300).collect::<String>());
+        }
+        Ok(res.json().await.unwrap_or(serde_json::Value::Null))
+    }
+}
+
+/// The token posting reports of `org_id`'s scans received through `integration_id`:
+/// the integration's own token, or `GITHUB_TOKEN` for the legacy endpoint's
+/// organization. `None` when there is none.
+async fn report_token(pool: &DbPool, integration_id: Option<Uuid>, org_id: Option<Uuid>) -> anyhow::Result<Option<String>> {
+    let Some(integration_id) = integration_id else {
+        let legacy = org_id.is_some() && org_id == webhooks::legacy_github_org();
+        return Ok(GithubReportConfig::global().token.clone().filter(|_| legacy));
+    };
+    let sealed = webhook_integrations_repo::find_integration(pool, integration_id)
+        .await?
+        .filter(|i| Some(i.org_id) == org_id)
+        .and_then(|i| i.report_token);
+    match sealed {
+        Some(sealed) => Ok(Some(SecretBox::global().open(&sealed)?)),
+        None => Ok(None),
+    }
+}
+
+/// Remember to report the scan `scan_id` started for `event` on its commit, if
+/// `target` asks for it and its organization has a token for the repository.
+/// Repositories mapped by several organizations are never reported on, since
+/// they cannot all own it.
+pub async fn register(
+    pool: &DbPool,
+    scan_id: Uuid,
+    target: &RepositoryTargetRow,
+    event: &RepoEvent,
+    integration_id: Option<Uuid>,
+) -> anyhow::Result<()> {
+    let Some(commit_sha) = &event.commit_sha else {
+        return Ok(());
+    };
+    if !target.report_to_provider || target.provider != provider::GITHUB {
+        return Ok(());
+    }
+    if report_token(pool, integration_id, Some(target.org_id)).await?.is_none() {
+        return Ok(());
+    }
+    if !webhooks::is_valid_repository(&target.provider, &event.repository) || !webhooks::is_valid_commit_sha(commit_sha) {
+        tracing::warn!("commit report: not reporting on {} {}: invalid repository or commit", event.repository, commit_sha);
+        return Ok(());
+    }
+    if repository_targets_repo::count_orgs_mapping(pool, &target.provider, &event.repository).await? > 1
+        || commit_reports_repo::reported_by_other_org(pool, &target.provider, &event.repository, commit_sha, target.org_id)
+            .await?
+    {
+        tracing::warn!(
+            "commit report: not reporting on {} {}: the repository is mapped by more than one organization",
+            event.repository,
+            commit_sha
+        );
+        return Ok(());
+    }
+
+    let cfg = GithubReportConfig::global();
+    let context = match orgs_repo::get_org(pool, target.org_id).await? {
+        Some(org) => format!("{} ({})", cfg.context, org.name),
+        None => cfg.context.clone(),
+    };
+    commit_reports_repo::create_commit_report(
+        pool,
+        &NewCommitReport {
+            scan_id,
+            repository_target_id: target.id,
+            provider: target.provider.clone(),
+            repository: event.repository.clone(),
+            commit_sha: commit_sha.clone(),
+            fail_on_severity: target.fail_on_severity.clone(),
+            integration_id,
+            org_id: target.org_id,
+            context,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Mark the commit of a starting scan as pending. Failures are logged, never returned.
+pub async fn report_started(pool: &DbPool, scan_id: Uuid) {
+    report(pool, scan_id, false).await
+}
+
+/// Post the pass/fail result of a completed or failed scan on its commit.
+/// Failures are logged, never returned.
+pub async fn report_finished(pool: &DbPool, scan_id: Uuid) {
+    report(pool, scan_id, true).await
+}
+
+async fn report(pool: &DbPool, scan_id: Uuid, finished: bool) {
+    let report = match commit_reports_repo::get_commit_report(pool, scan_id).await {
+        Ok(Some(report)) => report,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("commit report: could not load report of scan {}: {}", scan_id, e);
+            return;
+        }
+    };
+
+    let result = if finished {
+        post_result(pool, &report).await
+    } else {
+        post_pending(pool, &report).await
+    };
+    match result {
+        Ok(state) => {
+            tracing::info!("commit report: {} {} is {} (scan {})", report.repository, report.commit_sha, state, scan_id);
+            if let Err(e) = commit_reports_repo::mark_reported(pool, scan_id, state).await {
+                tracing::error!("commit report: could not store state of scan {}: {}", scan_id, e);
+            }
+        }
+        Err(e) => {
+            tracing::error!("commit report: posting result of scan {} failed: {}", scan_id, e);
+            if let Err(e) = commit_reports_repo::mark_failed(pool, scan_id, &e.to_string()).await {
+                tracing::error!("commit report: could not store error of scan {}: {}", scan_id, e);
+            }
+        }
+    }
+}
+
+/// Outcome of a finished scan against its report's severity threshold.
+struct Verdict {
+    threshold: Severity,
+    counts: SeverityCounts,
+    /// Open findings at or above the threshold.
+    blocking: Vec<Finding>,
+    /// Blocking findings absent from the previous completed scan of the target.
+    new_blocking: Vec<Finding>,
+}
+
+impl Verdict {
+    fn passed(&self) -> bool {
+        self.blocking.is_empty()
+    }
+
+    /// One line, within the 140 characters a commit status description allows.
+    fn description(&self) -> String {
+        let line = if self.passed() {
+            format!(
+                "Passed: no open findings at or above {} ({} open in total)",
+                self.threshold.as_str(),
+                self.counts.total
+            )
+        } else {
+            format!(
+                "Failed: {} open finding(s) at or above {}, {} new",
+                self.blocking.len(),
+                self.threshold.as_str(),
+                self.new_blocking.len()
+            )
+        };
+        line.chars().take(140).collect()
+    }
+}
+
+async fn evaluate(pool: &DbPool, scan: &scans_repo::ScanRow, threshold: Severity) -> anyhow::Result<Verdict> {
+    let findings = OpenFindings::load(pool, scan).await?;
+    let at_threshold = |found: Vec<Finding>| -> Vec<Finding> {
+        found.into_iter().filter(|f| f.severity >= threshold).collect()
+    };
+    Ok(Verdict {
+        threshold,
+        counts: findings.counts,
+        blocking: at_threshold(findings.open),
+        new_blocking: at_threshold(findings.new),
+    })
+}
+
+fn scan_link(scan_id: Uuid) -> String {
+    MailConfig::global().app_url(&format!("/dashboard/{}", scan_id))
+}
+
+async fn post_pending(pool: &DbPool, report: &CommitReportRow) -> anyhow::Result<&'static str> {
+    post_status(pool, report, "pending", "Scan running").await?;
+    Ok("pending")
+}
+
+async fn post_result(pool: &DbPool, report: &CommitReportRow) -> anyhow::Result<&'static str> {
+    let scan = scans_repo::get_scan(pool, report.scan_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("scan {} no longer exists", report.scan_id))?;
+    let threshold = Severity::parse(&report.fail_on_severity).unwrap_or(Severity::High);
+
+    let verdict = if scan.status == "completed" {
+        Some(evaluate(pool, &scan, threshold).await?)
+    } else {
+        None
+    };
+    let state = match &verdict {
+        Some(v) if v.passed() => "success",
+        Some(_) => "failure",
+        None => "error",
+    };
+    let description = verdict
+        .as_ref()
+        .map(Verdict::description)
+        .unwrap_or_else(|| "Scan failed before producing results".to_string());
+    post_status(pool, report, state, &description).await?;
+    Ok(state)
+}
+
+async fn post_status(pool: &DbPool, report: &CommitReportRow, state: &str, description: &str) -> anyhow::Result<()> {
+    if !webhooks::is_valid_repository(&report.provider, &report.repository)
+        || !webhooks::is_valid_commit_sha(&report.commit_sha)
+    {
+        anyhow::bail!("invalid repository or commit: {} {}", report.repository, report.commit_sha);
+    }
+    let token = report_token(pool, report.integration_id, report.org_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("no GitHub token for the organization of scan {}", report.scan_id))?;
+    let cfg = GithubReportConfig::global();
+    cfg.call(
+        &token,
+        reqwest::Method::POST,
+        &format!("/repos/{}/statuses/{}", report.repository, report.commit_sha),
+        json!({
+            "state": state,
+            "description": description,
+            "context": report.context.as_deref().unwrap_or(&cfg.context),
+            "target_url": scan_link(report.scan_id),
+        }),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,163 @@
+//! Requests to URLs chosen by users (outbound webhooks) must not reach the
+//! server's own network: loopback, private ranges, cloud metadata and the like.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum EgressError {
+    #[error("URL has no host")]
+    NoHost,
+
+    #[error("cannot resolve {0}")]
+    Unresolvable(String),
+
+    /// Resolves to an address that is not on the public internet
+    #[error("{0} resolves to a non-public address")]
+    NonPublic(String),
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_multicast()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || a == 0
+        // carrier-grade NAT, 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking, 198.18.0.0/15
+        || (a == 198 && (18..20).contains(&b))
+        // reserved, 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // IPv4-mapped, NAT64 and 6to4 addresses reach the embedded IPv4 address.
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        return is_public_v4(Ipv4Addr::new(
+            (segments[6] >> 8) as u8,
+            segments[6] as u8,
+            (segments[7] >> 8) as u8,
+            segments[7] as u8,
+        ));
+    }
+    if segments[0] == 0x2002 {
+        return is_public_v4(Ipv4Addr::new(
+            (segments[1] >> 8) as u8,
+            segments[1] as u8,
+            (segments[2] >> 8) as u8,
+            segments[2] as u8,
+        ));
+    }
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // IPv4-compatible, ::/96
+        || segments[..6] == [0; 6]
+        // unique local, fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // link-local, fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        // documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+/// Whether `ip` is an address on the public internet.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+/// Resolve the host of `url`, refusing it unless every address is public.
+/// Returns the host name and the addresses to connect to; connect only to
+/// these, as resolving again could give a different answer.
+pub async fn resolve_public(url: &Url) -> Result<(String, Vec<SocketAddr>), EgressError> {
+    let host = url.host_str().ok_or(EgressError::NoHost)?.to_string();
+    let port = url.port_or_known_default().ok_or(EgressError::NoHost)?;
+    let bare = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = match bare.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((bare, port))
+            .await
+            .map_err(|_| EgressError::Unresolvable(host.clone()))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(EgressError::Unresolvable(host));
+    }
+    if !addrs.iter().all(|a| is_public(a.ip())) {
+        return Err(EgressError::NonPublic(host));
+    }
+    Ok((bare.to_string(), addrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn public_addresses() {
+        for ip in ["93.184.216.34", "8.8.8.8", "100.128.0.1", "2606:2800:220:1::1", "::ffff:8.8.8.8", "2002:808:808::1"] {
+            assert!(public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn non_public_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "192.0.0.8",
+            "198.18.0.1",
+            "203.0.113.5",
+            "224.0.0.1",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "ff02::1",
+            "2001:db8::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+            "64:ff9b::a00:1",
+            "2002:7f00:1::1",
+            "::7f00:1",
+        ] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn literal_addresses_are_checked_without_lookup() {
+        let url = Url::parse("http://[::ffff:10.0.0.1]:8080/hook").unwrap();
+        assert!(matches!(resolve_public(&url).await, Err(EgressError::NonPublic(_))));
+
+        let url = Url::parse("https://93.184.216.34/hook").unwrap();
+        let (host, addrs) = resolve_public(&url).await.unwrap();
+        assert_eq!(host, "93.184.216.34");
+        assert_eq!(addrs, vec!["93.184.216.34:443".parse().unwrap()]);
+    }
+}
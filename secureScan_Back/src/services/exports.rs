@@ -0,0 +1,214 @@
+//! Bulk CSV / JSON Lines exports, streamed from a database cursor so exports of
+//! any size are never held in memory.
+
+use actix_web::web::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Postgres, QueryBuilder};
+use std::io;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+use crate::db::{self, DbPool};
+
+/// Encoded rows are sent to the client in chunks of about this size.
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks buffered between the database reader and a slow client.
+const CHANNEL_CHUNKS: usize = 4;
+
+/// Limits on running exports. Each one holds a pooled database connection
+/// for as long as its client keeps reading.
+///
+/// Configured through:
+/// - `EXPORT_MAX_CONCURRENT` exports running at once (default: 3 fewer than
+///   `DATABASE_MAX_CONNECTIONS`, at least 1; never more)
+/// - `EXPORT_STALL_TIMEOUT_SECS` how long a client may stop reading before its
+///   export is aborted (default 30)
+pub struct ExportConfig {
+    pub max_concurrent: usize,
+    pub stall_timeout: Duration,
+    permits: Arc<Semaphore>,
+}
+
+impl ExportConfig {
+    pub fn from_env() -> Self {
+        // leave connections for logins, workers, the scheduler and the event listener
+        let limit = (db::max_connections() as usize).saturating_sub(3).max(1);
+        let max_concurrent = match std::env::var("EXPORT_MAX_CONCURRENT").ok().and_then(|s| s.trim().parse().ok()) {
+            Some(n) if n > limit => {
+                tracing::warn!(
+                    "EXPORT_MAX_CONCURRENT={} would use up the database pool; using {}",
+                    n,
+                    limit
+                );
+                limit
+            }
+            Some(n) => n,
+            None => limit,
+        };
+        let stall_secs = std::env::var("EXPORT_STALL_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(30u64)
+            .max(1);
+        Self {
+            max_concurrent,
+            stall_timeout: Duration::from_secs(stall_secs),
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static ExportConfig {
+        static CONFIG: OnceLock<ExportConfig> = OnceLock::new();
+        CONFIG.get_or_init(ExportConfig::from_env)
+    }
+
+    /// A slot for one export, or `None` while `max_concurrent` are running.
+    pub fn try_start(&self) -> Option<OwnedSemaphorePermit> {
+        self.permits.clone().try_acquire_owned().ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+impl Format {
+    /// From the extension of the export URL: `csv` or `jsonl`.
+    pub fn parse(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::JsonLines => "application/x-ndjson",
+        }
+    }
+}
+
+/// One CSV field (RFC 4180). Values a spreadsheet would evaluate as a formula
+/// are prefixed with `'`, since titles and evidence come from scanned sites.
+pub fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// One CSV record, CRLF-terminated.
+pub fn csv_record<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line = fields
+        .into_iter()
+        .map(|f| csv_field(f.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Runs `query` on its own task and streams `header` followed by every row
+/// passed through `encode`. The task stops reading when the client goes away
+/// or stops reading for `stall_timeout`, and releases `permit` when done;
+/// a database error mid-export aborts the response, so a truncated file is
+/// never mistaken for a complete one.
+pub fn stream_rows<R, F>(
+    permit: OwnedSemaphorePermit,
+    pool: DbPool,
+    mut query: QueryBuilder<'static, Postgres>,
+    header: Option<String>,
+    mut encode: F,
+) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+    F: FnMut(R) -> String + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<Result<Bytes, io::Error>>(CHANNEL_CHUNKS);
+    let stall_timeout = ExportConfig::global().stall_timeout;
+
+    tokio::spawn(async move {
+        let _permit = permit;
+        let mut buf = header.unwrap_or_default();
+        let mut rows = query.build_query_as::<R>().fetch(&pool);
+        while let Some(row) = rows.next().await {
+            match row {
+                Ok(row) => buf.push_str(&encode(row)),
+                Err(e) => {
+                    tracing::error!("Export query failed: {}", e);
+                    let _ = tx.send(Err(io::Error::other(e.to_string()))).await;
+                    return;
+                }
+            }
+            if buf.len() >= CHUNK_BYTES {
+                let chunk = Bytes::from(std::mem::take(&mut buf));
+                match tokio::time::timeout(stall_timeout, tx.send(Ok(chunk))).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(_)) => return,
+                    Err(_) => {
+                        tracing::warn!("Export aborted: client stopped reading");
+                        return;
+                    }
+                }
+            }
+        }
+        if !buf.is_empty() {
+            let _ = tokio::time::timeout(stall_timeout, tx.send(Ok(Bytes::from(buf)))).await;
+        }
+    });
+
+    stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|chunk| (chunk, rx)) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_field_neutralizes_formulas() {
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\tcmd"), "'\tcmd");
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn csv_record_is_crlf_terminated() {
+        assert_eq!(csv_record(["a", "b,c", "=1"]), "a,\"b,c\",'=1\r\n");
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{Duration, Utc};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::login_attempts_repo;
+
+/// How an attempt authenticated, as recorded in the audit trail.
+pub mod method {
+    pub const PASSWORD: &str = "password";
+    pub const MFA: &str = "mfa";
+    pub const PASSWORD_RESET: &str = "password_reset";
+    pub const OIDC: &str = "oidc";
+}
+
+/// Throttling of failed logins.
+///
+/// Configured through:
+/// - `LOGIN_FREE_ATTEMPTS` failures per account before delays start (default 3)
+/// - `LOGIN_DELAY_BASE_SECS` first delay, doubled with every further failure (default 1)
+/// - `LOGIN_DELAY_MAX_SECS` cap of the progressive delay (default 60)
+/// - `LOGIN_LOCKOUT_THRESHOLD` failures per account that lock it (default 10)
+/// - `LOGIN_IP_LOCKOUT_THRESHOLD` failures per client IP that lock it out (default 50)
+/// - `LOGIN_LOCKOUT_SECS` lockout duration, also the window failures are counted in (default 900)
+pub struct LoginGuardConfig {
+    pub free_attempts: i64,
+    pub delay_base: Duration,
+    pub delay_max: Duration,
+    pub lockout_threshold: i64,
+    pub ip_lockout_threshold: i64,
+    pub lockout: Duration,
+}
+
+fn env_i64(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+impl LoginGuardConfig {
+    pub fn from_env() -> Self {
+        Self {
+            free_attempts: env_i64("LOGIN_FREE_ATTEMPTS", 3),
+            delay_base: Duration::seconds(env_i64("LOGIN_DELAY_BASE_SECS", 1)),
+            delay_max: Duration::seconds(env_i64("LOGIN_DELAY_MAX_SECS", 60)),
+            lockout_threshold: env_i64("LOGIN_LOCKOUT_THRESHOLD", 10),
+            ip_lockout_threshold: env_i64("LOGIN_IP_LOCKOUT_THRESHOLD", 50),
+            lockout: Duration::seconds(env_i64("LOGIN_LOCKOUT_SECS", 900)),
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static LoginGuardConfig {
+        static CONFIG: OnceLock<LoginGuardConfig> = OnceLock::new();
+        CONFIG.get_or_init(LoginGuardConfig::from_env)
+    }
+
+    /// Wait before the next attempt of an account after `failures` failures.
+    fn account_delay(&self, failures: i64) -> Duration {
+        if failures >= self.lockout_threshold {
+            return self.lockout;
+        }
+        let extra = failures - self.free_attempts;
+        if extra <= 0 {
+            return Duration::zero();
+        }
+        // base, 2*base, 4*base, … capped
+        let factor = 1i32.checked_shl((extra - 1).min(30) as u32).unwrap_or(i32::MAX);
+        let delay = self.delay_base.checked_mul(factor).unwrap_or(self.delay_max);
+        delay.min(self.delay_max)
+    }
+
+    /// Delays after the 1st, 2nd, … failure of an account, up to the lockout.
+    fn account_delays(&self) -> Vec<i64> {
+        (1..=self.lockout_threshold)
+            .map(|n| self.account_delay(n).num_seconds())
+            .collect()
+    }
+
+    /// Delays after the 1st, 2nd, … failure from a client IP: none until the lockout.
+    fn ip_delays(&self) -> Vec<i64> {
+        (1..=self.ip_lockout_threshold)
+            .map(|n| if n >= self.ip_lockout_threshold { self.lockout.num_seconds() } else { 0 })
+            .collect()
+    }
+}
+
+/// Where a login attempt came from.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAddr {
+    /// Recorded with the attempt.
+    pub ip: Option<String>,
+    /// `ip` is a reverse proxy's, shared by every client behind it.
+    pub shared: bool,
+}
+
+impl ClientAddr {
+    /// The address to throttle per IP; a proxy's is not, as locking it out
+    /// would lock out everyone.
+    pub fn throttle_ip(&self) -> Option<&str> {
+        self.ip.as_deref().filter(|_| !self.shared)
+    }
+}
+
+/// Emails are throttled case-insensitively.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+fn account_key(email: &str) -> String {
+    format!("email:{}", normalize_email(email))
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+/// Count an attempt of `email` from the client `ip` as failed until it proves
+/// otherwise, before any password hashing work. Returns the seconds until
+/// another attempt is allowed when the account or IP is throttled, in which
+/// case nothing is counted. Counting and checking is one statement per key, so
+/// concurrent attempts cannot all slip through.
+pub async fn claim_attempt(pool: &DbPool, email: &str, ip: Option<&str>) -> Result<Option<i64>, sqlx::Error> {
+    let cfg = LoginGuardConfig::global();
+    let now = Utc::now();
+    let since = now - cfg.lockout;
+    let mut keys = vec![account_key(email)];
+    keys.extend(ip.map(ip_key));
+
+    let mut tx = pool.begin().await?;
+    let mut admitted = login_attempts_repo::claim_attempt(&mut *tx, &keys[0], now, since, &cfg.account_delays())
+        .await?
+        .is_some();
+    if admitted {
+        if let Some(key) = keys.get(1) {
+            admitted = login_attempts_repo::claim_attempt(&mut *tx, key, now, since, &cfg.ip_delays())
+                .await?
+                .is_some();
+        }
+    }
+    if admitted {
+        tx.commit().await?;
+        return Ok(None);
+    }
+    tx.rollback().await?;
+
+    let allowed_at = login_attempts_repo::allowed_at(pool, &keys).await?;
+    Ok(Some(allowed_at.map_or(1, |t| (t - now).num_seconds().max(1))))
+}
+
+/// Take back an attempt counted by [`claim_attempt`] whose password was right.
+pub async fn release_attempt(pool: &DbPool, email: &str, ip: Option<&str>) -> Result<(), sqlx::Error> {
+    login_attempts_repo::release_attempt(pool, &account_key(email)).await?;
+    if let Some(ip) = ip {
+        login_attempts_repo::release_attempt(pool, &ip_key(ip)).await?;
+    }
+    Ok(())
+}
+
+/// Add an attempt to the audit trail. A success clears the account's failure count.
+pub async fn record(
+    pool: &DbPool,
+    email: &str,
+    user_id: Option<Uuid>,
+    ip: Option<&str>,
+    success: bool,
+    method: &str,
+) -> Result<(), sqlx::Error> {
+    let email = normalize_email(email);
+    login_attempts_repo::record_attempt(pool, &email, user_id, ip, success, method).await?;
+
+    if success {
+        login_attempts_repo::clear_failures(pool, &account_key(&email)).await?;
+    } else {
+        let cfg = LoginGuardConfig::global();
+        let stats = login_attempts_repo::account_failures(pool, &email, Utc::now() - cfg.lockout).await?;
+        if stats.failures == cfg.lockout_threshold {
+            tracing::warn!(
+                "login for {} locked for {}s after {} failed attempts (last from {})",
+                email,
+                cfg.lockout.num_seconds(),
+                stats.failures,
+                ip.unwrap_or("unknown")
+            );
+        } else {
+            tracing::info!("failed {} login for {} from {}", method, email, ip.unwrap_or("unknown"));
+        }
+    }
+    Ok(())
+}
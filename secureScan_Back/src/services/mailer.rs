@@ -0,0 +1,251 @@
+use futures_util::future::BoxFuture;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("invalid email address: {0}")]
+    Address(String),
+
+    #[error("could not build message: {0}")]
+    Build(String),
+
+    #[error("delivery failed: {0}")]
+    Transport(String),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A plain-text email.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers outgoing email. Implementations are selected with `MAIL_TRANSPORT`.
+pub trait Mailer: Send + Sync {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), MailError>>;
+}
+
+/// Settings shared by all mailers.
+///
+/// Configured through:
+/// - `MAIL_TRANSPORT` `log` (default), `file` or `smtp`
+/// - `MAIL_FROM` sender address (default `SecuraScan <no-reply@localhost>`)
+/// - `MAIL_DIR` output directory of the `file` transport (default `./mail`)
+/// - `APP_BASE_URL` frontend origin used in links (default `http://localhost:5173`)
+/// - `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD`,
+///   `SMTP_TLS` (`starttls` default, `tls` or `none`)
+pub struct MailConfig {
+    pub transport: String,
+    pub from: String,
+    pub dir: PathBuf,
+    pub app_base_url: String,
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+impl MailConfig {
+    pub fn from_env() -> Self {
+        Self {
+            transport: env_or("MAIL_TRANSPORT", "log").to_ascii_lowercase(),
+            from: env_or("MAIL_FROM", "SecuraScan <no-reply@localhost>"),
+            dir: PathBuf::from(env_or("MAIL_DIR", "./mail")),
+            app_base_url: env_or("APP_BASE_URL", "http://localhost:5173")
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static MailConfig {
+        static CONFIG: OnceLock<MailConfig> = OnceLock::new();
+        CONFIG.get_or_init(MailConfig::from_env)
+    }
+
+    /// Absolute frontend URL for `path`, e.g. `/reset-password?token=…`.
+    pub fn app_url(&self, path: &str) -> String {
+        format!("{}{}", self.app_base_url, path)
+    }
+}
+
+fn mailbox(raw: &str) -> Result<Mailbox, MailError> {
+    raw.parse::<Mailbox>().map_err(|_| MailError::Address(raw.to_string()))
+}
+
+/// Build an RFC 5322 message for `email`.
+fn build_message(from: &Mailbox, email: &Email) -> Result<Message, MailError> {
+    Message::builder()
+        .from(from.clone())
+        .to(mailbox(&email.to)?)
+        .subject(email.subject.clone())
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())
+        .map_err(|e| MailError::Build(e.to_string()))
+}
+
+/// Sends mail through an SMTP relay.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env(cfg: &MailConfig) -> Result<Self, MailError> {
+        let host = std::env::var("SMTP_HOST").map_err(|_| MailError::Transport("SMTP_HOST is not set".into()))?;
+        let tls = env_or("SMTP_TLS", "starttls").to_ascii_lowercase();
+
+        let mut builder = match tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host)),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host),
+        }
+        .map_err(|e| MailError::Transport(e.to_string()))?;
+
+        if let Some(port) = std::env::var("SMTP_PORT").ok().and_then(|p| p.parse::<u16>().ok()) {
+            builder = builder.port(port);
+        }
+        if let Ok(username) = std::env::var("SMTP_USERNAME") {
+            let password = std::env::var("SMTP_PASSWORD").unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: mailbox(&cfg.from)?,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), MailError>> {
+        Box::pin(async move {
+            let message = build_message(&self.from, email)?;
+            self.transport
+                .send(message)
+                .await
+                .map_err(|e| MailError::Transport(e.to_string()))?;
+            Ok(())
+        })
+    }
+}
+
+/// Writes each message as an `.eml` file, for local development and tests.
+pub struct FileMailer {
+    dir: PathBuf,
+    from: Mailbox,
+}
+
+impl FileMailer {
+    pub fn new(dir: PathBuf, from: &str) -> Result<Self, MailError> {
+        Ok(Self { dir, from: mailbox(from)? })
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), MailError>> {
+        Box::pin(async move {
+            let message = build_message(&self.from, email)?;
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let name = format!("{}-{}.eml", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"), Uuid::new_v4());
+            let path = self.dir.join(name);
+            tokio::fs::write(&path, message.formatted()).await?;
+            tracing::info!("mail to {} written to {}", email.to, path.display());
+            Ok(())
+        })
+    }
+}
+
+/// Replace the value of every `token=` in `text`, so links that grant access
+/// (verification, password reset) do not end up in logs.
+fn redact_tokens(text: &str) -> String {
+    const KEY: &str = "token=";
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(KEY) {
+        out.push_str(&rest[..i + KEY.len()]);
+        out.push_str("[redacted]");
+        rest = &rest[i + KEY.len()..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '&' || c == '#')
+            .unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Logs messages instead of sending them. The default when nothing is configured.
+/// Tokens in links are redacted; use the `file` transport to follow them locally.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), MailError>> {
+        Box::pin(async move {
+            tracing::info!("mail to {} | {}\n{}", email.to, email.subject, redact_tokens(&email.body));
+            Ok(())
+        })
+    }
+}
+
+/// The mailer selected by `MAIL_TRANSPORT`.
+pub fn from_env() -> Result<Arc<dyn Mailer>, MailError> {
+    let cfg = MailConfig::global();
+    let mailer: Arc<dyn Mailer> = match cfg.transport.as_str() {
+        "smtp" => Arc::new(SmtpMailer::from_env(cfg)?),
+        "file" => Arc::new(FileMailer::new(cfg.dir.clone(), &cfg.from)?),
+        "log" => {
+            tracing::warn!("MAIL_TRANSPORT=log: mail is only logged, with tokens redacted; set MAIL_TRANSPORT=smtp to deliver it");
+            Arc::new(LogMailer)
+        }
+        other => {
+            tracing::warn!("unknown MAIL_TRANSPORT {:?}; logging mail instead, with tokens redacted", other);
+            Arc::new(LogMailer)
+        }
+    };
+    Ok(mailer)
+}
+
+/// Send without making the caller wait for delivery; failures are logged.
+/// Also keeps response times independent of whether a mail was sent.
+pub fn send_in_background(mailer: Arc<dyn Mailer>, email: Email) {
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(&email).await {
+            tracing::error!("failed to send mail to {}: {}", email.to, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_tokens_in_links() {
+        assert_eq!(
+            redact_tokens("Verify: https://app/verify-email?token=abc123\nThanks"),
+            "Verify: https://app/verify-email?token=[redacted]\nThanks"
+        );
+        assert_eq!(
+            redact_tokens("https://app/reset?token=abc&next=/x#top token=def"),
+            "https://app/reset?token=[redacted]&next=/x#top token=[redacted]"
+        );
+        assert_eq!(redact_tokens("ends with token="), "ends with token=[redacted]");
+        assert_eq!(redact_tokens("nothing to hide"), "nothing to hide");
+    }
+}
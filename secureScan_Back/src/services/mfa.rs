@@ -0,0 +1,251 @@
+use chrono::Utc;
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::mfa_repo::{self, UserMfaRow};
+use crate::domain::refresh_tokens_repo;
+use crate::services::auth::{hash_token, AuthError};
+use crate::services::secret_box::{self, SecretBox};
+
+/// Name shown by authenticator apps.
+const TOTP_ISSUER: &str = "SecuraScan";
+const TOTP_DIGITS: u32 = 6;
+const TOTP_PERIOD_SECS: i64 = 30;
+/// Codes of the neighbouring time steps are accepted to tolerate clock drift.
+const TOTP_SKEW_STEPS: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// A started enrollment, shown to the user once.
+#[derive(Debug, serde::Serialize)]
+pub struct Enrollment {
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code.
+    pub provisioning_uri: String,
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// RFC 4226 HOTP value for `counter`.
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset] & 0x7f, digest[offset + 1], digest[offset + 2], digest[offset + 3]]);
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+/// The time step whose code equals `code`, if any within the allowed skew.
+fn matching_step(secret: &str, code: &str) -> Option<i64> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let now = Utc::now().timestamp() / TOTP_PERIOD_SECS;
+    (now - TOTP_SKEW_STEPS..=now + TOTP_SKEW_STEPS).find(|step| {
+        let expected = format!("{:0width$}", hotp(&key, *step as u64), width = TOTP_DIGITS as usize);
+        bool::from(expected.as_bytes().ct_eq(code.as_bytes()))
+    })
+}
+
+fn provisioning_uri(secret: &str, email: &str) -> String {
+    let label: String = url::form_urlencoded::byte_serialize(format!("{}:{}", TOTP_ISSUER, email).as_bytes()).collect();
+    format!(
+        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        label, secret, TOTP_ISSUER, TOTP_DIGITS, TOTP_PERIOD_SECS
+    )
+}
+
+/// Recovery codes are compared without case, spaces or dashes.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Ten random codes formatted as `xxxxx-xxxxx`, with the hashes to store.
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+    let mut hashes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+    for _ in 0..RECOVERY_CODE_COUNT {
+        let mut bytes = [0u8; 7];
+        OsRng.fill_bytes(&mut bytes);
+        let raw = BASE32_NOPAD.encode(&bytes).to_ascii_lowercase();
+        let code = format!("{}-{}", &raw[..5], &raw[5..10]);
+        hashes.push(hash_token(&normalize_recovery_code(&code)));
+        codes.push(code);
+    }
+    (codes, hashes)
+}
+
+/// The decrypted TOTP secret of an enrollment.
+fn totp_secret(mfa: &UserMfaRow) -> Result<String, AuthError> {
+    SecretBox::global().open(&mfa.totp_secret).map_err(|e| {
+        tracing::error!("TOTP secret of user {}: {}", mfa.user_id, e);
+        AuthError::SecretUnavailable
+    })
+}
+
+/// Encrypt TOTP secrets stored in plaintext by earlier versions.
+pub async fn seal_stored_secrets(pool: &DbPool) -> Result<u64, sqlx::Error> {
+    let mut sealed = 0;
+    for mfa in mfa_repo::list_unsealed(pool).await? {
+        if secret_box::is_sealed(&mfa.totp_secret) {
+            continue;
+        }
+        let encrypted = SecretBox::global().seal(&mfa.totp_secret);
+        if mfa_repo::replace_secret(pool, mfa.user_id, &mfa.totp_secret, &encrypted).await? {
+            sealed += 1;
+        }
+    }
+    Ok(sealed)
+}
+
+/// Start enrolling a TOTP secret. Restarting replaces a pending secret; a
+/// confirmed one must be disabled first.
+pub async fn enroll(pool: &DbPool, user_id: Uuid, email: &str) -> Result<Enrollment, AuthError> {
+    let secret = generate_secret();
+    mfa_repo::upsert_pending(pool, user_id, &SecretBox::global().seal(&secret))
+        .await?
+        .ok_or(AuthError::MfaAlreadyEnabled)?;
+    Ok(Enrollment {
+        provisioning_uri: provisioning_uri(&secret, email),
+        secret,
+    })
+}
+
+/// Confirm a pending enrollment with a current code. Returns the recovery codes,
+/// which are not recoverable afterwards. Existing sessions are revoked so every
+/// remaining session has passed the second factor.
+pub async fn confirm(pool: &DbPool, user_id: Uuid, code: &str) -> Result<Vec<String>, AuthError> {
+    let mfa = mfa_repo::get_mfa(pool, user_id).await?.ok_or(AuthError::MfaNotEnabled)?;
+    if mfa.enabled_at.is_some() {
+        return Err(AuthError::MfaAlreadyEnabled);
+    }
+    let step = matching_step(&totp_secret(&mfa)?, code).ok_or(AuthError::InvalidMfaCode)?;
+
+    let (codes, hashes) = generate_recovery_codes();
+    let mut tx = pool.begin().await?;
+    if !mfa_repo::claim_step(&mut *tx, user_id, step).await? {
+        return Err(AuthError::InvalidMfaCode);
+    }
+    mfa_repo::mark_enabled(&mut *tx, user_id).await?;
+    mfa_repo::replace_recovery_codes(&mut *tx, user_id, &hashes).await?;
+    tx.commit().await?;
+
+    refresh_tokens_repo::revoke_user_tokens(pool, user_id).await?;
+    Ok(codes)
+}
+
+/// Check a TOTP code of a confirmed enrollment. Each code is accepted once.
+pub async fn verify_totp(pool: &DbPool, user_id: Uuid, code: &str) -> Result<(), AuthError> {
+    let mfa = mfa_repo::get_mfa(pool, user_id)
+        .await?
+        .filter(|m| m.enabled_at.is_some())
+        .ok_or(AuthError::MfaNotEnabled)?;
+    let step = matching_step(&totp_secret(&mfa)?, code).ok_or(AuthError::InvalidMfaCode)?;
+    if !mfa_repo::claim_step(pool, user_id, step).await? {
+        return Err(AuthError::InvalidMfaCode);
+    }
+    Ok(())
+}
+
+/// Check a second factor: a TOTP code or an unused recovery code.
+pub async fn verify_second_factor(pool: &DbPool, user_id: Uuid, code: &str) -> Result<(), AuthError> {
+    let code = code.trim();
+    if code.bytes().all(|b| b.is_ascii_digit()) {
+        return verify_totp(pool, user_id, code).await;
+    }
+
+    if !mfa_repo::is_enabled(pool, user_id).await? {
+        return Err(AuthError::MfaNotEnabled);
+    }
+    let hash = hash_token(&normalize_recovery_code(code));
+    if !mfa_repo::consume_recovery_code(pool, user_id, &hash).await? {
+        return Err(AuthError::InvalidMfaCode);
+    }
+    let remaining = mfa_repo::count_unused_recovery_codes(pool, user_id).await?;
+    tracing::info!("user {} used a recovery code; {} left", user_id, remaining);
+    Ok(())
+}
+
+/// Replace all recovery codes after checking a TOTP code.
+pub async fn regenerate_recovery_codes(pool: &DbPool, user_id: Uuid, code: &str) -> Result<Vec<String>, AuthError> {
+    verify_totp(pool, user_id, code).await?;
+    let (codes, hashes) = generate_recovery_codes();
+    mfa_repo::replace_recovery_codes(pool, user_id, &hashes).await?;
+    Ok(codes)
+}
+
+/// Turn two-factor authentication off after checking a second factor.
+pub async fn disable(pool: &DbPool, user_id: Uuid, code: &str) -> Result<(), AuthError> {
+    verify_second_factor(pool, user_id, code).await?;
+    mfa_repo::delete_mfa(pool, user_id).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4226 appendix D.
+    #[test]
+    fn hotp_test_vectors() {
+        let key = b"12345678901234567890";
+        let expected = [755224, 287082, 359152, 969429, 338314, 254676, 287922, 162583, 399871, 520489];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(key, counter as u64), *code, "counter {}", counter);
+        }
+    }
+
+    fn code_at(secret: &str, step: i64) -> String {
+        let key = BASE32_NOPAD.decode(secret.as_bytes()).unwrap();
+        format!("{:06}", hotp(&key, step as u64))
+    }
+
+    #[test]
+    fn matching_step_accepts_neighbouring_steps_only() {
+        let secret = generate_secret();
+        let now = Utc::now().timestamp() / TOTP_PERIOD_SECS;
+        assert!(matching_step(&secret, &code_at(&secret, now)).is_some());
+        assert!(matching_step(&secret, &code_at(&secret, now - 1)).is_some());
+        assert!(matching_step(&secret, &format!(" {} ", code_at(&secret, now))).is_some());
+
+        // Unless it happens to equal a code of the allowed window.
+        let stale = code_at(&secret, now - 5);
+        if !(now - 1..=now + 2).any(|step| code_at(&secret, step) == stale) {
+            assert_eq!(matching_step(&secret, &stale), None);
+        }
+    }
+
+    #[test]
+    fn matching_step_rejects_malformed_codes() {
+        let secret = generate_secret();
+        assert_eq!(matching_step(&secret, ""), None);
+        assert_eq!(matching_step(&secret, "12345"), None);
+        assert_eq!(matching_step(&secret, "1234567"), None);
+        assert_eq!(matching_step(&secret, "12a456"), None);
+        assert_eq!(matching_step("not base32!", "123456"), None);
+    }
+
+    #[test]
+    fn recovery_codes_are_normalized() {
+        assert_eq!(normalize_recovery_code(" AbCdE-fGhIj "), "abcdefghij");
+        let (codes, hashes) = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(hash_token(&normalize_recovery_code(&codes[0].to_uppercase())), hashes[0]);
+    }
+}
@@ -1 +1,17 @@
 pub mod auth;
+pub mod commit_reports;
+pub mod egress;
+pub mod exports;
+pub mod login_guard;
+pub mod mailer;
+pub mod mfa;
+pub mod notifications;
+pub mod oidc;
+pub mod orgs;
+pub mod outbound_webhooks;
+pub mod password_policy;
+pub mod reports;
+pub mod risk;
+pub mod scan_findings;
+pub mod secret_box;
+pub mod webhooks;
@@ -0,0 +1,373 @@
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::findings::{Finding, Severity, SeverityCounts};
+use crate::domain::notification_channels_repo::{self, NotificationChannelRow};
+use crate::domain::scans_repo;
+use crate::services::mailer::MailConfig;
+use crate::services::scan_findings::OpenFindings;
+
+/// Chat services a channel can post to.
+pub mod kind {
+    /// Slack incoming webhook, formatted with Block Kit.
+    pub const SLACK: &str = "slack";
+    /// Microsoft Teams incoming webhook (Workflows or connector), formatted as an Adaptive Card.
+    pub const TEAMS: &str = "teams";
+
+    pub const ALL: [&str; 2] = [SLACK, TEAMS];
+}
+
+/// Whether `url` is an incoming webhook of the chat service `kind`: HTTPS on the
+/// default port to Slack's webhook host, or to the Microsoft hosts serving Teams
+/// connector and Workflows webhooks. Anything else would let a channel post scan
+/// results to, or probe, arbitrary servers.
+pub fn is_webhook_url(kind: &str, url: &url::Url) -> bool {
+    if url.scheme() != "https" || url.port().is_some_and(|p| p != 443) {
+        return false;
+    }
+    let Some(url::Host::Domain(host)) = url.host() else {
+        return false;
+    };
+    let host = host.to_ascii_lowercase();
+    match kind {
+        kind::SLACK => host == "hooks.slack.com",
+        kind::TEAMS => {
+            host == "outlook.office.com"
+                || host.ends_with(".webhook.office.com")
+                || host.ends_with(".logic.azure.com")
+                || host.ends_with(".api.powerplatform.com")
+        }
+        _ => false,
+    }
+}
+
+/// Findings listed in a message; the rest are only counted.
+const MAX_LISTED_FINDINGS: usize = 5;
+
+fn http() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .user_agent("SecuraScan")
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// What a message says about a scan.
+struct Message {
+    title: String,
+    /// One line under the title.
+    summary: String,
+    /// Label / value pairs (target, grade, counts, ...).
+    facts: Vec<(String, String)>,
+    /// Findings that triggered the message, most severe first.
+    findings: Vec<Finding>,
+    /// How many triggering findings there are in total.
+    finding_total: usize,
+    /// Fingerprints of findings absent from the previous completed scan, marked as new.
+    new: HashSet<String>,
+    link: Option<String>,
+    /// Red for findings / failures, neutral for tests.
+    alert: bool,
+}
+
+/// A finished scan's open findings, shared by every channel it is routed to.
+struct ScanSummary {
+    scan: scans_repo::ScanRow,
+    counts: SeverityCounts,
+    /// Open (unsuppressed) findings, most severe first.
+    open: Vec<Finding>,
+    /// Fingerprints of open findings absent from the previous completed scan.
+    new: HashSet<String>,
+}
+
+impl ScanSummary {
+    async fn load(pool: &DbPool, scan: scans_repo::ScanRow) -> anyhow::Result<Self> {
+        let findings = OpenFindings::load(pool, &scan).await?;
+        Ok(Self {
+            new: findings.new_fingerprints(),
+            counts: findings.counts,
+            open: findings.open,
+            scan,
+        })
+    }
+
+    /// The findings that make `channel` fire, if any.
+    fn triggering(&self, channel: &NotificationChannelRow) -> Vec<Finding> {
+        let threshold = Severity::parse(&channel.min_severity).unwrap_or(Severity::Critical);
+        self.open
+            .iter()
+            .filter(|f| f.severity >= threshold && (!channel.only_new || self.new.contains(&f.fingerprint())))
+            .cloned()
+            .collect()
+    }
+
+    fn message(&self, channel: &NotificationChannelRow, triggering: Vec<Finding>) -> Message {
+        let c = &self.counts;
+        let mut facts = vec![("Target".to_string(), self.scan.url.clone())];
+        if let (Some(grade), Some(score)) = (&self.scan.grade, self.scan.risk_score) {
+            facts.push(("Grade".to_string(), format!("{} (risk score {})", grade, score)));
+        }
+        facts.push((
+            "Open findings".to_string(),
+            format!(
+                "{} critical, {} high, {} medium, {} low, {} info",
+                c.critical, c.high, c.medium, c.low, c.info
+            ),
+        ));
+        let new_word = if channel.only_new { "new " } else { "" };
+        Message {
+            title: format!("{} {}finding(s) at or above {} on {}", triggering.len(), new_word, channel.min_severity, self.scan.url),
+            summary: format!("Scan {} completed with {} open finding(s).", self.scan.id, c.total),
+            facts,
+            finding_total: triggering.len(),
+            findings: triggering.into_iter().take(MAX_LISTED_FINDINGS).collect(),
+            new: self.new.clone(),
+            link: Some(scan_link(self.scan.id)),
+            alert: true,
+        }
+    }
+}
+
+fn scan_link(scan_id: Uuid) -> String {
+    MailConfig::global().app_url(&format!("/dashboard/{}", scan_id))
+}
+
+fn failure_message(scan: &scans_repo::ScanRow) -> Message {
+    Message {
+        title: format!("Scan of {} failed", scan.url),
+        summary: format!("Scan {} ended without results.", scan.id),
+        facts: vec![("Target".to_string(), scan.url.clone())],
+        findings: Vec::new(),
+        finding_total: 0,
+        new: HashSet::new(),
+        link: Some(scan_link(scan.id)),
+        alert: true,
+    }
+}
+
+fn test_message(channel: &NotificationChannelRow) -> Message {
+    let targets = if channel.target_ids.is_empty() {
+        "all targets".to_string()
+    } else {
+        format!("{} target(s)", channel.target_ids.len())
+    };
+    Message {
+        title: format!("SecuraScan test message for {}", channel.name),
+        summary: "This channel is set up to receive scan notifications.".to_string(),
+        facts: vec![
+            ("Minimum severity".to_string(), channel.min_severity.clone()),
+            ("Only new findings".to_string(), if channel.only_new { "yes" } else { "no" }.to_string()),
+            ("Targets".to_string(), targets),
+            ("Failed scans".to_string(), if channel.notify_on_failure { "notified" } else { "not notified" }.to_string()),
+        ],
+        findings: Vec::new(),
+        finding_total: 0,
+        new: HashSet::new(),
+        link: None,
+        alert: false,
+    }
+}
+
+impl Message {
+    fn finding_lines(&self, bold: impl Fn(&str) -> String, code: impl Fn(&str) -> String) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .findings
+            .iter()
+            .map(|f| {
+                format!(
+                    "{} {} at {}{}",
+                    bold(&f.severity.as_str().to_uppercase()),
+                    f.title,
+                    code(&f.location),
+                    if self.new.contains(&f.fingerprint()) { " (new)" } else { "" }
+                )
+            })
+            .collect();
+        if self.finding_total > self.findings.len() {
+            lines.push(format!("… and {} more", self.finding_total - self.findings.len()));
+        }
+        lines
+    }
+
+    /// Slack Block Kit payload.
+    fn slack(&self) -> JsonValue {
+        let fields: Vec<JsonValue> = self
+            .facts
+            .iter()
+            .map(|(label, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", slack_escape(label), slack_escape(value)) }))
+            .collect();
+        let mut blocks = vec![
+            json!({ "type": "header", "text": { "type": "plain_text", "text": truncate(&self.title, 150) } }),
+            json!({ "type": "section", "text": { "type": "mrkdwn", "text": slack_escape(&self.summary) }, "fields": fields }),
+        ];
+        let lines = self.finding_lines(|s| format!("*{}*", s), |s| format!("`{}`", s.replace('`', "'")));
+        if !lines.is_empty() {
+            let text = lines
+                .iter()
+                .map(|l| format!("• {}", slack_escape(l)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            blocks.push(json!({ "type": "section", "text": { "type": "mrkdwn", "text": truncate(&text, 3000) } }));
+        }
+        if let Some(link) = &self.link {
+            blocks.push(json!({
+                "type": "actions",
+                "elements": [{
+                    "type": "button",
+                    "text": { "type": "plain_text", "text": "View scan" },
+                    "url": link,
+                    "style": if self.alert { "danger" } else { "primary" },
+                }],
+            }));
+        }
+        json!({ "text": self.title, "blocks": blocks })
+    }
+
+    /// Teams message carrying an Adaptive Card.
+    fn teams(&self) -> JsonValue {
+        let facts: Vec<JsonValue> = self
+            .facts
+            .iter()
+            .map(|(label, value)| json!({ "title": label, "value": value }))
+            .collect();
+        let mut body = vec![
+            json!({
+                "type": "TextBlock",
+                "text": self.title,
+                "size": "Large",
+                "weight": "Bolder",
+                "wrap": true,
+                "color": if self.alert { "Attention" } else { "Default" },
+            }),
+            json!({ "type": "TextBlock", "text": self.summary, "wrap": true, "isSubtle": true }),
+            json!({ "type": "FactSet", "facts": facts }),
+        ];
+        for line in self.finding_lines(|s| format!("**{}**", s), |s| format!("`{}`", s)) {
+            body.push(json!({ "type": "TextBlock", "text": format!("- {}", line), "wrap": true, "spacing": "Small" }));
+        }
+        let actions: Vec<JsonValue> = self
+            .link
+            .iter()
+            .map(|link| json!({ "type": "Action.OpenUrl", "title": "View scan", "url": link }))
+            .collect();
+        json!({
+            "type": "message",
+            "summary": self.title,
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "msteams": { "width": "Full" },
+                    "body": body,
+                    "actions": actions,
+                },
+            }],
+        })
+    }
+}
+
+/// Slack treats `&`, `<` and `>` as control characters in mrkdwn.
+fn slack_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max - 1).collect();
+    out.push('…');
+    out
+}
+
+/// Post `message` to the channel and record the outcome on it.
+async fn deliver(pool: &DbPool, channel: &NotificationChannelRow, message: &Message) -> anyhow::Result<()> {
+    let result = post(channel, message).await;
+    let error = result.as_ref().err().map(|e| e.to_string());
+    if let Err(e) = notification_channels_repo::record_send(pool, channel.id, error.as_deref()).await {
+        tracing::error!("notifications: could not record outcome for channel {}: {}", channel.id, e);
+    }
+    result
+}
+
+async fn post(channel: &NotificationChannelRow, message: &Message) -> anyhow::Result<()> {
+    let body = if channel.kind == kind::TEAMS {
+        message.teams()
+    } else {
+        message.slack()
+    };
+    // Channels saved before hosts were checked are not posted to either.
+    let url = url::Url::parse(&channel.webhook_url)?;
+    if !is_webhook_url(&channel.kind, &url) {
+        anyhow::bail!("webhook URL is not a {} incoming webhook", channel.kind);
+    }
+    let res = http().post(url).json(&body).send().await?;
+    let status = res.status();
+    if !status.is_success() {
+        // The response body is not passed on: it is whatever the remote server chose to say.
+        anyhow::bail!("{} webhook answered {}", channel.kind, status.as_u16());
+    }
+    Ok(())
+}
+
+/// Send a test message describing the channel's routing rules.
+pub async fn send_test(pool: &DbPool, channel: &NotificationChannelRow) -> anyhow::Result<()> {
+    deliver(pool, channel, &test_message(channel)).await
+}
+
+/// Notify the organization's channels about a scan that just completed or failed.
+/// Failures are logged, never returned.
+pub async fn scan_finished(pool: &DbPool, scan_id: Uuid) {
+    if let Err(e) = notify(pool, scan_id).await {
+        tracing::error!("notifications: could not notify about scan {}: {}", scan_id, e);
+    }
+}
+
+async fn notify(pool: &DbPool, scan_id: Uuid) -> anyhow::Result<()> {
+    let Some(scan) = scans_repo::get_scan(pool, scan_id).await? else {
+        return Ok(());
+    };
+    let Some(org_id) = scan.org_id else {
+        return Ok(());
+    };
+    let channels: Vec<_> = notification_channels_repo::list_enabled_channels(pool, org_id)
+        .await?
+        .into_iter()
+        .filter(|c| c.covers_target(scan.target_id))
+        .collect();
+    if channels.is_empty() {
+        return Ok(());
+    }
+
+    if scan.status != "completed" {
+        let message = failure_message(&scan);
+        for channel in channels.iter().filter(|c| c.notify_on_failure) {
+            if let Err(e) = deliver(pool, channel, &message).await {
+                tracing::warn!("notifications: channel {} ({}) failed: {}", channel.id, channel.kind, e);
+            }
+        }
+        return Ok(());
+    }
+
+    let summary = ScanSummary::load(pool, scan).await?;
+    for channel in &channels {
+        let triggering = summary.triggering(channel);
+        if triggering.is_empty() {
+            continue;
+        }
+        if let Err(e) = deliver(pool, channel, &summary.message(channel, triggering)).await {
+            tracing::warn!("notifications: channel {} ({}) failed: {}", channel.id, channel.kind, e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,679 @@
+use chrono::{Duration, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use rand_core::{OsRng, RngCore};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Instant;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::orgs_repo::{self, Role};
+use crate::domain::user::User;
+use crate::domain::{oidc_repo, users_repo};
+use crate::services::auth::{self as auth_svc, hash_password, hash_token, AuthError, LoginStep};
+use crate::services::login_guard::{self, method};
+
+/// How long a user may spend at the identity provider before the login state expires.
+pub const STATE_TTL_SECS: i64 = 600;
+/// Keys are refetched for an unknown `kid` at most this often.
+const JWKS_MIN_REFRESH_SECS: u64 = 30;
+/// Signature algorithms accepted for ID tokens.
+const ID_TOKEN_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug, Error)]
+pub enum OidcError {
+    #[error("identity provider unavailable: {0}")]
+    Provider(String),
+
+    #[error("unknown or expired login state")]
+    InvalidState,
+
+    #[error("identity provider refused the login: {0}")]
+    Denied(String),
+
+    #[error("invalid ID token: {0}")]
+    InvalidIdToken(String),
+
+    #[error("the identity provider did not share an email address")]
+    MissingEmail,
+
+    #[error("the identity provider has not verified the email address")]
+    EmailUnverified,
+
+    #[error("no account is linked to this identity")]
+    NotProvisioned,
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+
+    #[error("database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+impl OidcError {
+    /// Code passed to the frontend as `?sso_error=`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            OidcError::Provider(_) => "provider_unavailable",
+            OidcError::InvalidState => "invalid_state",
+            OidcError::Denied(_) => "access_denied",
+            OidcError::InvalidIdToken(_) => "invalid_id_token",
+            OidcError::MissingEmail => "missing_email",
+            OidcError::EmailUnverified => "email_not_verified",
+            OidcError::NotProvisioned => "not_provisioned",
+            OidcError::Auth(_) | OidcError::Db(_) => "internal_error",
+        }
+    }
+}
+
+/// OpenID Connect single sign-on, enabled by setting `OIDC_ISSUER`.
+///
+/// Configured through:
+/// - `OIDC_ISSUER` issuer URL; its `/.well-known/openid-configuration` is used for discovery
+/// - `OIDC_CLIENT_ID`, `OIDC_CLIENT_SECRET` (leave the secret unset for a public client)
+/// - `OIDC_REDIRECT_URI` this backend's callback (default `http://localhost:8080/api/auth/oidc/callback`)
+/// - `OIDC_SCOPES` (default `openid email profile`)
+/// - `OIDC_GROUPS_CLAIM` claim listing the user's groups (default `groups`)
+/// - `OIDC_PROVIDER_NAME` label of the login button (default `SSO`)
+/// - `OIDC_AUTO_PROVISION` create accounts on first login (default true)
+/// - `OIDC_LINK_BY_EMAIL` link to an existing account with the same verified email (default false)
+/// - `OIDC_GROUP_MAPPING_ORGS` comma-separated ids of the organizations allowed to map IdP
+///   groups; group names are not scoped to an organization, so the operator vouches for them
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: String,
+    pub groups_claim: String,
+    pub provider_name: String,
+    pub auto_provision: bool,
+    pub link_by_email: bool,
+    pub group_mapping_orgs: Vec<Uuid>,
+}
+
+fn env_opt(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn env_flag(name: &str, default: bool) -> bool {
+    env_opt(name)
+        .map(|v| !matches!(v.to_ascii_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(default)
+}
+
+impl OidcConfig {
+    /// `None` when single sign-on is not configured.
+    pub fn from_env() -> Option<Self> {
+        let issuer = env_opt("OIDC_ISSUER")?.trim_end_matches('/').to_string();
+        let Some(client_id) = env_opt("OIDC_CLIENT_ID") else {
+            tracing::error!("OIDC_ISSUER is set without OIDC_CLIENT_ID; single sign-on disabled");
+            return None;
+        };
+        Some(Self {
+            issuer,
+            client_id,
+            client_secret: env_opt("OIDC_CLIENT_SECRET"),
+            redirect_uri: env_opt("OIDC_REDIRECT_URI")
+                .unwrap_or_else(|| "http://localhost:8080/api/auth/oidc/callback".into()),
+            scopes: env_opt("OIDC_SCOPES").unwrap_or_else(|| "openid email profile".into()),
+            groups_claim: env_opt("OIDC_GROUPS_CLAIM").unwrap_or_else(|| "groups".into()),
+            provider_name: env_opt("OIDC_PROVIDER_NAME").unwrap_or_else(|| "SSO".into()),
+            auto_provision: env_flag("OIDC_AUTO_PROVISION", true),
+            link_by_email: env_flag("OIDC_LINK_BY_EMAIL", false),
+            group_mapping_orgs: env_opt("OIDC_GROUP_MAPPING_ORGS")
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|id| !id.is_empty())
+                        .filter_map(|id| match Uuid::parse_str(id) {
+                            Ok(id) => Some(id),
+                            Err(_) => {
+                                tracing::error!("OIDC_GROUP_MAPPING_ORGS: {:?} is not an organization id", id);
+                                None
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Whether members of `org_id` may be granted roles through IdP groups.
+    pub fn allows_group_mappings(&self, org_id: Uuid) -> bool {
+        self.group_mapping_orgs.contains(&org_id)
+    }
+}
+
+/// The parts of the discovery document used here.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    userinfo_endpoint: Option<String>,
+}
+
+#[derive(Clone)]
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    jwks_fetched_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// What the identity provider asserts about the user.
+#[derive(Debug)]
+struct IdpUser {
+    subject: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    /// `None` when the provider sent no groups claim at all.
+    groups: Option<Vec<String>>,
+}
+
+/// A login sent to the provider.
+pub struct LoginStart {
+    /// Provider URL to send the browser to.
+    pub url: String,
+    /// Secret for the browser starting the login to keep (in a cookie) and
+    /// present at the callback, so nobody else's browser can finish it.
+    pub browser_binding: String,
+}
+
+/// Result of a completed callback.
+pub struct SsoLogin {
+    pub step: LoginStep,
+    /// Frontend path the user started from.
+    pub redirect_to: Option<String>,
+}
+
+/// OIDC relying party. The provider's metadata and signing keys are fetched on
+/// first use and cached.
+pub struct OidcClient {
+    pub config: OidcConfig,
+    http: reqwest::Client,
+    provider: RwLock<Option<Provider>>,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+/// The algorithm an ID token signed with `jwk` is verified with: the key's own
+/// `alg`, or for keys without one the header's if it fits the key type. The
+/// header alone never decides.
+fn signing_algorithm(jwk: &Jwk, header_alg: Algorithm) -> Result<Algorithm, OidcError> {
+    let alg = match jwk.common.key_algorithm {
+        Some(key_alg) => Algorithm::from_str(&key_alg.to_string())
+            .map_err(|_| OidcError::InvalidIdToken(format!("signing key has unusable alg {}", key_alg)))?,
+        None => header_alg,
+    };
+    if alg != header_alg {
+        return Err(OidcError::InvalidIdToken(format!("token alg {:?} does not match key alg {:?}", header_alg, alg)));
+    }
+    let fits_key = match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => matches!(
+            alg,
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 | Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512
+        ),
+        AlgorithmParameters::EllipticCurve(ec) => matches!(
+            (&ec.curve, alg),
+            (EllipticCurve::P256, Algorithm::ES256) | (EllipticCurve::P384, Algorithm::ES384)
+        ),
+        AlgorithmParameters::OctetKeyPair(_) => alg == Algorithm::EdDSA,
+        AlgorithmParameters::OctetKey(_) => false,
+    };
+    if !fits_key || !ID_TOKEN_ALGORITHMS.contains(&alg) {
+        return Err(OidcError::InvalidIdToken(format!("{:?} is not accepted for this key", alg)));
+    }
+    Ok(alg)
+}
+
+/// Only same-site paths are accepted as post-login destinations.
+pub fn safe_redirect(path: Option<&str>) -> Option<String> {
+    let path = path?.trim();
+    let ok = path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') && path.len() <= 512;
+    ok.then(|| path.to_string())
+}
+
+impl OidcClient {
+    pub fn from_env() -> Option<Self> {
+        let config = OidcConfig::from_env()?;
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .ok()?;
+        Some(Self {
+            config,
+            http,
+            provider: RwLock::new(None),
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, OidcError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| OidcError::Provider(format!("GET {}: {}", url, e)))?
+            .json::<T>()
+            .await
+            .map_err(|e| OidcError::Provider(format!("GET {}: {}", url, e)))
+    }
+
+    async fn discover(&self) -> Result<Provider, OidcError> {
+        let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(OidcError::Provider(format!(
+                "discovery document is for issuer {}, expected {}",
+                metadata.issuer, self.config.issuer
+            )));
+        }
+        let jwks: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+        tracing::info!("OIDC provider {} discovered with {} signing key(s)", metadata.issuer, jwks.keys.len());
+        Ok(Provider {
+            metadata,
+            jwks,
+            jwks_fetched_at: Instant::now(),
+        })
+    }
+
+    async fn provider(&self) -> Result<Provider, OidcError> {
+        if let Some(p) = self.provider.read().await.as_ref() {
+            return Ok(p.clone());
+        }
+        let mut slot = self.provider.write().await;
+        if let Some(p) = slot.as_ref() {
+            return Ok(p.clone());
+        }
+        let p = self.discover().await?;
+        *slot = Some(p.clone());
+        Ok(p)
+    }
+
+    /// Refetch the signing keys after a key rotation, rate limited.
+    async fn refresh_jwks(&self) -> Result<Provider, OidcError> {
+        let mut slot = self.provider.write().await;
+        let current = match slot.as_ref() {
+            Some(p) => p.clone(),
+            None => self.discover().await?,
+        };
+        if current.jwks_fetched_at.elapsed().as_secs() < JWKS_MIN_REFRESH_SECS {
+            return Ok(current);
+        }
+        let jwks: JwkSet = self.get_json(&current.metadata.jwks_uri).await?;
+        let refreshed = Provider {
+            jwks,
+            jwks_fetched_at: Instant::now(),
+            ..current
+        };
+        *slot = Some(refreshed.clone());
+        Ok(refreshed)
+    }
+
+    /// Start a login: remember state, nonce and PKCE verifier, and return the
+    /// provider URL to send the browser to.
+    pub async fn authorization_url(&self, pool: &DbPool, redirect_to: Option<&str>) -> Result<LoginStart, OidcError> {
+        let provider = self.provider().await?;
+        let state = random_token();
+        let browser_binding = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+        let code_challenge = BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()));
+
+        oidc_repo::insert_login_state(
+            pool,
+            &hash_token(&state),
+            &hash_token(&browser_binding),
+            &nonce,
+            &code_verifier,
+            safe_redirect(redirect_to).as_deref(),
+            Utc::now() + Duration::seconds(STATE_TTL_SECS),
+        )
+        .await?;
+
+        let url = url::Url::parse_with_params(
+            &provider.metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", self.config.scopes.as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| OidcError::Provider(format!("invalid authorization_endpoint: {}", e)))?;
+        Ok(LoginStart {
+            url: url.into(),
+            browser_binding,
+        })
+    }
+
+    /// Finish a login from the provider's redirect: check the state and that
+    /// `browser_binding` is the one given to the browser that started it,
+    /// redeem the code, validate the ID token and sign the matching user in.
+    pub async fn complete_login(
+        &self,
+        pool: &DbPool,
+        code: &str,
+        state: &str,
+        browser_binding: &str,
+        ip: Option<&str>,
+    ) -> Result<SsoLogin, OidcError> {
+        let login = oidc_repo::consume_login_state(pool, &hash_token(state), &hash_token(browser_binding))
+            .await?
+            .ok_or(OidcError::InvalidState)?;
+        let provider = self.provider().await?;
+
+        let tokens = self.exchange_code(&provider, code, &login.code_verifier).await?;
+        let id_token = tokens
+            .id_token
+            .ok_or_else(|| OidcError::InvalidIdToken("token response has no id_token".into()))?;
+        let mut claims = self.validate_id_token(provider, &id_token).await?;
+
+        if claims.get("nonce").and_then(Value::as_str) != Some(login.nonce.as_str()) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".into()));
+        }
+
+        // Some providers only put email and groups in the userinfo response.
+        let needs_userinfo = !claims.contains_key("email") || !claims.contains_key(&self.config.groups_claim);
+        if let (true, Some(access_token)) = (needs_userinfo, tokens.access_token.as_deref()) {
+            self.merge_userinfo(&mut claims, access_token).await;
+        }
+
+        let idp_user = self.idp_user(&claims)?;
+        let user = self.resolve_user(pool, &idp_user).await?;
+
+        if let Some(groups) = &idp_user.groups {
+            sync_group_memberships(pool, user.id, groups, &self.config.group_mapping_orgs).await?;
+        }
+
+        let step = auth_svc::second_factor_step(pool, user).await?;
+        if let LoginStep::Complete(user) = &step {
+            login_guard::record(pool, &user.email, Some(user.id), ip, true, method::OIDC).await?;
+        }
+        Ok(SsoLogin {
+            step,
+            redirect_to: login.redirect_to,
+        })
+    }
+
+    async fn exchange_code(&self, provider: &Provider, code: &str, code_verifier: &str) -> Result<TokenResponse, OidcError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        let mut request = self.http.post(&provider.metadata.token_endpoint);
+        match &self.config.client_secret {
+            Some(secret) => request = request.basic_auth(&self.config.client_id, Some(secret)),
+            None => form.push(("client_id", self.config.client_id.as_str())),
+        }
+
+        let resp = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| OidcError::Provider(format!("token request: {}", e)))?;
+        let status = resp.status();
+        let body: Value = resp
+            .json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("token response: {}", e)))?;
+        if !status.is_success() {
+            let error = body.get("error").and_then(Value::as_str).unwrap_or("unknown_error");
+            return Err(OidcError::Denied(format!("token endpoint answered {}: {}", status, error)));
+        }
+        serde_json::from_value(body).map_err(|e| OidcError::Provider(format!("token response: {}", e)))
+    }
+
+    /// Check signature, issuer, audience and expiry; returns all claims.
+    async fn validate_id_token(&self, provider: Provider, id_token: &str) -> Result<Map<String, Value>, OidcError> {
+        let header = jsonwebtoken::decode_header(id_token).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        let find_key = |jwks: &JwkSet| match &header.kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+        let jwk = match find_key(&provider.jwks) {
+            Some(jwk) => jwk,
+            None => find_key(&self.refresh_jwks().await?.jwks)
+                .ok_or_else(|| OidcError::InvalidIdToken(format!("no signing key {:?}", header.kid)))?,
+        };
+        let alg = signing_algorithm(&jwk, header.alg)?;
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| OidcError::InvalidIdToken(e.to_string()))?;
+
+        let mut validation = Validation::new(alg);
+        validation.set_issuer(&[provider.metadata.issuer.as_str()]);
+        validation.set_audience(&[self.config.client_id.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        jsonwebtoken::decode::<Map<String, Value>>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| OidcError::InvalidIdToken(e.to_string()))
+    }
+
+    /// Add userinfo claims missing from the ID token. The subject must match.
+    async fn merge_userinfo(&self, claims: &mut Map<String, Value>, access_token: &str) {
+        let Ok(provider) = self.provider().await else { return };
+        let Some(endpoint) = provider.metadata.userinfo_endpoint.as_deref() else { return };
+
+        let info = match self.http.get(endpoint).bearer_auth(access_token).send().await {
+            Ok(resp) if resp.status().is_success() => resp.json::<Map<String, Value>>().await.ok(),
+            Ok(resp) => {
+                tracing::warn!("OIDC userinfo answered {}", resp.status());
+                None
+            }
+            Err(e) => {
+                tracing::warn!("OIDC userinfo request failed: {}", e);
+                None
+            }
+        };
+        let Some(info) = info else { return };
+        if info.get("sub") != claims.get("sub") {
+            tracing::warn!("OIDC userinfo subject does not match the ID token; ignored");
+            return;
+        }
+        for (k, v) in info {
+            claims.entry(k).or_insert(v);
+        }
+    }
+
+    fn idp_user(&self, claims: &Map<String, Value>) -> Result<IdpUser, OidcError> {
+        let subject = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| OidcError::InvalidIdToken("missing sub".into()))?
+            .to_string();
+        let email = claims
+            .get("email")
+            .and_then(Value::as_str)
+            .map(|e| e.trim().to_string())
+            .filter(|e| e.contains('@'));
+        // Some providers send the flag as a string.
+        let email_verified = match claims.get("email_verified") {
+            Some(Value::Bool(b)) => Some(*b),
+            Some(Value::String(s)) => Some(s.eq_ignore_ascii_case("true")),
+            _ => None,
+        };
+        let groups = match claims.get(&self.config.groups_claim) {
+            Some(Value::Array(items)) => Some(items.iter().filter_map(Value::as_str).map(str::to_string).collect()),
+            Some(Value::String(s)) => Some(vec![s.clone()]),
+            _ => None,
+        };
+        Ok(IdpUser {
+            subject,
+            email,
+            email_verified,
+            groups,
+        })
+    }
+
+    /// The account of an IdP user: already linked, linked by email, or created.
+    async fn resolve_user(&self, pool: &DbPool, idp: &IdpUser) -> Result<User, OidcError> {
+        let issuer = self.config.issuer.as_str();
+
+        if let Some(identity) = oidc_repo::find_identity(pool, issuer, &idp.subject).await? {
+            oidc_repo::upsert_identity(pool, identity.user_id, issuer, &idp.subject, idp.email.as_deref()).await?;
+            return users_repo::find_by_id(pool, identity.user_id)
+                .await?
+                .ok_or(OidcError::NotProvisioned);
+        }
+
+        let email = idp.email.as_deref().ok_or(OidcError::MissingEmail)?;
+        // Linking or creating an account by email needs the provider to vouch for it;
+        // a missing flag counts as unverified.
+        if idp.email_verified != Some(true) {
+            return Err(OidcError::EmailUnverified);
+        }
+
+        if let Some(user) = users_repo::find_by_email(pool, email).await? {
+            if !self.config.link_by_email {
+                return Err(OidcError::NotProvisioned);
+            }
+            let mut tx = pool.begin().await?;
+            oidc_repo::upsert_identity(&mut *tx, user.id, issuer, &idp.subject, Some(email)).await?;
+            users_repo::mark_email_verified(&mut *tx, user.id).await?;
+            tx.commit().await?;
+            tracing::info!("linked {} identity {} to user {}", issuer, idp.subject, user.id);
+            return Ok(user);
+        }
+
+        if !self.config.auto_provision {
+            return Err(OidcError::NotProvisioned);
+        }
+
+        // SSO accounts get a random password; one can be set with a password reset.
+        let password_hash = hash_password(&random_token())?;
+        let mut tx = pool.begin().await?;
+        let user_id = users_repo::insert_user(&mut *tx, email, &password_hash).await?;
+        orgs_repo::create_org(&mut *tx, email, user_id).await?;
+        users_repo::mark_email_verified(&mut *tx, user_id).await?;
+        oidc_repo::upsert_identity(&mut *tx, user_id, issuer, &idp.subject, Some(email)).await?;
+        tx.commit().await?;
+        tracing::info!("provisioned user {} for {} identity {}", user_id, issuer, idp.subject);
+
+        users_repo::find_by_id(pool, user_id)
+            .await?
+            .ok_or(OidcError::NotProvisioned)
+    }
+}
+
+/// Grant the roles the user's IdP groups map to, the highest per organization,
+/// and drop SSO memberships no longer backed by a group. Only mappings of
+/// `org_ids`, the organizations allowed to use them, count.
+async fn sync_group_memberships(
+    pool: &DbPool,
+    user_id: Uuid,
+    groups: &[String],
+    org_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let mut roles: HashMap<Uuid, Role> = HashMap::new();
+    for mapping in oidc_repo::mappings_for_groups(pool, groups, org_ids).await? {
+        let role = mapping.role();
+        roles
+            .entry(mapping.org_id)
+            .and_modify(|r| *r = (*r).max(role))
+            .or_insert(role);
+    }
+    let roles: Vec<(Uuid, Role)> = roles.into_iter().collect();
+
+    let mut tx = pool.begin().await?;
+    orgs_repo::sync_sso_memberships(&mut tx, user_id, &roles).await?;
+    tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jwk(value: Value) -> Jwk {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn rsa(alg: Option<&str>) -> Jwk {
+        let mut key = json!({ "kty": "RSA", "kid": "k1", "n": "sXchDaQebHnPiGvyDOAT4saGEUetSyo9MKLOoWFsueri23bOdgWp4Dy1WlUzewbgBHod5pcM9H95GQRV3JDXboIRROSBigeC5yjU1hGzHHyXss8UDprecbAYxknTcQkhslANGRUZmdTOQ5qTRsLAt6BTYuyvVRdhS8exSZEy_c4gs_7svlJJQ4H9_NxsiIoLwAEk7-Q3UXERGYw_75IDrGA84-lA_-Ct4eTlXHBIY2EaV7t7LjJaynVJCpkv4LKjTTAumiGUIuQhrNhZLuF_RJLqHpM2kgWFLU7-VTdL1VbC2tejvcI2BlMkEpk1BzBZI0KQB0GaDWFLN-aEAw3vRw", "e": "AQAB" });
+        if let Some(alg) = alg {
+            key["alg"] = json!(alg);
+        }
+        jwk(key)
+    }
+
+    fn ec(crv: &str) -> Jwk {
+        jwk(json!({
+            "kty": "EC",
+            "crv": crv,
+            "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+            "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"
+        }))
+    }
+
+    #[test]
+    fn key_alg_decides() {
+        assert_eq!(signing_algorithm(&rsa(Some("RS256")), Algorithm::RS256).unwrap(), Algorithm::RS256);
+        assert!(signing_algorithm(&rsa(Some("RS256")), Algorithm::RS384).is_err());
+        assert!(signing_algorithm(&rsa(Some("RS256")), Algorithm::HS256).is_err());
+    }
+
+    #[test]
+    fn header_alg_must_fit_the_key_type() {
+        assert_eq!(signing_algorithm(&rsa(None), Algorithm::PS256).unwrap(), Algorithm::PS256);
+        assert!(signing_algorithm(&rsa(None), Algorithm::HS256).is_err());
+        assert!(signing_algorithm(&rsa(None), Algorithm::ES256).is_err());
+
+        assert_eq!(signing_algorithm(&ec("P-256"), Algorithm::ES256).unwrap(), Algorithm::ES256);
+        assert!(signing_algorithm(&ec("P-256"), Algorithm::ES384).is_err());
+        assert!(signing_algorithm(&ec("P-256"), Algorithm::RS256).is_err());
+
+        let shared = jwk(json!({ "kty": "oct", "k": "c2VjcmV0" }));
+        assert!(signing_algorithm(&shared, Algorithm::HS256).is_err());
+    }
+
+    #[test]
+    fn redirects_stay_on_site() {
+        assert_eq!(safe_redirect(Some("/dashboard?tab=1")), Some("/dashboard?tab=1".to_string()));
+        assert_eq!(safe_redirect(Some("//evil.example")), None);
+        assert_eq!(safe_redirect(Some("/\\evil.example")), None);
+        assert_eq!(safe_redirect(Some("https://evil.example")), None);
+        assert_eq!(safe_redirect(None), None);
+    }
+}
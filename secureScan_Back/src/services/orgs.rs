@@ -0,0 +1,90 @@
+use chrono::{Duration, Utc};
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::orgs_repo::{self, InvitationRow, MembershipRow, Role};
+use crate::domain::users_repo;
+use crate::services::auth::hash_token;
+
+/// How long an invitation token stays valid.
+const INVITATION_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Error)]
+pub enum InvitationError {
+    /// Token unknown, already used or expired
+    #[error("invitation is invalid or has expired")]
+    Invalid,
+
+    /// Accepted by a user whose email differs from the invited one
+    #[error("invitation was sent to a different email address")]
+    WrongRecipient,
+
+    /// The accepting account has not proven it owns its email address
+    #[error("verify your email address before accepting invitations")]
+    EmailUnverified,
+
+    #[error("database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Create an invitation to `org_id`. Returns the stored row and the token to
+/// deliver to the invitee, which is not recoverable afterwards.
+pub async fn invite(
+    pool: &DbPool,
+    org_id: Uuid,
+    email: &str,
+    role: Role,
+    invited_by: Uuid,
+) -> Result<(InvitationRow, String), sqlx::Error> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+
+    let row = orgs_repo::create_invitation(
+        pool,
+        org_id,
+        email,
+        role,
+        &hash_token(&token),
+        invited_by,
+        Utc::now() + Duration::days(INVITATION_TTL_DAYS),
+    )
+    .await?;
+    Ok((row, token))
+}
+
+/// Accept an invitation as `user_id`, whose verified account email must match
+/// the invitation. An existing member keeps the higher of their current and the
+/// invited role.
+pub async fn accept_invitation(pool: &DbPool, token: &str, user_id: Uuid) -> Result<MembershipRow, InvitationError> {
+    let mut tx = pool.begin().await?;
+    let invitation = orgs_repo::lock_pending_invitation(&mut tx, &hash_token(token))
+        .await?
+        .ok_or(InvitationError::Invalid)?;
+
+    let user = users_repo::find_by_id(&mut *tx, user_id)
+        .await?
+        .ok_or(InvitationError::Invalid)?;
+    if !invitation.email.eq_ignore_ascii_case(&user.email) {
+        return Err(InvitationError::WrongRecipient);
+    }
+    if user.email_verified_at.is_none() {
+        return Err(InvitationError::EmailUnverified);
+    }
+
+    let invited = Role::parse(&invitation.role).unwrap_or(Role::Viewer);
+    let current = orgs_repo::get_membership(&mut *tx, invitation.org_id, user_id)
+        .await?
+        .map(|m| m.role());
+    let role = current.map_or(invited, |r| r.max(invited));
+
+    orgs_repo::upsert_member(&mut *tx, invitation.org_id, user_id, role).await?;
+    orgs_repo::mark_invitation_accepted(&mut tx, invitation.id).await?;
+    tx.commit().await?;
+
+    orgs_repo::get_membership(pool, invitation.org_id, user_id)
+        .await?
+        .ok_or(InvitationError::Invalid)
+}
@@ -0,0 +1,291 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value as JsonValue};
+use std::sync::OnceLock;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::outbound_webhooks_repo::{self, AttemptResult, DeliveryRow, OutboundWebhookRow};
+use crate::domain::scans_repo;
+use crate::services::egress;
+use crate::services::mailer::MailConfig;
+use crate::services::scan_findings::OpenFindings;
+use crate::services::webhooks::{self, GENERIC_SIGNATURE_HEADER, GENERIC_TIMESTAMP_HEADER};
+
+/// Events an endpoint can subscribe to.
+pub mod events {
+    /// A scan finished with results.
+    pub const SCAN_COMPLETED: &str = "scan.completed";
+    /// A scan ended without results.
+    pub const SCAN_FAILED: &str = "scan.failed";
+    /// A completed scan has open findings absent from the previous completed scan of its target.
+    pub const FINDING_NEW: &str = "finding.new";
+    /// Sent on demand to test an endpoint; cannot be subscribed to.
+    pub const PING: &str = "ping";
+
+    pub const ALL: [&str; 3] = [SCAN_COMPLETED, SCAN_FAILED, FINDING_NEW];
+}
+
+pub const EVENT_HEADER: &str = "X-SecuraScan-Event";
+pub const DELIVERY_HEADER: &str = "X-SecuraScan-Delivery";
+
+/// Response bodies kept in the delivery log are cut to this many characters.
+const MAX_LOGGED_RESPONSE: usize = 1000;
+
+/// Delivery of outbound webhooks.
+///
+/// Configured through:
+/// - `OUTBOUND_WEBHOOK_INTERVAL_SECS` how often due deliveries are sent (default 5)
+/// - `OUTBOUND_WEBHOOK_TIMEOUT_SECS` request timeout (default 10)
+/// - `OUTBOUND_WEBHOOK_MAX_ATTEMPTS` attempts before a delivery is marked failed (default 8)
+/// - `OUTBOUND_WEBHOOK_RETRY_BASE_SECS` delay before the first retry, doubled on each further one (default 30, capped at 6 hours)
+/// - `OUTBOUND_WEBHOOK_ALLOW_PRIVATE` allow endpoints on loopback and private networks (default false)
+pub struct OutboundWebhookConfig {
+    pub interval: Duration,
+    pub max_attempts: i32,
+    pub retry_base_secs: i64,
+    pub allow_private: bool,
+    timeout: Duration,
+}
+
+fn env_num<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+impl OutboundWebhookConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval: Duration::from_secs(env_num("OUTBOUND_WEBHOOK_INTERVAL_SECS", 5u64).max(1)),
+            max_attempts: env_num("OUTBOUND_WEBHOOK_MAX_ATTEMPTS", 8i32).max(1),
+            retry_base_secs: env_num("OUTBOUND_WEBHOOK_RETRY_BASE_SECS", 30i64).max(1),
+            allow_private: std::env::var("OUTBOUND_WEBHOOK_ALLOW_PRIVATE")
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+                .unwrap_or(false),
+            timeout: Duration::from_secs(env_num("OUTBOUND_WEBHOOK_TIMEOUT_SECS", 10u64).max(1)),
+        }
+    }
+
+    /// Client for one delivery to `url`. Unless private endpoints are allowed,
+    /// the host is resolved once and checked, and the connection goes to the
+    /// checked addresses so a second lookup cannot point it elsewhere.
+    pub async fn client_for(&self, url: &str) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .user_agent("SecuraScan-Webhooks")
+            .redirect(reqwest::redirect::Policy::none());
+        if !self.allow_private {
+            let url = url::Url::parse(url).map_err(|e| e.to_string())?;
+            let (host, addrs) = egress::resolve_public(&url).await.map_err(|e| e.to_string())?;
+            builder = builder.resolve_to_addrs(&host, &addrs);
+        }
+        builder.build().map_err(|e| e.to_string())
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static OutboundWebhookConfig {
+        static CONFIG: OnceLock<OutboundWebhookConfig> = OnceLock::new();
+        CONFIG.get_or_init(OutboundWebhookConfig::from_env)
+    }
+
+    /// When to retry after `attempts` failed attempts, or `None` once they are used up.
+    pub fn retry_at(&self, attempts: i32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let exponent = (attempts - 1).clamp(0, 20) as u32;
+        let delay = self.retry_base_secs.saturating_mul(1i64 << exponent).min(6 * 3600);
+        Some(now + chrono::Duration::seconds(delay))
+    }
+}
+
+/// The start of a receiver's response body for the delivery log. Only as much
+/// as is kept is read; the rest of the response is dropped unread.
+async fn logged_response(mut res: reqwest::Response) -> String {
+    // a character takes at most 4 bytes in UTF-8
+    let max_bytes = MAX_LOGGED_RESPONSE * 4;
+    let mut body = Vec::new();
+    while body.len() < max_bytes {
+        match res.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    body.truncate(max_bytes);
+    String::from_utf8_lossy(&body).chars().take(MAX_LOGGED_RESPONSE).collect()
+}
+
+/// The JSON body of a delivery. Redeliveries keep the event `id`, so receivers can deduplicate.
+fn envelope(event_id: Uuid, event: &str, org_id: Uuid, data: JsonValue) -> JsonValue {
+    json!({
+        "id": event_id,
+        "event": event,
+        "created_at": Utc::now(),
+        "org_id": org_id,
+        "data": data,
+    })
+}
+
+/// Queue a delivery of `event` to every subscribed endpoint of the organization.
+async fn publish(pool: &DbPool, org_id: Uuid, event: &str, data: JsonValue) -> Result<usize, sqlx::Error> {
+    let endpoints = outbound_webhooks_repo::subscribers(pool, org_id, event).await?;
+    if endpoints.is_empty() {
+        return Ok(0);
+    }
+    let event_id = Uuid::new_v4();
+    let payload = envelope(event_id, event, org_id, data);
+    for endpoint in &endpoints {
+        outbound_webhooks_repo::create_delivery(pool, endpoint.id, event_id, event, &payload, None).await?;
+    }
+    Ok(endpoints.len())
+}
+
+/// Queue a `ping` delivery to one endpoint, whatever it subscribes to.
+pub async fn ping(pool: &DbPool, endpoint: &OutboundWebhookRow) -> Result<DeliveryRow, sqlx::Error> {
+    let event_id = Uuid::new_v4();
+    let payload = envelope(
+        event_id,
+        events::PING,
+        endpoint.org_id,
+        json!({ "webhook_id": endpoint.id, "events": endpoint.events }),
+    );
+    outbound_webhooks_repo::create_delivery(pool, endpoint.id, event_id, events::PING, &payload, None).await
+}
+
+/// Queue a new delivery with the payload of `original`.
+pub async fn redeliver(pool: &DbPool, original: &DeliveryRow) -> Result<DeliveryRow, sqlx::Error> {
+    outbound_webhooks_repo::create_delivery(
+        pool,
+        original.webhook_id,
+        original.event_id,
+        &original.event,
+        &original.payload,
+        Some(original.id),
+    )
+    .await
+}
+
+/// Queue the lifecycle events of a scan that just completed or failed.
+/// Failures are logged, never returned.
+pub async fn scan_finished(pool: &DbPool, scan_id: Uuid) {
+    if let Err(e) = publish_scan_events(pool, scan_id).await {
+        tracing::error!("outbound webhooks: could not queue events of scan {}: {}", scan_id, e);
+    }
+}
+
+async fn publish_scan_events(pool: &DbPool, scan_id: Uuid) -> anyhow::Result<()> {
+    let Some(scan) = scans_repo::get_scan(pool, scan_id).await? else {
+        return Ok(());
+    };
+    let Some(org_id) = scan.org_id else {
+        return Ok(());
+    };
+    let scan_json = json!({
+        "id": scan.id,
+        "url": scan.url,
+        "status": scan.status,
+        "created_at": scan.created_at,
+        "target_id": scan.target_id,
+        "schedule_id": scan.schedule_id,
+        "counts": scan.counts(),
+        "risk_score": scan.risk_score,
+        "grade": scan.grade,
+        "link": MailConfig::global().app_url(&format!("/dashboard/{}", scan.id)),
+    });
+
+    if scan.status != "completed" {
+        publish(pool, org_id, events::SCAN_FAILED, json!({ "scan": scan_json })).await?;
+        return Ok(());
+    }
+    publish(pool, org_id, events::SCAN_COMPLETED, json!({ "scan": scan_json })).await?;
+
+    // Only open findings count as new; skip the work when nobody listens.
+    if outbound_webhooks_repo::subscribers(pool, org_id, events::FINDING_NEW).await?.is_empty() {
+        return Ok(());
+    }
+    let new = OpenFindings::load(pool, &scan).await?.new;
+    if new.is_empty() {
+        return Ok(());
+    }
+    publish(pool, org_id, events::FINDING_NEW, json!({ "scan": scan_json, "findings": new })).await?;
+    Ok(())
+}
+
+/// Make one attempt at a claimed delivery and record its outcome.
+pub async fn attempt(pool: &DbPool, delivery: &DeliveryRow) -> Result<(), sqlx::Error> {
+    let cfg = OutboundWebhookConfig::global();
+    let endpoint = outbound_webhooks_repo::find_webhook(pool, delivery.webhook_id).await?;
+    let now = Utc::now();
+
+    let (status_code, error, response) = match &endpoint {
+        // Deliveries to a disabled endpoint fail for good; they can be redelivered once it is enabled again.
+        Some(endpoint) if !endpoint.enabled => (None, Some("endpoint is disabled".to_string()), None),
+        Some(endpoint) => send(cfg, endpoint, delivery).await,
+        None => return Ok(()),
+    };
+    let succeeded = status_code.is_some_and(|c| (200..300).contains(&c));
+    let retry_at = match &endpoint {
+        Some(endpoint) if endpoint.enabled && !succeeded => cfg.retry_at(delivery.attempts + 1, now),
+        _ => None,
+    };
+    if !succeeded {
+        tracing::warn!(
+            "outbound webhooks: delivery {} of {} to endpoint {} failed (attempt {}): {}",
+            delivery.id,
+            delivery.event,
+            delivery.webhook_id,
+            delivery.attempts + 1,
+            error.as_deref().unwrap_or("non-2xx response"),
+        );
+    }
+    outbound_webhooks_repo::record_attempt(
+        pool,
+        delivery.id,
+        &AttemptResult {
+            status_code,
+            error: error.as_deref(),
+            response: response.as_deref(),
+            retry_at,
+            succeeded,
+        },
+    )
+    .await
+}
+
+async fn send(
+    cfg: &OutboundWebhookConfig,
+    endpoint: &OutboundWebhookRow,
+    delivery: &DeliveryRow,
+) -> (Option<i32>, Option<String>, Option<String>) {
+    let body = match serde_json::to_vec(&delivery.payload) {
+        Ok(body) => body,
+        Err(e) => return (None, Some(e.to_string()), None),
+    };
+    let http = match cfg.client_for(&endpoint.url).await {
+        Ok(http) => http,
+        Err(e) => return (None, Some(e), None),
+    };
+    let timestamp = Utc::now().timestamp();
+    let signature = webhooks::sign_generic(endpoint.secret.as_bytes(), timestamp, &body);
+    let res = http
+        .post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(GENERIC_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(GENERIC_SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+    match res {
+        Ok(res) => {
+            let status = res.status();
+            let text = logged_response(res).await;
+            let error = (!status.is_success()).then(|| format!("HTTP {}", status.as_u16()));
+            (Some(status.as_u16() as i32), error, Some(text))
+        }
+        Err(e) => (None, Some(e.to_string()), None),
+    }
+}
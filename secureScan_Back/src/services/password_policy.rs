@@ -0,0 +1,230 @@
+use data_encoding::HEXUPPER;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Length of the SHA-1 prefix a breached-password range is keyed by.
+const PREFIX_LEN: usize = 5;
+
+/// Rules new passwords must satisfy.
+///
+/// Configured through:
+/// - `PASSWORD_MIN_LENGTH` minimum number of characters (default 8)
+/// - `PASSWORD_MAX_LENGTH` maximum number of characters, bounding hashing cost (default 128)
+/// - `PASSWORD_ALLOW_EMAIL` allow passwords containing the email's local part (default false)
+/// - `BREACHED_PASSWORDS_PATH` breached-password list: a file of SHA-1 hashes, or a
+///   directory of k-anonymity range files (unset disables the check)
+/// - `BREACHED_PASSWORDS_MAX_HASHES` hashes of a file list kept in memory, the rest
+///   are ignored (default 1000000, about 32 MB)
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub allow_email: bool,
+    breached: Option<BreachedPasswords>,
+}
+
+fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        let min_length = env_usize("PASSWORD_MIN_LENGTH", 8);
+        let max_length = env_usize("PASSWORD_MAX_LENGTH", 128).max(min_length);
+        let allow_email = std::env::var("PASSWORD_ALLOW_EMAIL")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false);
+
+        let max_hashes = env_usize("BREACHED_PASSWORDS_MAX_HASHES", 1_000_000);
+        let breached = std::env::var("BREACHED_PASSWORDS_PATH")
+            .ok()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .and_then(|p| match BreachedPasswords::open(Path::new(&p), max_hashes) {
+                Ok(list) => Some(list),
+                Err(e) => {
+                    tracing::error!("could not load breached passwords from {}: {}; check disabled", p, e);
+                    None
+                }
+            });
+
+        Self {
+            min_length,
+            max_length,
+            allow_email,
+            breached,
+        }
+    }
+
+    /// Process-wide policy, read from the environment on first use. Loading a
+    /// breached-password file blocks, so first use belongs on a blocking thread.
+    pub fn global() -> &'static PasswordPolicy {
+        static POLICY: OnceLock<PasswordPolicy> = OnceLock::new();
+        POLICY.get_or_init(PasswordPolicy::from_env)
+    }
+
+    /// Every rule `password` breaks for the account `email`; empty when it is acceptable.
+    pub async fn check(&self, email: &str, password: &str) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            violations.push(PolicyViolation::TooShort { min: self.min_length });
+        }
+        if length > self.max_length {
+            // Longer input is neither hashed nor looked up.
+            violations.push(PolicyViolation::TooLong { max: self.max_length });
+            return violations;
+        }
+        if !self.allow_email && contains_email(email, password) {
+            violations.push(PolicyViolation::ContainsEmail);
+        }
+        if let Some(breached) = &self.breached {
+            match breached.occurrences(password).await {
+                Ok(Some(count)) => violations.push(PolicyViolation::Breached { count }),
+                Ok(None) => {}
+                Err(e) => tracing::error!("breached password lookup failed: {}", e),
+            }
+        }
+        violations
+    }
+
+    /// Whether a login password is short enough to be worth hashing.
+    pub fn within_max_length(&self, password: &str) -> bool {
+        password.chars().count() <= self.max_length
+    }
+}
+
+/// A broken password rule, returned to clients as `{ "code": "too_short", "message": …, "min": 8 }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum PolicyViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    ContainsEmail,
+    /// Found in the breached-password list `count` times (0 when the list has no counts).
+    Breached { count: u64 },
+}
+
+impl PolicyViolation {
+    pub fn message(&self) -> String {
+        match self {
+            PolicyViolation::TooShort { min } => format!("must be at least {} characters", min),
+            PolicyViolation::TooLong { max } => format!("must be at most {} characters", max),
+            PolicyViolation::ContainsEmail => "must not contain your email address".into(),
+            PolicyViolation::Breached { .. } => "appears in a list of breached passwords".into(),
+        }
+    }
+}
+
+/// The whole email, or its local part when long enough to matter, appears in the password.
+fn contains_email(email: &str, password: &str) -> bool {
+    let email = email.trim().to_lowercase();
+    let password = password.to_lowercase();
+    let local = email.split('@').next().unwrap_or_default();
+    (!email.is_empty() && password.contains(&email)) || (local.chars().count() >= 3 && password.contains(local))
+}
+
+/// Upper-case hex SHA-1, split into the range prefix and the suffix within it.
+fn sha1_parts(password: &str) -> (String, String) {
+    let digest = HEXUPPER.encode(&Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = digest.split_at(PREFIX_LEN);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Parse `HASH[:COUNT]`, as in the Pwned Passwords downloads.
+fn parse_line(line: &str) -> Option<(String, u64)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (hash, count) = match line.split_once(':') {
+        Some((hash, count)) => (hash, count.trim().parse().unwrap_or(0)),
+        None => (line, 0),
+    };
+    Some((hash.trim().to_ascii_uppercase(), count))
+}
+
+/// Breached-password hashes, looked up by SHA-1 prefix the way the k-anonymity
+/// range API is, so a directory of downloaded range files can be used as-is.
+enum BreachedPasswords {
+    /// One `SHA1[:COUNT]` line per password, kept in memory as sorted digests.
+    Loaded(Vec<([u8; 20], u64)>),
+    /// `<dir>/<PREFIX>` or `<dir>/<PREFIX>.txt` files of `SUFFIX[:COUNT]` lines, read per lookup.
+    Ranges(PathBuf),
+}
+
+impl BreachedPasswords {
+    /// Blocking: reads a file list into memory, at most `max_hashes` of it.
+    fn open(path: &Path, max_hashes: usize) -> std::io::Result<Self> {
+        if path.is_dir() {
+            tracing::info!("breached passwords: range files in {}", path.display());
+            return Ok(BreachedPasswords::Ranges(path.to_path_buf()));
+        }
+
+        let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut hashes = Vec::new();
+        for line in reader.lines() {
+            let Some((hash, count)) = parse_line(&line?) else {
+                continue;
+            };
+            let Some(digest) = HEXUPPER.decode(hash.as_bytes()).ok().and_then(|d| <[u8; 20]>::try_from(d).ok()) else {
+                continue;
+            };
+            if hashes.len() == max_hashes {
+                tracing::warn!(
+                    "breached passwords: {} has more than {} hashes, the rest are ignored; \
+                     raise BREACHED_PASSWORDS_MAX_HASHES or use a directory of range files",
+                    path.display(),
+                    max_hashes
+                );
+                break;
+            }
+            hashes.push((digest, count));
+        }
+        hashes.sort_unstable_by_key(|(digest, _)| *digest);
+        hashes.dedup_by_key(|(digest, _)| *digest);
+        hashes.shrink_to_fit();
+        tracing::info!("breached passwords: loaded {} hashes from {}", hashes.len(), path.display());
+        Ok(BreachedPasswords::Loaded(hashes))
+    }
+
+    /// How often `password` was seen in breaches, or `None` if it is not listed.
+    async fn occurrences(&self, password: &str) -> std::io::Result<Option<u64>> {
+        match self {
+            BreachedPasswords::Loaded(hashes) => {
+                let digest: [u8; 20] = Sha1::digest(password.as_bytes()).into();
+                Ok(hashes
+                    .binary_search_by_key(&digest, |(d, _)| *d)
+                    .ok()
+                    .map(|i| hashes[i].1))
+            }
+            BreachedPasswords::Ranges(dir) => {
+                let (prefix, suffix) = sha1_parts(password);
+                let mut content = None;
+                for path in [dir.join(&prefix), dir.join(format!("{}.txt", prefix))] {
+                    match tokio::fs::read_to_string(path).await {
+                        Ok(text) => {
+                            content = Some(text);
+                            break;
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(content.and_then(|text| {
+                    text.lines()
+                        .filter_map(parse_line)
+                        .find(|(s, _)| *s == suffix)
+                        .map(|(_, count)| count)
+                }))
+            }
+        }
+    }
+}
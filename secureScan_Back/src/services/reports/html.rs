@@ -0,0 +1,409 @@
+//! Standalone HTML report: one document with inline CSS and SVG, no external
+//! assets, so it can be mailed or archived as is.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+use super::{category, remediation, rule_title, ScanReport};
+use crate::domain::findings::{AnnotatedFinding, Severity, SeverityCounts};
+use crate::services::mailer::MailConfig;
+
+pub const CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// Policy sent with the report and repeated in it: inline styles only, no scripts or fetches.
+pub const CONTENT_SECURITY_POLICY: &str = "default-src 'none'; style-src 'unsafe-inline'; img-src data:";
+
+/// Response headers highlighted in the headers table.
+const SECURITY_HEADERS: [&str; 9] = [
+    "content-security-policy",
+    "strict-transport-security",
+    "x-frame-options",
+    "x-content-type-options",
+    "referrer-policy",
+    "x-xss-protection",
+    "permissions-policy",
+    "cross-origin-opener-policy",
+    "cross-origin-resource-policy",
+];
+
+const STYLE: &str = r#"
+:root { --critical:#b91c1c; --high:#ea580c; --medium:#d97706; --low:#2563eb; --info:#6b7280; --border:#e5e7eb; --muted:#6b7280; }
+* { box-sizing: border-box; }
+body { margin: 0; font: 15px/1.5 -apple-system, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; color: #111827; background: #f3f4f6; }
+main { max-width: 960px; margin: 0 auto; padding: 32px 24px 48px; background: #fff; }
+header { border-bottom: 1px solid var(--border); padding-bottom: 16px; margin-bottom: 24px; }
+h1 { font-size: 26px; margin: 0 0 4px; }
+h2 { font-size: 20px; margin: 32px 0 12px; border-bottom: 1px solid var(--border); padding-bottom: 6px; }
+h3 { font-size: 17px; margin: 24px 0 8px; }
+.muted { color: var(--muted); }
+.meta { display: flex; flex-wrap: wrap; gap: 4px 24px; font-size: 14px; }
+.cards { display: grid; grid-template-columns: repeat(auto-fit, minmax(150px, 1fr)); gap: 12px; margin: 16px 0; }
+.card { border: 1px solid var(--border); border-radius: 8px; padding: 12px 14px; }
+.card .value { font-size: 26px; font-weight: 700; }
+.card .label { font-size: 13px; color: var(--muted); }
+.grade { display: inline-block; min-width: 44px; text-align: center; border-radius: 8px; color: #fff; font-weight: 700; background: #374151; padding: 0 8px; }
+.grade-A { background: #15803d; } .grade-B { background: #65a30d; } .grade-C { background: #d97706; } .grade-D { background: #ea580c; } .grade-F { background: #b91c1c; }
+.charts { display: grid; grid-template-columns: repeat(auto-fit, minmax(300px, 1fr)); gap: 24px; }
+.pill { display: inline-block; border-radius: 999px; padding: 0 10px; font-size: 12px; font-weight: 600; color: #fff; text-transform: uppercase; letter-spacing: .03em; }
+.sev-critical { background: var(--critical); } .sev-high { background: var(--high); } .sev-medium { background: var(--medium); } .sev-low { background: var(--low); } .sev-info { background: var(--info); }
+.finding { border: 1px solid var(--border); border-left-width: 4px; border-radius: 6px; padding: 10px 14px; margin: 10px 0; }
+.finding.critical { border-left-color: var(--critical); } .finding.high { border-left-color: var(--high); } .finding.medium { border-left-color: var(--medium); } .finding.low { border-left-color: var(--low); } .finding.info { border-left-color: var(--info); }
+.finding.suppressed { opacity: .65; }
+.finding h4 { margin: 0 0 4px; font-size: 15px; }
+.finding dl { display: grid; grid-template-columns: 110px 1fr; gap: 2px 12px; margin: 8px 0 0; font-size: 14px; }
+.finding dt { color: var(--muted); }
+.finding dd { margin: 0; overflow-wrap: anywhere; }
+code, pre { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 13px; }
+code { background: #f3f4f6; border-radius: 4px; padding: 0 4px; }
+pre { background: #f9fafb; border: 1px solid var(--border); border-radius: 4px; padding: 8px; margin: 0; white-space: pre-wrap; overflow-wrap: anywhere; }
+table { width: 100%; border-collapse: collapse; font-size: 14px; }
+th, td { text-align: left; padding: 6px 8px; border-bottom: 1px solid var(--border); vertical-align: top; }
+td.value { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 13px; overflow-wrap: anywhere; }
+tr.security td:first-child { font-weight: 600; }
+footer { margin-top: 40px; padding-top: 12px; border-top: 1px solid var(--border); font-size: 13px; color: var(--muted); }
+@media print { body { background: #fff; } main { padding: 0; } .finding { break-inside: avoid; } }
+"#;
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape `s`, turning `backticked` spans into `<code>`.
+fn inline_code(s: &str) -> String {
+    s.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", escape(part))
+            } else {
+                escape(part)
+            }
+        })
+        .collect()
+}
+
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "#b91c1c",
+        Severity::High => "#ea580c",
+        Severity::Medium => "#d97706",
+        Severity::Low => "#2563eb",
+        Severity::Info => "#6b7280",
+    }
+}
+
+fn count_of(counts: &SeverityCounts, severity: Severity) -> i32 {
+    match severity {
+        Severity::Critical => counts.critical,
+        Severity::High => counts.high,
+        Severity::Medium => counts.medium,
+        Severity::Low => counts.low,
+        Severity::Info => counts.info,
+    }
+}
+
+/// Horizontal bar chart as inline SVG; `rows` are (label, value, color).
+fn bar_chart(title: &str, rows: &[(String, i32, &str)]) -> String {
+    const WIDTH: i32 = 420;
+    const LABEL: i32 = 150;
+    const ROW: i32 = 28;
+    let max = rows.iter().map(|r| r.1).max().unwrap_or(0).max(1);
+    let bar_space = WIDTH - LABEL - 40;
+    let height = ROW * rows.len().max(1) as i32 + 4;
+
+    let mut svg = format!(
+        r#"<figure><figcaption><strong>{}</strong></figcaption><svg xmlns="http://www.w3.org/2000/svg" width="100%" viewBox="0 0 {} {}" role="img" aria-label="{}">"#,
+        escape(title),
+        WIDTH,
+        height,
+        escape(title)
+    );
+    for (i, (label, value, color)) in rows.iter().enumerate() {
+        let y = i as i32 * ROW + 4;
+        let width = if *value > 0 { (*value * bar_space / max).max(2) } else { 0 };
+        let _ = write!(
+            svg,
+            r##"<text x="0" y="{ty}" font-size="13" fill="#374151">{label}</text><rect x="{x}" y="{y}" width="{width}" height="18" rx="3" fill="{color}"/><text x="{vx}" y="{ty}" font-size="13" fill="#111827">{value}</text>"##,
+            ty = y + 14,
+            label = escape(label),
+            x = LABEL,
+            vx = LABEL + width + 6,
+        );
+    }
+    svg.push_str("</svg></figure>");
+    svg
+}
+
+fn executive_summary(report: &ScanReport) -> String {
+    let scan = &report.scan;
+    let c = &report.findings.counts;
+    let worst = Severity::ALL.into_iter().find(|s| count_of(c, *s) > 0);
+
+    let mut text = format!(
+        "The scan of <strong>{}</strong> on {} found <strong>{}</strong> open finding(s)",
+        escape(&scan.url),
+        scan.created_at.format("%Y-%m-%d %H:%M UTC"),
+        c.total
+    );
+    let parts: Vec<String> = Severity::ALL
+        .into_iter()
+        .filter(|s| count_of(c, *s) > 0)
+        .map(|s| format!("{} {}", count_of(c, s), s.as_str()))
+        .collect();
+    if !parts.is_empty() {
+        let _ = write!(text, ": {}", parts.join(", "));
+    }
+    text.push('.');
+    if report.findings.suppressed_count > 0 {
+        let _ = write!(
+            text,
+            " {} further finding(s) are suppressed as accepted risk, false positive or won't fix.",
+            report.findings.suppressed_count
+        );
+    }
+    if let (Some(grade), Some(score)) = (&scan.grade, scan.risk_score) {
+        let _ = write!(text, " The target is graded <strong>{}</strong> with a risk score of {} out of 100.", escape(grade), score);
+    }
+    let verdict = match worst {
+        Some(Severity::Critical) | Some(Severity::High) => {
+            "Findings of high or critical severity expose the site to likely attacks and should be fixed first."
+        }
+        Some(Severity::Medium) => "No critical or high findings were found; the medium findings weaken defence in depth and should be planned in.",
+        Some(_) => "Only low and informational findings remain; they harden the site further but carry little risk on their own.",
+        None => "No open findings remain.",
+    };
+    let _ = write!(text, " {}", verdict);
+    format!("<p>{}</p>", text)
+}
+
+fn finding_html(annotated: &AnnotatedFinding) -> String {
+    let f = &annotated.finding;
+    let sev = f.severity.as_str();
+    let mut html = format!(
+        r#"<article class="finding {sev}{suppressed}"><h4><span class="pill sev-{sev}">{sev}</span> {title}</h4><dl><dt>Rule</dt><dd><code>{rule}</code></dd><dt>Location</dt><dd>{location}</dd>"#,
+        sev = sev,
+        suppressed = if annotated.suppression.is_some() { " suppressed" } else { "" },
+        title = escape(&f.title),
+        rule = escape(&f.rule_id),
+        location = escape(&f.location),
+    );
+    if let Some(evidence) = &f.evidence {
+        let _ = write!(html, "<dt>Evidence</dt><dd><pre>{}</pre></dd>", escape(evidence));
+    }
+    let _ = write!(html, "<dt>Remediation</dt><dd>{}</dd>", inline_code(&remediation(&f.rule_id)));
+    if let Some(s) = &annotated.suppression {
+        let until = s
+            .expires_at
+            .map(|e| format!(" until {}", e.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        let _ = write!(
+            html,
+            "<dt>Suppressed</dt><dd>{} by {}{}: {}</dd>",
+            escape(&s.state.replace('_', " ")),
+            escape(&s.author),
+            until,
+            escape(&s.reason)
+        );
+    }
+    html.push_str("</dl></article>");
+    html
+}
+
+/// Findings grouped by category, most severe category first; suppressed findings last in each.
+fn findings_section(report: &ScanReport) -> String {
+    let mut groups: Vec<(&'static str, Vec<&AnnotatedFinding>)> = Vec::new();
+    for annotated in &report.findings.findings {
+        let name = category(&annotated.finding.rule_id);
+        match groups.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, items)) => items.push(annotated),
+            None => groups.push((name, vec![annotated])),
+        }
+    }
+    let rank = |f: &AnnotatedFinding| (f.suppression.is_none(), f.finding.severity);
+    for (_, items) in groups.iter_mut() {
+        items.sort_by_key(|f| std::cmp::Reverse(rank(f)));
+    }
+    groups.sort_by_key(|(name, items)| (std::cmp::Reverse(items.iter().map(|f| rank(f)).max()), *name));
+
+    if groups.is_empty() {
+        return r#"<p class="muted">The scan reported no findings.</p>"#.to_string();
+    }
+    let mut html = String::new();
+    for (name, items) in groups {
+        let open = items.iter().filter(|f| f.suppression.is_none()).count();
+        let _ = write!(
+            html,
+            r#"<section><h3>{} <span class="muted">({} open, {} total)</span></h3>"#,
+            escape(name),
+            open,
+            items.len()
+        );
+        for annotated in items {
+            html.push_str(&finding_html(annotated));
+        }
+        html.push_str("</section>");
+    }
+    html
+}
+
+fn headers_section(report: &ScanReport) -> String {
+    let Some(headers) = report.result.headers.as_ref().and_then(|h| h.as_object()) else {
+        return r#"<p class="muted">No response headers were captured.</p>"#.to_string();
+    };
+    let mut rows: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (name.to_ascii_lowercase(), value)
+        })
+        .collect();
+    rows.sort();
+
+    let missing: Vec<&str> = SECURITY_HEADERS
+        .iter()
+        .copied()
+        .filter(|h| !rows.iter().any(|(name, _)| name == h))
+        .collect();
+
+    let mut html = String::from(r#"<table><thead><tr><th>Header</th><th>Value</th></tr></thead><tbody>"#);
+    for (name, value) in &rows {
+        let _ = write!(
+            html,
+            r#"<tr{}><td>{}</td><td class="value">{}</td></tr>"#,
+            if SECURITY_HEADERS.contains(&name.as_str()) { r#" class="security""# } else { "" },
+            escape(name),
+            escape(value)
+        );
+    }
+    html.push_str("</tbody></table>");
+    if !missing.is_empty() {
+        let list: Vec<String> = missing.iter().map(|h| format!("<code>{}</code>", escape(h))).collect();
+        let _ = write!(html, r#"<p class="muted">Security headers not sent: {}.</p>"#, list.join(", "));
+    }
+    html
+}
+
+/// The whole report document.
+pub fn render(report: &ScanReport, generated_at: DateTime<Utc>) -> String {
+    let scan = &report.scan;
+    let c = &report.findings.counts;
+    let link = MailConfig::global().app_url(&format!("/dashboard/{}", scan.id));
+
+    let severity_rows: Vec<(String, i32, &str)> = Severity::ALL
+        .into_iter()
+        .map(|s| (s.as_str().to_string(), count_of(c, s), severity_color(s)))
+        .collect();
+    let mut category_rows: Vec<(String, i32, &str)> = Vec::new();
+    for annotated in report.findings.findings.iter().filter(|f| f.suppression.is_none()) {
+        let name = category(&annotated.finding.rule_id);
+        match category_rows.iter_mut().find(|(n, _, _)| n == name) {
+            Some(row) => row.1 += 1,
+            None => category_rows.push((name.to_string(), 1, "#4b5563")),
+        }
+    }
+    category_rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let grade = scan.grade.as_deref().unwrap_or("–");
+    let mut open: Vec<&AnnotatedFinding> = report.findings.findings.iter().filter(|f| f.suppression.is_none()).collect();
+    open.sort_by_key(|f| std::cmp::Reverse(f.finding.severity));
+    let mut rule_titles: Vec<String> = Vec::new();
+    for annotated in open {
+        let title = rule_title(&annotated.finding.rule_id, &annotated.finding.title);
+        if !rule_titles.contains(&title) {
+            rule_titles.push(title);
+        }
+    }
+
+    let mut html = String::with_capacity(16 * 1024);
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta http-equiv="Content-Security-Policy" content="{csp}">
+<title>Security scan report – {title_url}</title>
+<style>{style}</style>
+</head>
+<body>
+<main>
+<header>
+<h1>Security scan report</h1>
+<div class="meta"><span><strong>Target:</strong> {url}</span><span><strong>Scanned:</strong> {scanned}</span><span><strong>Completed:</strong> {completed}</span><span class="muted">Scan {id}</span></div>
+</header>
+<h2>Executive summary</h2>
+<div class="cards">
+<div class="card"><div class="value"><span class="grade grade-{grade_class}">{grade}</span></div><div class="label">Grade</div></div>
+<div class="card"><div class="value">{score}</div><div class="label">Risk score (0–100)</div></div>
+<div class="card"><div class="value">{open}</div><div class="label">Open findings</div></div>
+<div class="card"><div class="value" style="color:var(--critical)">{critical_high}</div><div class="label">Critical and high</div></div>
+<div class="card"><div class="value">{suppressed}</div><div class="label">Suppressed</div></div>
+</div>
+{summary}
+"#,
+        csp = CONTENT_SECURITY_POLICY,
+        title_url = escape(&scan.url),
+        style = STYLE,
+        url = escape(&scan.url),
+        scanned = scan.created_at.format("%Y-%m-%d %H:%M UTC"),
+        completed = report
+            .result
+            .completed_at
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "–".to_string()),
+        id = scan.id,
+        grade_class = escape(&grade.chars().next().map(String::from).unwrap_or_default()),
+        grade = escape(grade),
+        score = scan.risk_score.map(|s| s.to_string()).unwrap_or_else(|| "–".to_string()),
+        open = c.total,
+        critical_high = c.critical + c.high,
+        suppressed = report.findings.suppressed_count,
+        summary = executive_summary(report),
+    );
+    if !rule_titles.is_empty() {
+        let items: Vec<String> = rule_titles.iter().take(5).map(|t| format!("<li>{}</li>", escape(t))).collect();
+        let _ = write!(html, "<p><strong>Main issues:</strong></p><ul>{}</ul>", items.join(""));
+    }
+
+    let _ = write!(
+        html,
+        r#"<div class="charts">{}{}</div>
+<h2>Findings</h2>
+{}
+<h2>HTTP response headers</h2>
+{}
+<footer>Generated by SecuraScan {} on {}. Live results: <a href="{}">{}</a></footer>
+</main>
+</body>
+</html>
+"#,
+        bar_chart("Open findings by severity", &severity_rows),
+        if category_rows.is_empty() {
+            String::new()
+        } else {
+            bar_chart("Open findings by category", &category_rows)
+        },
+        findings_section(report),
+        headers_section(report),
+        env!("CARGO_PKG_VERSION"),
+        generated_at.format("%Y-%m-%d %H:%M UTC"),
+        escape(&link),
+        escape(&link),
+    );
+    html
+}
@@ -0,0 +1,229 @@
+//! JUnit XML, which most CI systems render natively. Every check the scanner
+//! runs is a test case that fails when the scan has open findings of it at or
+//! above a severity threshold.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fmt::Write;
+use url::Url;
+
+use super::{category, remediation, rule_title, ScanReport};
+use crate::db::DbPool;
+use crate::domain::findings::{rules, AnnotatedFinding, Finding, Severity};
+use crate::services::mailer::MailConfig;
+use crate::services::scan_findings::OpenFindings;
+
+pub const CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+/// Which findings fail their check.
+pub struct Gate {
+    pub threshold: Severity,
+    /// Fingerprints of findings absent from the target's previous completed
+    /// scan; `None` gates on every finding.
+    pub new: Option<HashSet<String>>,
+}
+
+impl Gate {
+    /// With `new_only`, only regressions since the previous completed scan of the
+    /// same target fail; on a target's first scan every finding is new.
+    pub async fn load(
+        pool: &DbPool,
+        report: &ScanReport,
+        threshold: Severity,
+        new_only: bool,
+    ) -> Result<Self, sqlx::Error> {
+        if !new_only {
+            return Ok(Self { threshold, new: None });
+        }
+        let findings = OpenFindings::from_annotated(pool, &report.scan, &report.findings).await?;
+        Ok(Self {
+            threshold,
+            new: Some(findings.new_fingerprints()),
+        })
+    }
+
+    fn fails(&self, finding: &Finding) -> bool {
+        finding.severity >= self.threshold
+            && self.new.as_ref().is_none_or(|new| new.contains(&finding.fingerprint()))
+    }
+}
+
+/// Escape text for XML content and attribute values, dropping the control
+/// characters XML 1.0 cannot represent.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// JUnit timestamps are ISO 8601 without a time zone; ours are UTC.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn describe(finding: &Finding) -> String {
+    let mut text = format!("[{}] {}\n  location: {}\n", finding.severity.as_str(), finding.title, finding.location);
+    if let Some(evidence) = &finding.evidence {
+        let _ = writeln!(text, "  evidence: {}", evidence);
+    }
+    let _ = writeln!(text, "  fingerprint: {}", finding.fingerprint());
+    text
+}
+
+fn test_case(out: &mut String, rule_id: &str, findings: &[&AnnotatedFinding], gate: &Gate) -> (bool, bool) {
+    let (suppressed, open): (Vec<&AnnotatedFinding>, Vec<&AnnotatedFinding>) =
+        findings.iter().copied().partition(|a| a.suppression.is_some());
+    let (failing, passing): (Vec<&Finding>, Vec<&Finding>) =
+        open.iter().map(|a| &a.finding).partition(|f| gate.fails(f));
+    let skipped = open.is_empty() && !suppressed.is_empty();
+
+    let mut body = String::new();
+    if let Some(worst) = failing.iter().map(|f| f.severity).max() {
+        let message = format!(
+            "{} {}finding(s) at or above {}",
+            failing.len(),
+            if gate.new.is_some() { "new " } else { "" },
+            gate.threshold.as_str(),
+        );
+        let mut details: String = failing.iter().map(|f| describe(f)).collect::<Vec<_>>().join("\n");
+        let _ = write!(details, "\nRemediation: {}\n", remediation(rule_id));
+        let _ = writeln!(
+            body,
+            "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+            escape(&message),
+            worst.as_str(),
+            escape(&details),
+        );
+    } else if skipped {
+        let reasons: Vec<String> = suppressed
+            .iter()
+            .filter_map(|a| a.suppression.as_ref())
+            .map(|s| format!("{}: {}", s.state, s.reason))
+            .collect();
+        let _ = writeln!(body, "      <skipped message=\"suppressed ({})\"/>", escape(&reasons.join("; ")));
+    }
+
+    // Findings that did not fail the check are still shown with the test case.
+    let mut notes = String::new();
+    for f in &passing {
+        let _ = write!(notes, "not gated: {}", describe(f));
+    }
+    for a in suppressed.iter().filter(|_| !skipped) {
+        if let Some(s) = &a.suppression {
+            let _ = write!(notes, "suppressed ({}: {}): {}", s.state, s.reason, describe(&a.finding));
+        }
+    }
+    if !notes.is_empty() {
+        let _ = writeln!(body, "      <system-out>{}</system-out>", escape(&notes));
+    }
+
+    let _ = write!(
+        out,
+        "    <testcase classname=\"{}\" name=\"{}\" time=\"0\"",
+        escape(category(rule_id)),
+        escape(&rule_title(rule_id, rule_id)),
+    );
+    if body.is_empty() {
+        out.push_str("/>\n");
+    } else {
+        let _ = write!(out, ">\n{}    </testcase>\n", body);
+    }
+    (!failing.is_empty(), skipped)
+}
+
+/// The JUnit report of a scan: one test suite with a test case per scanner check
+/// (plus any other rule the scan reported). Checks without findings pass; a check
+/// whose findings are all suppressed is skipped.
+pub fn render(report: &ScanReport, gate: &Gate) -> String {
+    let scan = &report.scan;
+    let mut rule_ids = rules::checks();
+    for a in &report.findings.findings {
+        if !rule_ids.contains(&a.finding.rule_id) {
+            rule_ids.push(a.finding.rule_id.clone());
+        }
+    }
+
+    let mut cases = String::new();
+    let (mut failures, mut skipped) = (0, 0);
+    for rule_id in &rule_ids {
+        let findings: Vec<&AnnotatedFinding> =
+            report.findings.findings.iter().filter(|a| &a.finding.rule_id == rule_id).collect();
+        let (failed, was_skipped) = test_case(&mut cases, rule_id, &findings, gate);
+        failures += failed as usize;
+        skipped += was_skipped as usize;
+    }
+
+    let time = report
+        .result
+        .completed_at
+        .map(|done| (done - scan.created_at).num_milliseconds().max(0) as f64 / 1000.0)
+        .unwrap_or(0.0);
+    let counts = format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"",
+        rule_ids.len(),
+        failures,
+        skipped,
+        time
+    );
+    let host = Url::parse(&scan.url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let properties = [
+        ("scan_id", scan.id.to_string()),
+        ("target", scan.url.clone()),
+        ("grade", scan.grade.clone().unwrap_or_default()),
+        ("risk_score", scan.risk_score.map(|s| s.to_string()).unwrap_or_default()),
+        ("fail_on", gate.threshold.as_str().to_string()),
+        ("new_only", gate.new.is_some().to_string()),
+        ("report_url", MailConfig::global().app_url(&format!("/dashboard/{}", scan.id))),
+    ];
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(out, "<testsuites name=\"SecuraScan\" {}>", counts);
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"{}\" {} timestamp=\"{}\" hostname=\"{}\">",
+        escape(&format!("SecuraScan: {}", scan.url)),
+        counts,
+        timestamp(scan.created_at),
+        escape(&host),
+    );
+    out.push_str("    <properties>\n");
+    for (name, value) in properties {
+        let _ = writeln!(out, "      <property name=\"{}\" value=\"{}\"/>", name, escape(&value));
+    }
+    out.push_str("    </properties>\n");
+    out.push_str(&cases);
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_markup() {
+        assert_eq!(escape(r#"<a href="x">'&'</a>"#), "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;");
+        assert_eq!(escape("plain text"), "plain text");
+    }
+
+    #[test]
+    fn escape_drops_characters_xml_cannot_hold() {
+        assert_eq!(escape("a\u{0}b\u{1b}c\u{FFFE}d\u{FFFF}"), "abcd");
+        assert_eq!(escape("tab\tnewline\ncr\r"), "tab\tnewline\ncr\r");
+        assert_eq!(escape("ünïcødé ✓"), "ünïcødé ✓");
+    }
+}
@@ -0,0 +1,117 @@
+//! Exports of a scan's results in formats other tools read.
+
+pub mod html;
+pub mod junit;
+pub mod sarif;
+
+use chrono::Utc;
+
+use crate::db::DbPool;
+use crate::domain::findings::{self, rules, AnnotatedFindings};
+use crate::domain::scans_repo::{self, ScanResultRow, ScanRow};
+use crate::domain::suppressions_repo;
+
+/// Everything an export needs about one scan.
+pub struct ScanReport {
+    pub scan: ScanRow,
+    pub result: ScanResultRow,
+    /// Findings with the organization's suppressions applied, in scanner order.
+    pub findings: AnnotatedFindings,
+}
+
+impl ScanReport {
+    /// `None` while the scan has no result (queued, running or failed).
+    pub async fn load(pool: &DbPool, scan: ScanRow) -> Result<Option<Self>, sqlx::Error> {
+        let Some(result) = scans_repo::get_latest_scan_result(pool, scan.id).await? else {
+            return Ok(None);
+        };
+        let suppressions = suppressions_repo::list_active(pool, scan.org_id).await?;
+        let scan_findings = scans_repo::load_findings(pool, scan.id).await?;
+        let findings = findings::annotate(scan_findings, &suppressions, scan.target_id, Utc::now());
+        Ok(Some(Self { scan, result, findings }))
+    }
+
+    /// File name for a download of this report, e.g. `securascan-<id>.sarif`.
+    pub fn file_name(&self, extension: &str) -> String {
+        format!("securascan-{}.{}", self.scan.id, extension)
+    }
+}
+
+/// Short name of a rule; `fallback` (e.g. a finding's title) for rules without one.
+pub fn rule_title(rule_id: &str, fallback: &str) -> String {
+    if let Some(header) = rule_id.strip_prefix(rules::MISSING_HEADER_PREFIX) {
+        return format!("Missing header: {}", header);
+    }
+    match rule_id {
+        rules::NO_HTTPS => "Target not served over HTTPS",
+        rules::HTML_FORMS => "HTML forms present",
+        rules::TECHNOLOGY_DISCLOSURE => "Technology disclosure",
+        rules::HTTP_ERROR_STATUS => "HTTP error status",
+        _ => fallback,
+    }
+    .to_string()
+}
+
+/// What a rule checks for, in one sentence.
+pub fn describe_rule(rule_id: &str) -> String {
+    if let Some(header) = rule_id.strip_prefix(rules::MISSING_HEADER_PREFIX) {
+        return format!("The response does not set the {} security header.", header);
+    }
+    match rule_id {
+        rules::NO_HTTPS => "The target is served over plain HTTP instead of HTTPS.",
+        rules::HTML_FORMS => "The page contains HTML forms, which widen the attack surface.",
+        rules::TECHNOLOGY_DISCLOSURE => "Response headers disclose the server software or framework in use.",
+        rules::HTTP_ERROR_STATUS => "The target answered with an HTTP error status.",
+        rules::UNCLASSIFIED => "A scanner finding that does not map to a known rule.",
+        _ => "A finding reported by the SecuraScan web scanner.",
+    }
+    .to_string()
+}
+
+/// Group a rule belongs to in reports.
+pub fn category(rule_id: &str) -> &'static str {
+    if rule_id.starts_with(rules::MISSING_HEADER_PREFIX) {
+        return "Security headers";
+    }
+    match rule_id {
+        rules::NO_HTTPS => "Transport security",
+        rules::TECHNOLOGY_DISCLOSURE => "Information disclosure",
+        rules::HTML_FORMS => "Attack surface",
+        rules::HTTP_ERROR_STATUS => "Availability",
+        _ => "Other",
+    }
+}
+
+/// How to fix findings of a rule. Header values and settings are quoted in backticks.
+pub fn remediation(rule_id: &str) -> String {
+    if let Some(header) = rule_id.strip_prefix(rules::MISSING_HEADER_PREFIX) {
+        return match header {
+            "content-security-policy" => "Send a `Content-Security-Policy` header that limits where scripts, styles and frames \
+                may load from, e.g. `default-src 'self'`. Roll it out with `Content-Security-Policy-Report-Only` first."
+                .to_string(),
+            "strict-transport-security" => {
+                "Send `Strict-Transport-Security: max-age=31536000; includeSubDomains` on HTTPS responses.".to_string()
+            }
+            "x-frame-options" => "Send `X-Frame-Options: DENY` (or `SAMEORIGIN`), or the CSP directive \
+                `frame-ancestors 'none'`, so other sites cannot frame the page."
+                .to_string(),
+            "x-content-type-options" => "Send `X-Content-Type-Options: nosniff`.".to_string(),
+            "referrer-policy" => "Send `Referrer-Policy: strict-origin-when-cross-origin` or stricter.".to_string(),
+            "x-xss-protection" => "Current browsers ignore this header's filter; send `X-XSS-Protection: 0` and rely on \
+                a `Content-Security-Policy`."
+                .to_string(),
+            other => format!("Send the `{}` header on every response.", other),
+        };
+    }
+    match rule_id {
+        rules::NO_HTTPS => "Serve the site over HTTPS only and redirect plain HTTP requests to HTTPS.",
+        rules::HTML_FORMS => "Make sure every form submits over HTTPS, carries an anti-CSRF token and is validated on \
+            the server.",
+        rules::TECHNOLOGY_DISCLOSURE => "Remove generator meta tags and version details from `Server` and \
+            `X-Powered-By` headers.",
+        rules::HTTP_ERROR_STATUS => "Check that the URL is right and the application healthy; error pages should not \
+            reveal stack traces or internals.",
+        _ => "Review the finding and fix it in the application or its server configuration.",
+    }
+    .to_string()
+}
@@ -0,0 +1,166 @@
+//! SARIF 2.1.0 (<https://docs.oasis-open.org/sarif/sarif/v2.1.0/>), as read by
+//! GitHub code scanning and other static analysis dashboards.
+
+use serde_json::{json, Value as JsonValue};
+use url::Url;
+
+use super::{describe_rule, rule_title, ScanReport};
+use crate::domain::findings::{AnnotatedFinding, Finding, Severity};
+use crate::services::mailer::MailConfig;
+
+pub const CONTENT_TYPE: &str = "application/sarif+json";
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF result level of a severity.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// GitHub's `security-severity` score (0.0–10.0), which it buckets back into
+/// critical (9.0+), high (7.0+), medium (4.0+) and low. Informational findings get none.
+fn security_severity(severity: Severity) -> Option<&'static str> {
+    match severity {
+        Severity::Critical => Some("9.5"),
+        Severity::High => Some("8.0"),
+        Severity::Medium => Some("5.5"),
+        Severity::Low => Some("3.0"),
+        Severity::Info => None,
+    }
+}
+
+/// `missing-header-x-frame-options` → `MissingHeaderXFrameOptions`.
+fn rule_name(rule_id: &str) -> String {
+    rule_id
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn rule(finding: &Finding) -> JsonValue {
+    let mut properties = json!({ "tags": ["security", "dast"] });
+    if let Some(score) = security_severity(finding.severity) {
+        properties["security-severity"] = json!(score);
+    }
+    json!({
+        "id": finding.rule_id,
+        "name": rule_name(&finding.rule_id),
+        "shortDescription": { "text": rule_title(&finding.rule_id, &finding.title) },
+        "fullDescription": { "text": describe_rule(&finding.rule_id) },
+        "defaultConfiguration": { "level": level(finding.severity) },
+        "properties": properties,
+    })
+}
+
+/// Where a finding was observed: its own URL when it has one, else the scanned
+/// URL with the location (e.g. a header name) as a logical location.
+fn locations(finding: &Finding, scan_url: &str) -> JsonValue {
+    let is_url = Url::parse(&finding.location).is_ok_and(|u| matches!(u.scheme(), "http" | "https"));
+    let uri = if is_url { finding.location.as_str() } else { scan_url };
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": uri },
+            "region": { "startLine": 1 },
+        },
+    });
+    if !is_url && !finding.location.is_empty() {
+        location["logicalLocations"] = json!([{ "name": finding.location, "kind": "resource" }]);
+    }
+    json!([location])
+}
+
+fn result(annotated: &AnnotatedFinding, rule_index: usize, scan_url: &str) -> JsonValue {
+    let f = &annotated.finding;
+    let text = match &f.evidence {
+        Some(evidence) => format!("{} Evidence: {}", f.title, evidence),
+        None => f.title.clone(),
+    };
+    let mut result = json!({
+        "ruleId": f.rule_id,
+        "ruleIndex": rule_index,
+        "level": level(f.severity),
+        "message": { "text": text },
+        "locations": locations(f, scan_url),
+        "partialFingerprints": { "securascanFingerprint/v1": f.fingerprint() },
+        "properties": { "severity": f.severity.as_str() },
+    });
+    if let Some(s) = &annotated.suppression {
+        result["suppressions"] = json!([{
+            "kind": "external",
+            "status": "accepted",
+            "justification": format!("{}: {}", s.state, s.reason),
+            "properties": { "suppression_id": s.id, "author": s.author, "expires_at": s.expires_at },
+        }]);
+    }
+    result
+}
+
+/// The SARIF log of a scan: one run, one rule per rule ID seen, one result per
+/// finding. Suppressed findings are included with a `suppressions` entry.
+pub fn render(report: &ScanReport) -> JsonValue {
+    let scan = &report.scan;
+    let mut rule_ids: Vec<&str> = Vec::new();
+    let mut rules = Vec::new();
+    let mut results = Vec::new();
+    for annotated in &report.findings.findings {
+        let f = &annotated.finding;
+        let index = match rule_ids.iter().position(|id| *id == f.rule_id) {
+            Some(index) => index,
+            None => {
+                rule_ids.push(&f.rule_id);
+                rules.push(rule(f));
+                rule_ids.len() - 1
+            }
+        };
+        results.push(result(annotated, index, &scan.url));
+    }
+
+    // GitHub groups uploads into categories by the automation ID up to its last `/`.
+    let host = Url::parse(&scan.url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| "target".to_string());
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "SecuraScan",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "semanticVersion": env!("CARGO_PKG_VERSION"),
+                    "informationUri": MailConfig::global().app_url("/"),
+                    "rules": rules,
+                },
+            },
+            "automationDetails": { "id": format!("securascan/{}/{}", host, scan.id) },
+            "invocations": [{
+                "executionSuccessful": scan.status == "completed",
+                "startTimeUtc": scan.created_at,
+                "endTimeUtc": report.result.completed_at,
+            }],
+            "results": results,
+            "properties": {
+                "scan_id": scan.id,
+                "target": scan.url,
+                "grade": scan.grade,
+                "risk_score": scan.risk_score,
+                "counts": report.findings.counts,
+                "suppressed_count": report.findings.suppressed_count,
+                "report_url": MailConfig::global().app_url(&format!("/dashboard/{}", scan.id)),
+            },
+        }],
+    })
+}
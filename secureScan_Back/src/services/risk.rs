@@ -0,0 +1,326 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::findings::{self, Severity, SeverityCounts};
+use crate::domain::{scans_repo, suppressions_repo};
+
+/// Weighting used to turn severity counts into a risk score and grade.
+///
+/// `score = min(100, Σ weight(severity) × open findings of that severity)`,
+/// so 0 means no known risk and 100 is the worst. The grade is the first
+/// letter whose threshold is ≥ the score, or `F` beyond the last threshold.
+/// Weights are clamped to 0–100.
+///
+/// Configured through:
+/// - `RISK_WEIGHTS` (default `critical=40,high=20,medium=8,low=3,info=0`)
+/// - `RISK_GRADE_THRESHOLDS` (default `A=5,B=15,C=35,D=60`)
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+    pub critical: i32,
+    pub high: i32,
+    pub medium: i32,
+    pub low: i32,
+    pub info: i32,
+    /// `(grade, max score)` pairs in ascending order of score.
+    pub grades: Vec<(String, i32)>,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            critical: 40,
+            high: 20,
+            medium: 8,
+            low: 3,
+            info: 0,
+            grades: vec![
+                ("A".to_string(), 5),
+                ("B".to_string(), 15),
+                ("C".to_string(), 35),
+                ("D".to_string(), 60),
+            ],
+        }
+    }
+}
+
+/// Highest weight a severity can have; one finding then already scores 100.
+const MAX_WEIGHT: i32 = 100;
+
+/// Parse `key=value,key=value` pairs, skipping malformed entries.
+fn parse_pairs(raw: &str) -> Vec<(String, i32)> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            Some((k.trim().to_string(), v.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+impl RiskConfig {
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var("RISK_WEIGHTS").ok().as_deref(),
+            std::env::var("RISK_GRADE_THRESHOLDS").ok().as_deref(),
+        )
+    }
+
+    /// Defaults overridden by raw `RISK_WEIGHTS` / `RISK_GRADE_THRESHOLDS` values.
+    pub fn from_values(weights: Option<&str>, grade_thresholds: Option<&str>) -> Self {
+        let mut cfg = RiskConfig::default();
+
+        if let Some(raw) = weights {
+            for (key, weight) in parse_pairs(raw) {
+                if !(0..=MAX_WEIGHT).contains(&weight) {
+                    tracing::warn!("RISK_WEIGHTS: weight {} for {} clamped to 0-{}", weight, key, MAX_WEIGHT);
+                }
+                let weight = weight.clamp(0, MAX_WEIGHT);
+                match Severity::parse(&key) {
+                    Some(Severity::Critical) => cfg.critical = weight,
+                    Some(Severity::High) => cfg.high = weight,
+                    Some(Severity::Medium) => cfg.medium = weight,
+                    Some(Severity::Low) => cfg.low = weight,
+                    Some(Severity::Info) => cfg.info = weight,
+                    None => tracing::warn!("RISK_WEIGHTS: unknown severity {}", key),
+                }
+            }
+        }
+
+        if let Some(raw) = grade_thresholds {
+            let mut grades = parse_pairs(raw);
+            if grades.is_empty() {
+                tracing::warn!("RISK_GRADE_THRESHOLDS is empty or malformed; using defaults");
+            } else {
+                grades.sort_by_key(|(_, max)| *max);
+                cfg.grades = grades;
+            }
+        }
+
+        cfg
+    }
+
+    /// Process-wide configuration, read from the environment on first use.
+    pub fn global() -> &'static RiskConfig {
+        static CONFIG: OnceLock<RiskConfig> = OnceLock::new();
+        CONFIG.get_or_init(RiskConfig::from_env)
+    }
+
+    pub fn score(&self, counts: &SeverityCounts) -> i32 {
+        let raw = [
+            (self.critical, counts.critical),
+            (self.high, counts.high),
+            (self.medium, counts.medium),
+            (self.low, counts.low),
+            (self.info, counts.info),
+        ]
+        .iter()
+        .fold(0i32, |sum, (weight, count)| sum.saturating_add(weight.saturating_mul(*count)));
+        raw.clamp(0, 100)
+    }
+
+    pub fn grade(&self, score: i32) -> String {
+        self.grades
+            .iter()
+            .find(|(_, max)| score <= *max)
+            .map(|(grade, _)| grade.clone())
+            .unwrap_or_else(|| "F".to_string())
+    }
+}
+
+/// Severity counts, score and grade of one scan.
+#[derive(Debug, Clone)]
+pub struct RiskSummary {
+    pub counts: SeverityCounts,
+    pub score: i32,
+    pub grade: String,
+}
+
+pub fn summarize(counts: SeverityCounts, cfg: &RiskConfig) -> RiskSummary {
+    let score = cfg.score(&counts);
+    RiskSummary {
+        grade: cfg.grade(score),
+        score,
+        counts,
+    }
+}
+
+/// Recompute and store the risk summary of a scan from its latest result,
+/// ignoring findings covered by active suppressions. Scans without results
+/// are left unscored.
+pub async fn score_scan(pool: &DbPool, scan_id: Uuid) -> anyhow::Result<Option<RiskSummary>> {
+    let Some(scan) = scans_repo::get_scan(pool, scan_id).await? else {
+        return Ok(None);
+    };
+    if scans_repo::get_latest_scan_result(pool, scan_id).await?.is_none() {
+        return Ok(None);
+    }
+
+    let suppressions = suppressions_repo::list_active(pool, scan.org_id).await?;
+    let scan_findings = scans_repo::load_findings(pool, scan_id).await?;
+    let annotated = findings::annotate(scan_findings, &suppressions, scan.target_id, Utc::now());
+
+    let summary = summarize(annotated.counts, RiskConfig::global());
+    scans_repo::update_scan_risk(pool, scan_id, &summary.counts, summary.score, &summary.grade).await?;
+    Ok(Some(summary))
+}
+
+/// Re-score every scan with results, optionally only those of one organization
+/// or target. Used after suppressions change and to backfill scans created
+/// before scoring existed.
+pub async fn rescore_scans(
+    pool: &DbPool,
+    org_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    only_unscored: bool,
+) -> anyhow::Result<usize> {
+    let ids = scans_repo::list_scorable_scan_ids(pool, org_id, target_id, only_unscored).await?;
+    let mut scored = 0;
+    for id in ids {
+        match score_scan(pool, id).await {
+            Ok(Some(_)) => scored += 1,
+            Ok(None) => {}
+            Err(e) => tracing::error!("Failed to score scan {}: {}", id, e),
+        }
+    }
+    Ok(scored)
+}
+
+/// How long a background re-score waits for further requests to fold in.
+const RESCORE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+type RescoreKey = (Option<Uuid>, Option<Uuid>, bool);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RescoreState {
+    /// Waiting to start; new requests are covered by the coming run.
+    Queued,
+    Running,
+    /// Requested again while running; run once more afterwards.
+    Rerun,
+}
+
+fn rescores() -> &'static Mutex<HashMap<RescoreKey, RescoreState>> {
+    static RESCORES: OnceLock<Mutex<HashMap<RescoreKey, RescoreState>>> = OnceLock::new();
+    RESCORES.get_or_init(Default::default)
+}
+
+/// Run [`rescore_scans`] in the background. Requests for the same scope are
+/// coalesced: a burst of suppression changes re-scores once, and at most one
+/// re-score per scope runs at a time.
+pub fn spawn_rescore(pool: DbPool, org_id: Option<Uuid>, target_id: Option<Uuid>, only_unscored: bool) {
+    let key = (org_id, target_id, only_unscored);
+    {
+        let mut states = rescores().lock().unwrap();
+        match states.get(&key).copied() {
+            None => {
+                states.insert(key, RescoreState::Queued);
+            }
+            Some(RescoreState::Running) => {
+                states.insert(key, RescoreState::Rerun);
+                return;
+            }
+            Some(RescoreState::Queued | RescoreState::Rerun) => return,
+        }
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(RESCORE_DEBOUNCE).await;
+            rescores().lock().unwrap().insert(key, RescoreState::Running);
+
+            match rescore_scans(&pool, org_id, target_id, only_unscored).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Re-scored {} scan(s)", n),
+                Err(e) => tracing::error!("Re-scoring scans failed: {}", e),
+            }
+
+            let mut states = rescores().lock().unwrap();
+            if states.get(&key) == Some(&RescoreState::Rerun) {
+                states.insert(key, RescoreState::Queued);
+            } else {
+                states.remove(&key);
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(critical: i32, high: i32, medium: i32, low: i32, info: i32) -> SeverityCounts {
+        SeverityCounts {
+            critical,
+            high,
+            medium,
+            low,
+            info,
+            total: [high, medium, low, info].iter().fold(critical, |sum, n| sum.saturating_add(*n)),
+        }
+    }
+
+    fn grades(cfg: &RiskConfig) -> Vec<(&str, i32)> {
+        cfg.grades.iter().map(|(g, max)| (g.as_str(), *max)).collect()
+    }
+
+    #[test]
+    fn default_weights_and_grades() {
+        let cfg = RiskConfig::from_values(None, None);
+        assert_eq!((cfg.critical, cfg.high, cfg.medium, cfg.low, cfg.info), (40, 20, 8, 3, 0));
+        assert_eq!(grades(&cfg), [("A", 5), ("B", 15), ("C", 35), ("D", 60)]);
+
+        let summary = summarize(counts(0, 1, 2, 1, 5), &cfg);
+        assert_eq!((summary.score, summary.grade.as_str()), (20 + 16 + 3, "D"));
+        assert_eq!(summarize(counts(1, 1, 0, 0, 0), &cfg).grade, "D");
+        assert_eq!(summarize(counts(1, 1, 1, 0, 0), &cfg).grade, "F");
+    }
+
+    #[test]
+    fn grade_thresholds_are_inclusive() {
+        let cfg = RiskConfig::default();
+        for (score, grade) in [(0, "A"), (5, "A"), (6, "B"), (15, "B"), (35, "C"), (60, "D"), (61, "F"), (100, "F")] {
+            assert_eq!(cfg.grade(score), grade, "score {}", score);
+        }
+    }
+
+    #[test]
+    fn no_findings_score_zero() {
+        let summary = summarize(SeverityCounts::default(), &RiskConfig::default());
+        assert_eq!((summary.score, summary.grade.as_str()), (0, "A"));
+
+        let only_info = summarize(counts(0, 0, 0, 0, 1000), &RiskConfig::default());
+        assert_eq!((only_info.score, only_info.grade.as_str()), (0, "A"));
+    }
+
+    #[test]
+    fn weights_are_clamped_and_unknown_severities_ignored() {
+        let cfg = RiskConfig::from_values(Some(" critical = 500, high=-7, urgent=50, low=x, medium=9"), None);
+        assert_eq!((cfg.critical, cfg.high, cfg.medium, cfg.low, cfg.info), (MAX_WEIGHT, 0, 9, 3, 0));
+    }
+
+    #[test]
+    fn malformed_grade_thresholds_keep_defaults() {
+        for raw in ["", "garbage", "A:5,B:15", "A=five"] {
+            let cfg = RiskConfig::from_values(None, Some(raw));
+            assert_eq!(grades(&cfg), grades(&RiskConfig::default()), "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn grade_thresholds_are_sorted_and_skip_bad_entries() {
+        let cfg = RiskConfig::from_values(None, Some("C=50,A=10,B=oops,B=30"));
+        assert_eq!(grades(&cfg), [("A", 10), ("B", 30), ("C", 50)]);
+        assert_eq!(cfg.grade(51), "F");
+    }
+
+    #[test]
+    fn score_saturates_at_100() {
+        let cfg = RiskConfig::from_values(Some("critical=100,high=100,medium=100,low=100,info=100"), None);
+        assert_eq!(cfg.score(&counts(i32::MAX, i32::MAX, i32::MAX, i32::MAX, i32::MAX)), 100);
+        assert_eq!(RiskConfig::default().score(&counts(3, 0, 0, 0, 0)), 100);
+    }
+}
@@ -0,0 +1,62 @@
+//! A finished scan's open findings and which of them are new, as used by
+//! commit statuses, notifications, outbound webhooks and CI reports.
+
+use chrono::Utc;
+use std::collections::HashSet;
+
+use crate::db::DbPool;
+use crate::domain::findings::{self, AnnotatedFindings, Finding, SeverityCounts};
+use crate::domain::scans_repo::{self, ScanRow};
+use crate::domain::suppressions_repo;
+
+#[derive(Debug, Clone)]
+pub struct OpenFindings {
+    /// Open findings per severity.
+    pub counts: SeverityCounts,
+    /// Findings not covered by an active suppression, most severe first.
+    pub open: Vec<Finding>,
+    /// Open findings absent from the previous completed scan of the same
+    /// target, most severe first. On a target's first scan every finding is new.
+    pub new: Vec<Finding>,
+}
+
+impl OpenFindings {
+    /// Load a scan's findings and apply its organization's active suppressions.
+    pub async fn load(pool: &DbPool, scan: &ScanRow) -> Result<Self, sqlx::Error> {
+        let suppressions = suppressions_repo::list_active(pool, scan.org_id).await?;
+        let scan_findings = scans_repo::load_findings(pool, scan.id).await?;
+        let annotated = findings::annotate(scan_findings, &suppressions, scan.target_id, Utc::now());
+        Self::from_annotated(pool, scan, &annotated).await
+    }
+
+    /// Like [`OpenFindings::load`], for findings the caller already annotated.
+    pub async fn from_annotated(
+        pool: &DbPool,
+        scan: &ScanRow,
+        annotated: &AnnotatedFindings,
+    ) -> Result<Self, sqlx::Error> {
+        let mut open: Vec<Finding> = annotated
+            .findings
+            .iter()
+            .filter(|f| f.suppression.is_none())
+            .map(|f| f.finding.clone())
+            .collect();
+        open.sort_by_key(|f| std::cmp::Reverse(f.severity));
+
+        let baseline = match scans_repo::get_previous_completed_scan(pool, scan).await? {
+            Some(previous) => scans_repo::load_findings(pool, previous.id).await?,
+            None => Vec::new(),
+        };
+        let new = findings::diff(&open, &baseline).new;
+        Ok(Self {
+            counts: annotated.counts.clone(),
+            open,
+            new,
+        })
+    }
+
+    /// Fingerprints of the new findings.
+    pub fn new_fingerprints(&self) -> HashSet<String> {
+        self.new.iter().map(Finding::fingerprint).collect()
+    }
+}
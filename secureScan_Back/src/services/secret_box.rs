@@ -0,0 +1,145 @@
+//! Encryption of secrets the server must be able to read back (TOTP secrets,
+//! GitHub report tokens), so a leaked database dump does not leak them.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use data_encoding::{BASE64, HEXLOWER_PERMISSIVE};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Marks values sealed with AES-256-GCM; the rest is base64 of nonce and ciphertext.
+const PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum SecretBoxError {
+    /// Not sealed, sealed with another key, or tampered with
+    #[error("stored secret cannot be decrypted")]
+    Undecryptable,
+}
+
+/// Key for secrets stored encrypted.
+///
+/// Configured through:
+/// - `SECRETS_KEY` 32 bytes as 64 hex characters or base64. When unset the key is
+///   derived from `JWT_SECRET`, so rotating that secret makes stored secrets
+///   unreadable; without either a random per-process key is used.
+pub struct SecretBox {
+    cipher: Aes256Gcm,
+}
+
+fn parse_key(raw: &str) -> Option<[u8; 32]> {
+    let raw = raw.trim();
+    let bytes = HEXLOWER_PERMISSIVE
+        .decode(raw.as_bytes())
+        .or_else(|_| BASE64.decode(raw.as_bytes()))
+        .ok()?;
+    bytes.try_into().ok()
+}
+
+impl SecretBox {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)) }
+    }
+
+    pub fn from_env() -> Self {
+        if let Some(raw) = std::env::var("SECRETS_KEY").ok().filter(|s| !s.trim().is_empty()) {
+            // Another key would make every stored secret unreadable, so never fall back.
+            let key = parse_key(&raw).expect("SECRETS_KEY must be 32 bytes as hex or base64");
+            return Self::new(key);
+        }
+        match std::env::var("JWT_SECRET") {
+            Ok(s) if !s.trim().is_empty() => {
+                tracing::warn!("SECRETS_KEY is not set; deriving the encryption key of stored secrets from JWT_SECRET");
+                Self::new(Sha256::digest(format!("securascan/secrets:{}", s).as_bytes()).into())
+            }
+            _ => {
+                tracing::warn!("SECRETS_KEY is not set; using a random key, stored secrets will not survive a restart");
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                Self::new(key)
+            }
+        }
+    }
+
+    /// Process-wide key, read from the environment on first use.
+    pub fn global() -> &'static SecretBox {
+        static BOX: OnceLock<SecretBox> = OnceLock::new();
+        BOX.get_or_init(SecretBox::from_env)
+    }
+
+    pub fn seal(&self, plaintext: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("AES-GCM encryption of in-memory data does not fail");
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        format!("{}{}", PREFIX, BASE64.encode(&sealed))
+    }
+
+    pub fn open(&self, stored: &str) -> Result<String, SecretBoxError> {
+        let encoded = stored.strip_prefix(PREFIX).ok_or(SecretBoxError::Undecryptable)?;
+        let sealed = BASE64.decode(encoded.as_bytes()).map_err(|_| SecretBoxError::Undecryptable)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(SecretBoxError::Undecryptable);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretBoxError::Undecryptable)?;
+        String::from_utf8(plaintext).map_err(|_| SecretBoxError::Undecryptable)
+    }
+}
+
+/// Whether a stored value was sealed, as opposed to a plaintext value written
+/// before encryption existed.
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_open() {
+        let secret_box = SecretBox::new([7; 32]);
+        let sealed = secret_box.seal("JBSWY3DPEHPK3PXP");
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(secret_box.open(&sealed).unwrap(), "JBSWY3DPEHPK3PXP");
+        // A fresh nonce every time.
+        assert_ne!(secret_box.seal("JBSWY3DPEHPK3PXP"), sealed);
+    }
+
+    #[test]
+    fn open_rejects_other_keys_and_tampering() {
+        let sealed = SecretBox::new([7; 32]).seal("secret");
+        assert!(SecretBox::new([8; 32]).open(&sealed).is_err());
+
+        let mut bytes = BASE64.decode(sealed.strip_prefix(PREFIX).unwrap().as_bytes()).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let tampered = format!("{}{}", PREFIX, BASE64.encode(&bytes));
+        assert!(SecretBox::new([7; 32]).open(&tampered).is_err());
+
+        assert!(SecretBox::new([7; 32]).open("secret").is_err());
+        assert!(SecretBox::new([7; 32]).open("v1:AAAA").is_err());
+        assert!(!is_sealed("secret"));
+    }
+
+    #[test]
+    fn keys_in_hex_or_base64() {
+        assert_eq!(parse_key(&"ab".repeat(32)), Some([0xab; 32]));
+        assert_eq!(parse_key(&format!(" {} ", "AB".repeat(32))), Some([0xab; 32]));
+        assert_eq!(parse_key(&BASE64.encode(&[1; 32])), Some([1; 32]));
+        assert_eq!(parse_key(&"ab".repeat(16)), None);
+        assert_eq!(parse_key("not a key"), None);
+    }
+}
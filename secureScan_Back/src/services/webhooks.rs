@@ -0,0 +1,794 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::repository_targets_repo::{self, RepositoryTargetRow};
+use crate::domain::scans_repo;
+use crate::domain::webhook_deliveries_repo::{self, DeliveryKey};
+use crate::jobs::queue::{ScanJob, ScanQueue};
+use crate::scanner::ScanOptions;
+use crate::services::commit_reports;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Senders of inbound webhooks.
+pub mod provider {
+    pub const GITHUB: &str = "github";
+    pub const GITLAB: &str = "gitlab";
+    /// Any system that signs the documented payload, see [`super::parse_generic`].
+    pub const GENERIC: &str = "generic";
+
+    pub const ALL: [&str; 3] = [GITHUB, GITLAB, GENERIC];
+}
+
+/// Events a repository target can listen to. Provider events are mapped onto these.
+pub mod events {
+    pub const PUSH: &str = "push";
+    /// GitHub pull requests, GitLab merge requests.
+    pub const PULL_REQUEST: &str = "pull_request";
+    pub const DEPLOYMENT_STATUS: &str = "deployment_status";
+    /// A successful CI pipeline (GitLab pipeline events).
+    pub const PIPELINE: &str = "pipeline";
+
+    pub const ALL: [&str; 4] = [PUSH, PULL_REQUEST, DEPLOYMENT_STATUS, PIPELINE];
+    /// Selected when a repository target names none; pipelines would mostly rescan pushes.
+    pub const DEFAULT: [&str; 3] = [PUSH, PULL_REQUEST, DEPLOYMENT_STATUS];
+}
+
+/// Headers of the generic format: the signing time and `sha256=<hex>` of
+/// HMAC-SHA256(secret, `<timestamp>.<body>`).
+pub const GENERIC_TIMESTAMP_HEADER: &str = "X-SecuraScan-Timestamp";
+pub const GENERIC_SIGNATURE_HEADER: &str = "X-SecuraScan-Signature";
+
+/// How far a generic delivery's timestamp may be from now, against replays.
+const GENERIC_TOLERANCE_SECS: i64 = 300;
+
+/// Pull request actions that bring new code to scan.
+const PULL_REQUEST_ACTIONS: [&str; 4] = ["opened", "synchronize", "reopened", "ready_for_review"];
+
+/// A repository event that may start scans, independent of the provider that sent it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoEvent {
+    /// One of [`events::ALL`].
+    pub event: &'static str,
+    /// `owner/name`, lower case.
+    pub repository: String,
+    pub branch: Option<String>,
+    pub commit_sha: Option<String>,
+    pub pull_request: Option<i64>,
+    pub environment: Option<String>,
+    /// URL a successful deployment is reachable at.
+    pub deployment_url: Option<String>,
+}
+
+/// What a webhook payload amounts to.
+#[derive(Debug)]
+pub enum ParsedEvent {
+    Event(RepoEvent),
+    /// The provider checking the endpoint is reachable.
+    Ping,
+    /// Nothing to scan; the reason is returned to the provider for its delivery log.
+    Ignored(String),
+}
+
+/// Request headers of a delivery, looked up case-insensitively.
+pub trait HeaderLookup {
+    fn header(&self, name: &str) -> Option<&str>;
+}
+
+fn header<'a>(headers: &'a impl HeaderLookup, name: &str) -> Option<&'a str> {
+    headers.header(name).map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn hmac_sha256(secret: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Constant time comparison of a `sha256=<hex>` header with the expected MAC.
+fn signature_matches(header: &str, expected: &[u8]) -> bool {
+    let Some(hex_sig) = header.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(given) = hex::decode(hex_sig) else {
+        return false;
+    };
+    given.len() == expected.len() && given.ct_eq(expected).unwrap_u8() == 1u8
+}
+
+/// Organization the legacy GitHub endpoint starts scans in (`GITHUB_WEBHOOK_ORG_ID`).
+pub fn legacy_github_org() -> Option<Uuid> {
+    std::env::var("GITHUB_WEBHOOK_ORG_ID").ok().and_then(|s| s.trim().parse().ok())
+}
+
+/// Whether `repository` is an `owner/name` path the provider can have, safe to
+/// put into API URLs: GitHub has exactly two segments, GitLab groups nest.
+/// Segments consist of `[A-Za-z0-9_.-]` and are never `.` or `..`.
+pub fn is_valid_repository(provider_name: &str, repository: &str) -> bool {
+    let parts: Vec<&str> = repository.split('/').collect();
+    let shape = if provider_name == provider::GITHUB { parts.len() == 2 } else { parts.len() >= 2 };
+    shape
+        && parts.iter().all(|p| {
+            !p.is_empty()
+                && *p != "."
+                && *p != ".."
+                && p.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'-'))
+        })
+}
+
+/// Whether `sha` is a full 40 character commit SHA.
+pub fn is_valid_commit_sha(sha: &str) -> bool {
+    sha.len() == 40 && sha.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// A new random integration secret.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// `sha256=<hex>` signature of a generic delivery sent at `timestamp` (Unix seconds).
+pub fn sign_generic(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mac = hmac_sha256(secret, &[timestamp.to_string().as_bytes(), b".", body]);
+    format!("sha256={}", hex::encode(mac))
+}
+
+/// Check that a delivery of `provider` was sent by the holder of `secret`.
+pub fn verify(provider: &str, secret: &[u8], headers: &impl HeaderLookup, body: &[u8]) -> Result<(), &'static str> {
+    match provider {
+        provider::GITHUB => {
+            let sig = header(headers, "X-Hub-Signature-256").ok_or("missing X-Hub-Signature-256")?;
+            if !signature_matches(sig, &hmac_sha256(secret, &[body])) {
+                return Err("signature mismatch");
+            }
+        }
+        provider::GITLAB => {
+            // GitLab sends the secret token itself.
+            let token = header(headers, "X-Gitlab-Token").ok_or("missing X-Gitlab-Token")?;
+            if token.len() != secret.len() || token.as_bytes().ct_eq(secret).unwrap_u8() != 1u8 {
+                return Err("token mismatch");
+            }
+        }
+        provider::GENERIC => {
+            let timestamp: i64 = header(headers, GENERIC_TIMESTAMP_HEADER)
+                .and_then(|t| t.parse().ok())
+                .ok_or("missing or invalid X-SecuraScan-Timestamp")?;
+            if (Utc::now().timestamp() - timestamp).abs() > GENERIC_TOLERANCE_SECS {
+                return Err("timestamp outside the allowed window");
+            }
+            let sig = header(headers, GENERIC_SIGNATURE_HEADER).ok_or("missing X-SecuraScan-Signature")?;
+            let expected = hmac_sha256(secret, &[timestamp.to_string().as_bytes(), b".", body]);
+            if !signature_matches(sig, &expected) {
+                return Err("signature mismatch");
+            }
+        }
+        _ => return Err("unknown provider"),
+    }
+    Ok(())
+}
+
+/// A verified delivery, ready to be mapped to scans.
+#[derive(Debug)]
+pub struct Inbound {
+    pub delivery_id: String,
+    pub parsed: ParsedEvent,
+}
+
+/// Interpret a delivery of `provider`: its ID, for deduplication, and its event.
+pub fn parse(provider: &str, headers: &impl HeaderLookup, payload: &JsonValue) -> Result<Inbound, String> {
+    let (delivery_id, parsed) = match provider {
+        provider::GITHUB => {
+            let event = header(headers, "X-GitHub-Event").ok_or("missing X-GitHub-Event")?;
+            let delivery = header(headers, "X-GitHub-Delivery").ok_or("missing X-GitHub-Delivery")?;
+            (delivery.to_string(), parse_github(event, payload)?)
+        }
+        provider::GITLAB => {
+            let event = header(headers, "X-Gitlab-Event").ok_or("missing X-Gitlab-Event")?;
+            // Idempotency-Key stays the same when GitLab retries a delivery.
+            let delivery = header(headers, "Idempotency-Key")
+                .or_else(|| header(headers, "X-Gitlab-Event-UUID"))
+                .ok_or("missing X-Gitlab-Event-UUID")?;
+            (delivery.to_string(), parse_gitlab(event, payload)?)
+        }
+        provider::GENERIC => {
+            let delivery = str_at(payload, "/id").ok_or("payload has no id")?;
+            (delivery.to_string(), parse_generic(payload)?)
+        }
+        other => return Err(format!("unknown provider {}", other)),
+    };
+    Ok(Inbound { delivery_id, parsed })
+}
+
+fn str_at<'a>(payload: &'a JsonValue, pointer: &str) -> Option<&'a str> {
+    payload.pointer(pointer).and_then(|v| v.as_str()).filter(|s| !s.is_empty())
+}
+
+fn owned_at(payload: &JsonValue, pointer: &str) -> Option<String> {
+    str_at(payload, pointer).map(str::to_string)
+}
+
+/// Interpret a GitHub webhook of type `event` (the `X-GitHub-Event` header).
+pub fn parse_github(event: &str, payload: &JsonValue) -> Result<ParsedEvent, String> {
+    if event == "ping" {
+        return Ok(ParsedEvent::Ping);
+    }
+    let repository = str_at(payload, "/repository/full_name")
+        .ok_or_else(|| "payload has no repository.full_name".to_string())?
+        .to_lowercase();
+
+    match event {
+        events::PUSH => {
+            if payload.get("deleted").and_then(|v| v.as_bool()).unwrap_or(false) {
+                return Ok(ParsedEvent::Ignored("branch deleted".into()));
+            }
+            let git_ref = str_at(payload, "/ref").unwrap_or_default();
+            let Some(branch) = git_ref.strip_prefix("refs/heads/") else {
+                return Ok(ParsedEvent::Ignored(format!("not a branch: {}", git_ref)));
+            };
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::PUSH,
+                repository,
+                branch: Some(branch.to_string()),
+                commit_sha: owned_at(payload, "/after"),
+                pull_request: None,
+                environment: None,
+                deployment_url: None,
+            }))
+        }
+        events::PULL_REQUEST => {
+            let action = str_at(payload, "/action").unwrap_or_default();
+            if !PULL_REQUEST_ACTIONS.contains(&action) {
+                return Ok(ParsedEvent::Ignored(format!("pull request action {}", action)));
+            }
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::PULL_REQUEST,
+                repository,
+                // Branch filters apply to the branch the pull request merges into.
+                branch: owned_at(payload, "/pull_request/base/ref"),
+                commit_sha: owned_at(payload, "/pull_request/head/sha"),
+                pull_request: payload.pointer("/pull_request/number").and_then(|v| v.as_i64()),
+                environment: None,
+                deployment_url: None,
+            }))
+        }
+        events::DEPLOYMENT_STATUS => {
+            let state = str_at(payload, "/deployment_status/state").unwrap_or_default();
+            if state != "success" {
+                return Ok(ParsedEvent::Ignored(format!("deployment state {}", state)));
+            }
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::DEPLOYMENT_STATUS,
+                repository,
+                branch: owned_at(payload, "/deployment/ref"),
+                commit_sha: owned_at(payload, "/deployment/sha"),
+                pull_request: None,
+                environment: owned_at(payload, "/deployment_status/environment")
+                    .or_else(|| owned_at(payload, "/deployment/environment")),
+                deployment_url: owned_at(payload, "/deployment_status/environment_url")
+                    .or_else(|| owned_at(payload, "/deployment_status/target_url")),
+            }))
+        }
+        other => Ok(ParsedEvent::Ignored(format!("unsupported event {}", other))),
+    }
+}
+
+/// An all-zero SHA, which GitLab sends as `after` when a branch is deleted.
+fn is_null_sha(sha: &str) -> bool {
+    sha.bytes().all(|b| b == b'0')
+}
+
+/// Interpret a GitLab webhook of type `event` (the `X-Gitlab-Event` header).
+pub fn parse_gitlab(event: &str, payload: &JsonValue) -> Result<ParsedEvent, String> {
+    let repository = str_at(payload, "/project/path_with_namespace")
+        .ok_or_else(|| "payload has no project.path_with_namespace".to_string())?
+        .to_lowercase();
+
+    match event {
+        "Push Hook" => {
+            let after = str_at(payload, "/after").unwrap_or_default();
+            if is_null_sha(after) {
+                return Ok(ParsedEvent::Ignored("branch deleted".into()));
+            }
+            let git_ref = str_at(payload, "/ref").unwrap_or_default();
+            let Some(branch) = git_ref.strip_prefix("refs/heads/") else {
+                return Ok(ParsedEvent::Ignored(format!("not a branch: {}", git_ref)));
+            };
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::PUSH,
+                repository,
+                branch: Some(branch.to_string()),
+                commit_sha: owned_at(payload, "/checkout_sha").or_else(|| Some(after.to_string())),
+                pull_request: None,
+                environment: None,
+                deployment_url: None,
+            }))
+        }
+        "Merge Request Hook" => {
+            let action = str_at(payload, "/object_attributes/action").unwrap_or_default();
+            // `update` also fires for title or label changes; only new commits carry `oldrev`.
+            let new_commits = action == "update" && payload.pointer("/object_attributes/oldrev").is_some();
+            if !(matches!(action, "open" | "reopen") || new_commits) {
+                return Ok(ParsedEvent::Ignored(format!("merge request action {}", action)));
+            }
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::PULL_REQUEST,
+                repository,
+                branch: owned_at(payload, "/object_attributes/target_branch"),
+                commit_sha: owned_at(payload, "/object_attributes/last_commit/id"),
+                pull_request: payload.pointer("/object_attributes/iid").and_then(|v| v.as_i64()),
+                environment: None,
+                deployment_url: None,
+            }))
+        }
+        "Pipeline Hook" => {
+            let status = str_at(payload, "/object_attributes/status").unwrap_or_default();
+            if status != "success" {
+                return Ok(ParsedEvent::Ignored(format!("pipeline status {}", status)));
+            }
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::PIPELINE,
+                repository,
+                branch: owned_at(payload, "/object_attributes/ref"),
+                commit_sha: owned_at(payload, "/object_attributes/sha"),
+                pull_request: None,
+                environment: None,
+                deployment_url: None,
+            }))
+        }
+        "Deployment Hook" => {
+            let status = str_at(payload, "/status").unwrap_or_default();
+            if status != "success" {
+                return Ok(ParsedEvent::Ignored(format!("deployment status {}", status)));
+            }
+            Ok(ParsedEvent::Event(RepoEvent {
+                event: events::DEPLOYMENT_STATUS,
+                repository,
+                branch: owned_at(payload, "/ref"),
+                commit_sha: owned_at(payload, "/commit_sha").or_else(|| owned_at(payload, "/sha")),
+                pull_request: None,
+                environment: owned_at(payload, "/environment"),
+                deployment_url: owned_at(payload, "/environment_external_url"),
+            }))
+        }
+        other => Ok(ParsedEvent::Ignored(format!("unsupported event {}", other))),
+    }
+}
+
+/// Interpret a generic webhook:
+///
+/// ```json
+/// { "id": "unique delivery id", "event": "push", "repository": "owner/name",
+///   "branch": "main", "commit_sha": "…", "environment": "preview", "url": "https://…" }
+/// ```
+///
+/// `event` is one of [`events::ALL`] or `ping`; `url` is the deployment URL of a
+/// `deployment_status` event. Only `id`, `event` and `repository` are required.
+pub fn parse_generic(payload: &JsonValue) -> Result<ParsedEvent, String> {
+    let event = str_at(payload, "/event").ok_or("payload has no event")?;
+    if event == "ping" {
+        return Ok(ParsedEvent::Ping);
+    }
+    let Some(event) = events::ALL.iter().copied().find(|e| *e == event) else {
+        return Ok(ParsedEvent::Ignored(format!("unsupported event {}", event)));
+    };
+    let repository = str_at(payload, "/repository")
+        .ok_or("payload has no repository")?
+        .to_lowercase();
+    Ok(ParsedEvent::Event(RepoEvent {
+        event,
+        repository,
+        branch: owned_at(payload, "/branch"),
+        commit_sha: owned_at(payload, "/commit_sha"),
+        pull_request: payload.get("pull_request").and_then(|v| v.as_i64()),
+        environment: owned_at(payload, "/environment"),
+        deployment_url: owned_at(payload, "/url"),
+    }))
+}
+
+/// Whether `value` matches one of `patterns`; an empty list matches everything.
+/// A trailing `*` matches any suffix, e.g. `release/*`.
+fn matches_any(patterns: &[String], value: Option<&str>) -> bool {
+    if patterns.is_empty() {
+        return true;
+    }
+    let Some(value) = value else {
+        return false;
+    };
+    patterns.iter().any(|p| match p.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => p == value,
+    })
+}
+
+/// The URL `target` scans for `event`, or why it does not apply.
+fn scan_url(target: &RepositoryTargetRow, event: &RepoEvent) -> Result<String, String> {
+    if !matches_any(&target.branches, event.branch.as_deref()) {
+        return Err("branch not selected".into());
+    }
+    if event.event == events::DEPLOYMENT_STATUS {
+        if !matches_any(&target.environments, event.environment.as_deref()) {
+            return Err("environment not selected".into());
+        }
+        if target.use_deployment_url {
+            if let Some(url) = event.deployment_url.as_deref().filter(|u| is_scannable(u)) {
+                return Ok(url.to_string());
+            }
+        }
+    }
+    target
+        .target_url
+        .clone()
+        .ok_or_else(|| "no target_url and no deployment URL".into())
+}
+
+pub fn is_scannable(url: &str) -> bool {
+    Url::parse(url)
+        .map(|u| matches!(u.scheme(), "http" | "https") && u.host().is_some())
+        .unwrap_or(false)
+}
+
+/// A scan started by a webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggeredScan {
+    pub scan_id: Uuid,
+    pub repository_target_id: Uuid,
+    pub url: String,
+}
+
+/// A repository target that listens to the event but did not scan.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedTarget {
+    pub repository_target_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub enum DeliveryOutcome {
+    /// The delivery was handled before; these scans came from it.
+    Duplicate { scan_ids: Vec<Uuid> },
+    Handled {
+        scans: Vec<TriggeredScan>,
+        skipped: Vec<SkippedTarget>,
+    },
+}
+
+/// Start the scans `event` maps to in `org_id`, once per delivery `key`.
+pub async fn handle_delivery(
+    pool: &DbPool,
+    queue: &ScanQueue,
+    key: DeliveryKey<'_>,
+    org_id: Uuid,
+    event: &RepoEvent,
+) -> anyhow::Result<DeliveryOutcome> {
+    let claimed = webhook_deliveries_repo::claim_delivery(pool, key, event.event, Some(&event.repository)).await?;
+    let Some(already_scanned) = claimed else {
+        let scan_ids = webhook_deliveries_repo::get_delivery(pool, key)
+            .await?
+            .map(|d| d.scan_ids)
+            .unwrap_or_default();
+        return Ok(DeliveryOutcome::Duplicate { scan_ids });
+    };
+
+    match trigger_scans(pool, queue, key, org_id, event, &already_scanned).await {
+        Ok((scans, skipped)) => Ok(DeliveryOutcome::Handled { scans, skipped }),
+        Err(e) => {
+            // Let the provider's redelivery start the scans that are still missing.
+            if let Err(mark) = webhook_deliveries_repo::mark_delivery_failed(pool, key).await {
+                tracing::error!("webhook: could not mark delivery {} as failed: {}", key.delivery_id, mark);
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn trigger_scans(
+    pool: &DbPool,
+    queue: &ScanQueue,
+    key: DeliveryKey<'_>,
+    org_id: Uuid,
+    event: &RepoEvent,
+    already_scanned: &[Uuid],
+) -> anyhow::Result<(Vec<TriggeredScan>, Vec<SkippedTarget>)> {
+    let targets =
+        repository_targets_repo::find_for_event(pool, org_id, key.provider, &event.repository, event.event).await?;
+
+    let mut scans = Vec::new();
+    let mut skipped = Vec::new();
+    for target in targets {
+        if already_scanned.contains(&target.id) {
+            skipped.push(SkippedTarget {
+                repository_target_id: target.id,
+                reason: "scanned by an earlier attempt of this delivery".to_string(),
+            });
+            continue;
+        }
+        let url = match scan_url(&target, event) {
+            Ok(url) => url,
+            Err(reason) => {
+                skipped.push(SkippedTarget {
+                    repository_target_id: target.id,
+                    reason,
+                });
+                continue;
+            }
+        };
+        let scan_id = enqueue_scan(pool, queue, &target, event, key.integration_id, &url).await?;
+        webhook_deliveries_repo::record_delivery_scan(pool, key, target.id, scan_id).await?;
+        tracing::info!(
+            "webhook: {} on {} started scan {} of {}",
+            event.event,
+            event.repository,
+            scan_id,
+            url
+        );
+        scans.push(TriggeredScan {
+            scan_id,
+            repository_target_id: target.id,
+            url,
+        });
+    }
+    Ok((scans, skipped))
+}
+
+async fn enqueue_scan(
+    pool: &DbPool,
+    queue: &ScanQueue,
+    target: &RepositoryTargetRow,
+    event: &RepoEvent,
+    integration_id: Option<Uuid>,
+    url: &str,
+) -> anyhow::Result<Uuid> {
+    let parsed = Url::parse(url)?;
+    let options: ScanOptions = serde_json::from_value(target.options.clone()).unwrap_or_else(|e| {
+        tracing::warn!("webhook: ignoring invalid options on repository target {}: {}", target.id, e);
+        ScanOptions::default()
+    });
+
+    let row = scans_repo::create_scan(
+        pool,
+        &scans_repo::NewScan {
+            url: url.to_string(),
+            options: target.options.clone(),
+            schedule_id: None,
+            owner_id: target.created_by,
+            org_id: Some(target.org_id),
+        },
+    )
+    .await?;
+    // Registered before the job is queued so the worker finds it when the scan starts.
+    commit_reports::register(pool, row.id, target, event, integration_id).await?;
+    queue
+        .enqueue(ScanJob {
+            id: row.id,
+            target: parsed,
+            options,
+        })
+        .await?;
+    Ok(row.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Headers(Vec<(&'static str, String)>);
+
+    impl HeaderLookup for Headers {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.0
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        }
+    }
+
+    const SECRET: &[u8] = b"whsec_test";
+    const BODY: &[u8] = br#"{"ref":"refs/heads/main"}"#;
+
+    fn event(parsed: Result<ParsedEvent, String>) -> RepoEvent {
+        match parsed {
+            Ok(ParsedEvent::Event(event)) => event,
+            other => panic!("expected an event, got {:?}", other),
+        }
+    }
+
+    fn ignored(parsed: Result<ParsedEvent, String>) -> bool {
+        matches!(parsed, Ok(ParsedEvent::Ignored(_)))
+    }
+
+    #[test]
+    fn github_signature() {
+        let sig = format!("sha256={}", hex::encode(hmac_sha256(SECRET, &[BODY])));
+        let headers = Headers(vec![("x-hub-signature-256", sig.clone())]);
+        assert_eq!(verify(provider::GITHUB, SECRET, &headers, BODY), Ok(()));
+        assert_eq!(verify(provider::GITHUB, b"other", &headers, BODY), Err("signature mismatch"));
+        assert_eq!(verify(provider::GITHUB, SECRET, &headers, b"{}"), Err("signature mismatch"));
+
+        let unprefixed = Headers(vec![("X-Hub-Signature-256", sig.trim_start_matches("sha256=").to_string())]);
+        assert!(verify(provider::GITHUB, SECRET, &unprefixed, BODY).is_err());
+        let truncated = Headers(vec![("X-Hub-Signature-256", sig[..sig.len() - 2].to_string())]);
+        assert!(verify(provider::GITHUB, SECRET, &truncated, BODY).is_err());
+        assert_eq!(verify(provider::GITHUB, SECRET, &Headers(vec![]), BODY), Err("missing X-Hub-Signature-256"));
+    }
+
+    #[test]
+    fn gitlab_token() {
+        let headers = |token: &str| Headers(vec![("X-Gitlab-Token", token.to_string())]);
+        assert_eq!(verify(provider::GITLAB, SECRET, &headers("whsec_test"), BODY), Ok(()));
+        assert_eq!(verify(provider::GITLAB, SECRET, &headers("whsec_tesT"), BODY), Err("token mismatch"));
+        assert_eq!(verify(provider::GITLAB, SECRET, &headers("whsec_test2"), BODY), Err("token mismatch"));
+        assert_eq!(verify(provider::GITLAB, SECRET, &headers(" "), BODY), Err("missing X-Gitlab-Token"));
+    }
+
+    #[test]
+    fn generic_signature_and_timestamp_window() {
+        let signed = |timestamp: i64, secret: &[u8]| {
+            Headers(vec![
+                (GENERIC_TIMESTAMP_HEADER, timestamp.to_string()),
+                (GENERIC_SIGNATURE_HEADER, sign_generic(secret, timestamp, BODY)),
+            ])
+        };
+        let now = Utc::now().timestamp();
+        assert_eq!(verify(provider::GENERIC, SECRET, &signed(now, SECRET), BODY), Ok(()));
+        assert_eq!(verify(provider::GENERIC, SECRET, &signed(now - 60, SECRET), BODY), Ok(()));
+        assert_eq!(
+            verify(provider::GENERIC, SECRET, &signed(now - GENERIC_TOLERANCE_SECS - 60, SECRET), BODY),
+            Err("timestamp outside the allowed window")
+        );
+        assert_eq!(
+            verify(provider::GENERIC, SECRET, &signed(now + GENERIC_TOLERANCE_SECS + 60, SECRET), BODY),
+            Err("timestamp outside the allowed window")
+        );
+        assert_eq!(verify(provider::GENERIC, SECRET, &signed(now, b"other"), BODY), Err("signature mismatch"));
+
+        // The timestamp is part of the signed message.
+        let replayed = Headers(vec![
+            (GENERIC_TIMESTAMP_HEADER, (now + 1).to_string()),
+            (GENERIC_SIGNATURE_HEADER, sign_generic(SECRET, now, BODY)),
+        ]);
+        assert_eq!(verify(provider::GENERIC, SECRET, &replayed, BODY), Err("signature mismatch"));
+
+        let no_timestamp = Headers(vec![(GENERIC_SIGNATURE_HEADER, sign_generic(SECRET, now, BODY))]);
+        assert!(verify(provider::GENERIC, SECRET, &no_timestamp, BODY).is_err());
+        assert_eq!(verify("bitbucket", SECRET, &signed(now, SECRET), BODY), Err("unknown provider"));
+    }
+
+    #[test]
+    fn github_events() {
+        let push = json!({
+            "ref": "refs/heads/main",
+            "after": "a".repeat(40),
+            "repository": { "full_name": "Acme/Web" }
+        });
+        let parsed = event(parse_github("push", &push));
+        assert_eq!(parsed.event, events::PUSH);
+        assert_eq!(parsed.repository, "acme/web");
+        assert_eq!(parsed.branch.as_deref(), Some("main"));
+        assert_eq!(parsed.commit_sha, Some("a".repeat(40)));
+
+        let tag = json!({ "ref": "refs/tags/v1", "repository": { "full_name": "acme/web" } });
+        assert!(ignored(parse_github("push", &tag)));
+        let deleted = json!({ "ref": "refs/heads/main", "deleted": true, "repository": { "full_name": "acme/web" } });
+        assert!(ignored(parse_github("push", &deleted)));
+
+        let pull_request = |action: &str| {
+            json!({
+                "action": action,
+                "repository": { "full_name": "acme/web" },
+                "pull_request": { "number": 7, "base": { "ref": "main" }, "head": { "sha": "b".repeat(40) } }
+            })
+        };
+        let parsed = event(parse_github("pull_request", &pull_request("synchronize")));
+        assert_eq!(parsed.event, events::PULL_REQUEST);
+        assert_eq!(parsed.pull_request, Some(7));
+        assert_eq!(parsed.branch.as_deref(), Some("main"));
+        assert!(ignored(parse_github("pull_request", &pull_request("closed"))));
+
+        let deployment = |state: &str| {
+            json!({
+                "repository": { "full_name": "acme/web" },
+                "deployment": { "ref": "main", "sha": "c".repeat(40), "environment": "staging" },
+                "deployment_status": { "state": state, "environment_url": "https://staging.acme.example" }
+            })
+        };
+        let parsed = event(parse_github("deployment_status", &deployment("success")));
+        assert_eq!(parsed.environment.as_deref(), Some("staging"));
+        assert_eq!(parsed.deployment_url.as_deref(), Some("https://staging.acme.example"));
+        assert!(ignored(parse_github("deployment_status", &deployment("failure"))));
+
+        assert!(matches!(parse_github("ping", &json!({})), Ok(ParsedEvent::Ping)));
+        assert!(ignored(parse_github("issues", &json!({ "repository": { "full_name": "acme/web" } }))));
+        assert!(parse_github("push", &json!({ "ref": "refs/heads/main" })).is_err());
+    }
+
+    #[test]
+    fn gitlab_events() {
+        let push = |after: &str| {
+            json!({
+                "ref": "refs/heads/main",
+                "after": after,
+                "checkout_sha": after,
+                "project": { "path_with_namespace": "Group/Sub/Web" }
+            })
+        };
+        let parsed = event(parse_gitlab("Push Hook", &push(&"d".repeat(40))));
+        assert_eq!(parsed.event, events::PUSH);
+        assert_eq!(parsed.repository, "group/sub/web");
+        assert!(ignored(parse_gitlab("Push Hook", &push(&"0".repeat(40)))));
+
+        let merge_request = |attributes: JsonValue| {
+            json!({ "project": { "path_with_namespace": "group/web" }, "object_attributes": attributes })
+        };
+        let opened = merge_request(json!({ "action": "open", "iid": 3, "target_branch": "main" }));
+        let parsed = event(parse_gitlab("Merge Request Hook", &opened));
+        assert_eq!(parsed.event, events::PULL_REQUEST);
+        assert_eq!(parsed.pull_request, Some(3));
+        let new_commits = merge_request(json!({ "action": "update", "oldrev": "e".repeat(40) }));
+        assert!(matches!(parse_gitlab("Merge Request Hook", &new_commits), Ok(ParsedEvent::Event(_))));
+        let relabeled = merge_request(json!({ "action": "update" }));
+        assert!(ignored(parse_gitlab("Merge Request Hook", &relabeled)));
+
+        let pipeline = |status: &str| merge_request(json!({ "status": status, "ref": "main" }));
+        assert_eq!(event(parse_gitlab("Pipeline Hook", &pipeline("success"))).event, events::PIPELINE);
+        assert!(ignored(parse_gitlab("Pipeline Hook", &pipeline("failed"))));
+
+        assert!(parse_gitlab("Push Hook", &json!({})).is_err());
+    }
+
+    #[test]
+    fn generic_events() {
+        let parsed = event(parse_generic(&json!({
+            "id": "1",
+            "event": "deployment_status",
+            "repository": "Acme/Web",
+            "url": "https://preview.acme.example"
+        })));
+        assert_eq!(parsed.event, events::DEPLOYMENT_STATUS);
+        assert_eq!(parsed.repository, "acme/web");
+        assert_eq!(parsed.deployment_url.as_deref(), Some("https://preview.acme.example"));
+
+        assert!(matches!(parse_generic(&json!({ "event": "ping" })), Ok(ParsedEvent::Ping)));
+        assert!(ignored(parse_generic(&json!({ "event": "release", "repository": "acme/web" }))));
+        assert!(parse_generic(&json!({ "event": "push" })).is_err());
+        assert!(parse_generic(&json!({ "repository": "acme/web" })).is_err());
+    }
+
+    #[test]
+    fn delivery_ids() {
+        let headers = Headers(vec![
+            ("X-Gitlab-Event", "Push Hook".to_string()),
+            ("X-Gitlab-Event-UUID", "event-uuid".to_string()),
+            ("Idempotency-Key", "retry-key".to_string()),
+        ]);
+        let payload = json!({ "ref": "refs/tags/v1", "project": { "path_with_namespace": "group/web" } });
+        assert_eq!(parse(provider::GITLAB, &headers, &payload).unwrap().delivery_id, "retry-key");
+
+        let headers = Headers(vec![("X-GitHub-Event", "ping".to_string())]);
+        assert!(parse(provider::GITHUB, &headers, &json!({})).is_err());
+    }
+
+    #[test]
+    fn repository_names() {
+        assert!(is_valid_repository(provider::GITHUB, "acme/web"));
+        assert!(is_valid_repository(provider::GITHUB, "acme-co/web.site_2"));
+        assert!(!is_valid_repository(provider::GITHUB, "group/sub/web"));
+        assert!(is_valid_repository(provider::GITLAB, "group/sub/web"));
+        assert!(!is_valid_repository(provider::GITLAB, "web"));
+        assert!(!is_valid_repository(provider::GITLAB, "group//web"));
+        assert!(!is_valid_repository(provider::GITHUB, "acme/.."));
+        assert!(!is_valid_repository(provider::GITHUB, "acme/web?x=1"));
+        assert!(!is_valid_repository(provider::GITHUB, "acme/w%2Feb"));
+    }
+}
@@ -0,0 +1,275 @@
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::mfa_repo;
+use crate::domain::orgs_repo::{self, MembershipRow, Permission, Role};
+use crate::services::auth::{self as auth_svc, AuthError};
+use crate::services::login_guard::ClientAddr;
+
+/// Whether `Forwarded` / `X-Forwarded-For` are trusted for the client address.
+/// Enable with `TRUST_PROXY_HEADERS=true` only behind a proxy that sets them.
+pub fn trust_proxy_headers() -> bool {
+    static TRUST: OnceLock<bool> = OnceLock::new();
+    *TRUST.get_or_init(|| {
+        std::env::var("TRUST_PROXY_HEADERS")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false)
+    })
+}
+
+/// Address of the client, used to throttle failed logins per IP. Without
+/// trusted proxy headers, a request relayed by a proxy only shows the proxy's
+/// address, which is marked as shared.
+pub fn client_addr(req: &HttpRequest) -> ClientAddr {
+    if trust_proxy_headers() {
+        if let Some(ip) = req.connection_info().realip_remote_addr() {
+            // `realip_remote_addr` may carry a port when it falls back to the peer
+            return ClientAddr {
+                ip: Some(
+                    ip.parse::<std::net::SocketAddr>()
+                        .map(|addr| addr.ip().to_string())
+                        .unwrap_or_else(|_| ip.to_string()),
+                ),
+                shared: false,
+            };
+        }
+    }
+    let headers = req.headers();
+    ClientAddr {
+        ip: req.peer_addr().map(|addr| addr.ip().to_string()),
+        shared: headers.contains_key(header::FORWARDED) || headers.contains_key("x-forwarded-for"),
+    }
+}
+
+/// Header selecting the organization a request acts in.
+pub const ORG_HEADER: &str = "X-Org-Id";
+
+/// The caller of a request, authenticated by a Bearer access token or API key.
+///
+/// Add it as a handler argument to require authentication; requests without
+/// valid credentials are rejected with 401.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub email: String,
+    /// The API key used, or `None` for a user session.
+    pub api_key_id: Option<Uuid>,
+    /// Scopes of the API key; sessions are not restricted.
+    scopes: Option<Vec<String>>,
+}
+
+impl AuthUser {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|granted| granted.iter().any(|s| s == scope))
+    }
+
+    /// Fail with 403 unless the caller may use `scope`.
+    pub fn require_scope(&self, scope: &str) -> Result<(), ApiError> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!("API key lacks the {} scope", scope)))
+        }
+    }
+
+    /// Fail with 403 for API keys, for actions reserved to interactive sessions.
+    pub fn require_session(&self) -> Result<(), ApiError> {
+        match self.api_key_id {
+            None => Ok(()),
+            Some(_) => Err(ApiError::Forbidden("not allowed with an API key".into())),
+        }
+    }
+}
+
+/// The only route that takes its token from the query string.
+const QUERY_TOKEN_ROUTE: &str = "/api/scans/{id}/events";
+
+/// Token from `Authorization: Bearer ...`, or from the `access_token` query
+/// parameter on the event stream, for `EventSource` which cannot set headers.
+/// Query strings end up in logs, so there only short-lived access tokens are
+/// accepted, never API keys.
+fn request_token(req: &HttpRequest) -> Option<String> {
+    let from_header = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer ").or_else(|| v.strip_prefix("bearer ")))
+        .map(|t| t.trim().to_string());
+    if from_header.is_some() {
+        return from_header;
+    }
+
+    if req.match_pattern().as_deref() != Some(QUERY_TOKEN_ROUTE) {
+        return None;
+    }
+    url::form_urlencoded::parse(req.query_string().as_bytes())
+        .find(|(k, _)| k == "access_token")
+        .map(|(_, v)| v.into_owned())
+        .filter(|t| !auth_svc::is_api_key(t))
+}
+
+async fn authenticate(pool: Option<web::Data<DbPool>>, token: String) -> Result<AuthUser, ApiError> {
+    if !auth_svc::is_api_key(&token) {
+        let claims = auth_svc::verify_access_token(&token)
+            .map_err(|_| ApiError::Unauthorized("invalid or expired token".into()))?;
+        return Ok(AuthUser {
+            id: claims.sub,
+            email: claims.email,
+            api_key_id: None,
+            scopes: None,
+        });
+    }
+
+    let pool = pool.ok_or_else(|| ApiError::Internal("database pool not configured".into()))?;
+    match auth_svc::authenticate_api_key(pool.get_ref(), &token).await {
+        Ok(key) => Ok(AuthUser {
+            id: key.user_id,
+            email: key.email,
+            api_key_id: Some(key.id),
+            scopes: Some(key.scopes),
+        }),
+        Err(AuthError::InvalidToken) => Err(ApiError::Unauthorized("invalid, expired or revoked API key".into())),
+        Err(e) => Err(ApiError::Internal(e.to_string())),
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let token = request_token(req);
+        let pool = req.app_data::<web::Data<DbPool>>().cloned();
+        Box::pin(async move {
+            match token {
+                None => Err(ApiError::Unauthorized("missing bearer token".into())),
+                Some(token) => authenticate(pool, token).await,
+            }
+        })
+    }
+}
+
+/// An authenticated caller acting within one organization, with their role there.
+///
+/// The organization comes from the `X-Org-Id` header (or `org_id` query
+/// parameter); without one, the caller's oldest membership is used. Callers
+/// that are not members of the selected organization are rejected with 403.
+#[derive(Debug, Clone)]
+pub struct OrgContext {
+    pub user: AuthUser,
+    pub org_id: Uuid,
+    pub role: Role,
+}
+
+impl OrgContext {
+    /// Fail with 403 unless the caller's role allows `permission`.
+    pub fn require(&self, permission: Permission) -> Result<(), ApiError> {
+        if self.role.can(permission) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!(
+                "the {} role is not allowed to do this",
+                self.role.as_str()
+            )))
+        }
+    }
+
+    /// Context for an organization named in the request path rather than the
+    /// `X-Org-Id` header. Organizations the caller is not a member of are
+    /// reported as not found.
+    pub async fn for_org(pool: &DbPool, user: AuthUser, org_id: Uuid) -> Result<OrgContext, ApiError> {
+        let membership = orgs_repo::get_membership(pool, org_id, user.id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound("organization not found".into()))?;
+        OrgContext::from_membership(pool, user, membership).await
+    }
+
+    /// Build the context, refusing members without two-factor authentication
+    /// when the organization requires it.
+    async fn from_membership(pool: &DbPool, user: AuthUser, membership: MembershipRow) -> Result<OrgContext, ApiError> {
+        if membership.require_mfa {
+            let enabled = mfa_repo::is_enabled(pool, user.id)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            if !enabled {
+                return Err(ApiError::Forbidden(
+                    "this organization requires two-factor authentication; enable it at /api/auth/mfa".into(),
+                ));
+            }
+        }
+        Ok(OrgContext {
+            org_id: membership.org_id,
+            role: membership.role(),
+            user,
+        })
+    }
+
+    /// Role check plus API key scope check.
+    pub fn require_with_scope(&self, permission: Permission, scope: &str) -> Result<(), ApiError> {
+        self.user.require_scope(scope)?;
+        self.require(permission)
+    }
+}
+
+fn requested_org(req: &HttpRequest) -> Result<Option<Uuid>, ApiError> {
+    let raw = req
+        .headers()
+        .get(ORG_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .or_else(|| {
+            url::form_urlencoded::parse(req.query_string().as_bytes())
+                .find(|(k, _)| k == "org_id")
+                .map(|(_, v)| v.into_owned())
+        });
+    match raw.as_deref() {
+        None | Some("") => Ok(None),
+        Some(v) => Uuid::parse_str(v)
+            .map(Some)
+            .map_err(|_| ApiError::BadRequest(format!("invalid {}", ORG_HEADER))),
+    }
+}
+
+async fn resolve_org(
+    pool: Option<web::Data<DbPool>>,
+    user: AuthUser,
+    requested: Option<Uuid>,
+) -> Result<OrgContext, ApiError> {
+    let pool = pool.ok_or_else(|| ApiError::Internal("database pool not configured".into()))?;
+    let membership = match requested {
+        Some(org_id) => orgs_repo::get_membership(pool.get_ref(), org_id, user.id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::Forbidden("not a member of this organization".into()))?,
+        None => orgs_repo::list_memberships(pool.get_ref(), user.id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ApiError::Forbidden("not a member of any organization".into()))?,
+    };
+
+    OrgContext::from_membership(pool.get_ref(), user, membership).await
+}
+
+impl FromRequest for OrgContext {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = AuthUser::from_request(req, payload);
+        let requested = requested_org(req);
+        let pool = req.app_data::<web::Data<DbPool>>().cloned();
+        Box::pin(async move {
+            let user = user.await?;
+            resolve_org(pool, user, requested?).await
+        })
+    }
+}
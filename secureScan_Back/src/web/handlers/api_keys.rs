@@ -0,0 +1,94 @@
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::api_keys_repo;
+use crate::domain::errors::ApiError;
+use crate::services::auth::{self as auth_svc, scopes};
+use crate::web::auth::AuthUser;
+
+/// Request body for POST /api/keys
+#[derive(Deserialize)]
+pub struct ApiKeyPayload {
+    pub name: String,
+    /// Granted scopes, e.g. `["scans:read", "scans:write"]`.
+    pub scopes: Vec<String>,
+    /// Omit for a key that never expires.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// POST /api/keys
+/// Create a personal API key. The key is only returned in this response.
+#[post("/api/keys")]
+pub async fn create_api_key(
+    pool: web::Data<DbPool>,
+    payload: web::Json<ApiKeyPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    // Keys cannot mint keys, otherwise a leaked key could outlive its revocation.
+    user.require_session()?;
+    let payload = payload.into_inner();
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name is required".into()));
+    }
+
+    let mut granted: Vec<String> = Vec::new();
+    for scope in payload.scopes.iter().map(|s| s.trim()) {
+        if !scopes::ALL.contains(&scope) {
+            return Err(ApiError::BadRequest(format!(
+                "unknown scope: {} (expected one of: {})",
+                scope,
+                scopes::ALL.join(", ")
+            )));
+        }
+        if !granted.iter().any(|g| g == scope) {
+            granted.push(scope.to_string());
+        }
+    }
+    if granted.is_empty() {
+        return Err(ApiError::BadRequest("at least one scope is required".into()));
+    }
+    if payload.expires_at.is_some_and(|e| e <= Utc::now()) {
+        return Err(ApiError::BadRequest("expires_at must be in the future".into()));
+    }
+
+    let (row, key) = auth_svc::create_api_key(pool.get_ref(), user.id, &name, &granted, payload.expires_at)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let mut body = json!(row);
+    body["key"] = json!(key);
+    Ok(HttpResponse::Created().json(body))
+}
+
+/// GET /api/keys
+/// The caller's API keys, without their secrets.
+#[get("/api/keys")]
+pub async fn list_api_keys(pool: web::Data<DbPool>, user: AuthUser) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let rows = api_keys_repo::list_api_keys(pool.get_ref(), user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// DELETE /api/keys/{id}
+/// Revoke an API key; it stops working immediately.
+#[delete("/api/keys/{id}")]
+pub async fn revoke_api_key(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    api_keys_repo::revoke_api_key(pool.get_ref(), path.into_inner(), user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("API key not found".into()))?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,9 +1,32 @@
-use actix_web::{post, web, HttpResponse, Result};
+use actix_web::cookie::{time, Cookie, SameSite};
+use actix_web::{get, http::header, post, web, HttpRequest, HttpResponse, Result};
 use serde::Deserialize;
 
 use crate::db::DbPool;
+use crate::domain::{login_attempts_repo, mfa_repo, users_repo};
 use crate::services::auth as auth_svc;
-use crate::services::auth::AuthError;
+use crate::services::mailer::{MailConfig, Mailer};
+use crate::services::auth::{AuthError, LoginStep, TokenPair};
+use crate::services::mfa;
+use crate::services::oidc::{self as oidc_svc, OidcClient, OidcError, SsoLogin};
+use crate::services::password_policy::PolicyViolation;
+use crate::web::auth::{client_addr, AuthUser};
+
+/// Query of /api/auth/oidc/login
+#[derive(Deserialize)]
+pub struct OidcLoginQuery {
+    /// Frontend path to return to after login.
+    pub redirect_to: Option<String>,
+}
+
+/// Query the identity provider redirects to /api/auth/oidc/callback with
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
 
 /// Request body for /api/auth/register
 #[derive(Deserialize)]
@@ -19,78 +42,514 @@ pub struct LoginPayload {
     pub password: String,
 }
 
+/// Request body for /api/auth/refresh and /api/auth/logout
+#[derive(Deserialize)]
+pub struct RefreshPayload {
+    pub refresh_token: String,
+}
+
+/// Request body for /api/auth/verify-email/resend and /api/auth/password-reset
+#[derive(Deserialize)]
+pub struct EmailPayload {
+    pub email: String,
+}
+
+/// Request body for /api/auth/verify-email
+#[derive(Deserialize)]
+pub struct TokenPayload {
+    pub token: String,
+}
+
+/// Request body for /api/auth/login/mfa
+#[derive(Deserialize)]
+pub struct MfaLoginPayload {
+    pub mfa_token: String,
+    /// TOTP code or recovery code.
+    pub code: String,
+}
+
+/// Request body for the /api/auth/mfa endpoints that need a code
+#[derive(Deserialize)]
+pub struct MfaCodePayload {
+    pub code: String,
+}
+
+/// Request body for /api/auth/password-reset/confirm
+#[derive(Deserialize)]
+pub struct ResetPasswordPayload {
+    pub token: String,
+    pub password: String,
+}
+
+/// `{ "code": "too_short", "min": 8, "message": "must be at least 8 characters" }`
+fn violation_json(v: &PolicyViolation) -> serde_json::Value {
+    let mut json = serde_json::to_value(v).unwrap_or_default();
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert("message".into(), v.message().into());
+    }
+    json
+}
+
+fn auth_error_response(e: AuthError) -> HttpResponse {
+    match e {
+        AuthError::InvalidCredentials => HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "invalid_credentials"
+        })),
+        AuthError::InvalidToken => HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "invalid_token"
+        })),
+        AuthError::EmailNotVerified => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "email_not_verified"
+        })),
+        AuthError::InvalidMfaCode => HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "invalid_mfa_code"
+        })),
+        AuthError::MfaAlreadyEnabled => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "mfa_already_enabled"
+        })),
+        AuthError::MfaNotEnabled => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "mfa_not_enabled"
+        })),
+        AuthError::WeakPassword(violations) => HttpResponse::UnprocessableEntity().json(serde_json::json!({
+            "error": "weak_password",
+            "violations": violations.iter().map(violation_json).collect::<Vec<_>>(),
+        })),
+        AuthError::TooManyAttempts { retry_after_secs } => HttpResponse::TooManyRequests()
+            .insert_header((header::RETRY_AFTER, retry_after_secs.to_string()))
+            .json(serde_json::json!({
+                "error": "too_many_attempts",
+                "retry_after": retry_after_secs
+            })),
+        AuthError::Db(_) | AuthError::Hash | AuthError::Signing | AuthError::SecretUnavailable => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "internal_error"
+            }))
+        }
+    }
+}
+
+fn session_json(id: uuid::Uuid, email: &str, tokens: TokenPair) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "email": email,
+        "access_token": tokens.access_token,
+        "token_type": tokens.token_type,
+        "expires_in": tokens.expires_in,
+        "refresh_token": tokens.refresh_token,
+    })
+}
+
 /// POST /api/auth/register
-/// Register a new user with email & password.
+/// Register a new user with email & password and mail them a verification link.
+/// Answers 202 whether or not the email was already registered, so registered
+/// emails cannot be probed; the owner of an existing account is mailed a notice.
 #[post("/api/auth/register")]
 pub async fn register(
     pool: web::Data<DbPool>,
+    mailer: web::Data<dyn Mailer>,
     payload: web::Json<RegisterPayload>,
 ) -> Result<HttpResponse> {
     let email = payload.email.trim();
-    let password = payload.password.as_str();
-
-    match auth_svc::register_user(&pool, email, password).await {
-        Ok(user_id) => Ok(HttpResponse::Ok().json(serde_json::json!({
-            "id": user_id,
+    match auth_svc::register_user(&pool, mailer.into_inner(), email, payload.password.as_str()).await {
+        Ok(()) => Ok(HttpResponse::Accepted().json(serde_json::json!({
             "email": email,
+            "email_verified": false,
         }))),
-        Err(e) => {
-            let resp = match e {
-                AuthError::EmailTaken => {
-                    HttpResponse::Conflict().json(serde_json::json!({
-                        "error": "email_taken"
-                    }))
-                }
-                AuthError::InvalidCredentials => {
-                    HttpResponse::Unauthorized().json(serde_json::json!({
-                        "error": "invalid_credentials"
-                    }))
-                }
-                AuthError::Db(_) | AuthError::Hash => {
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "internal_error"
-                    }))
-                }
-            };
-            Ok(resp)
-        }
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/verify-email
+/// Confirm an email address with the token from the verification email.
+#[post("/api/auth/verify-email")]
+pub async fn verify_email(
+    pool: web::Data<DbPool>,
+    payload: web::Json<TokenPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::verify_email(&pool, payload.token.trim()).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/verify-email/resend
+/// Send a new verification link. Always answers 202 so registered emails cannot be probed.
+#[post("/api/auth/verify-email/resend")]
+pub async fn resend_verification(
+    pool: web::Data<DbPool>,
+    mailer: web::Data<dyn Mailer>,
+    payload: web::Json<EmailPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::resend_verification(&pool, mailer.into_inner(), payload.email.trim()).await {
+        Ok(()) => Ok(HttpResponse::Accepted().finish()),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/password-reset
+/// Mail a password reset link. Always answers 202 so registered emails cannot be probed.
+#[post("/api/auth/password-reset")]
+pub async fn request_password_reset(
+    pool: web::Data<DbPool>,
+    mailer: web::Data<dyn Mailer>,
+    payload: web::Json<EmailPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::request_password_reset(&pool, mailer.into_inner(), payload.email.trim()).await {
+        Ok(()) => Ok(HttpResponse::Accepted().finish()),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/password-reset/confirm
+/// Set a new password with a reset token. Signs the user out everywhere.
+#[post("/api/auth/password-reset/confirm")]
+pub async fn confirm_password_reset(
+    pool: web::Data<DbPool>,
+    payload: web::Json<ResetPasswordPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::reset_password(&pool, payload.token.trim(), &payload.password).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(auth_error_response(e)),
     }
 }
 
 /// POST /api/auth/login
-/// Log in a user with email & password.
+/// Log in a user with email & password. Users with two-factor authentication get
+/// `{ "mfa_required": true, "mfa_token": … }` and finish at /api/auth/login/mfa.
 #[post("/api/auth/login")]
 pub async fn login(
+    req: HttpRequest,
     pool: web::Data<DbPool>,
     payload: web::Json<LoginPayload>,
 ) -> Result<HttpResponse> {
     let email = payload.email.trim();
     let password = payload.password.as_str();
+    let client = client_addr(&req);
 
-    match auth_svc::login_user(&pool, email, password).await {
-        Ok(user_id) => Ok(HttpResponse::Ok().json(serde_json::json!({
-            "id": user_id,
-            "email": email,
-        }))),
+    let user = match auth_svc::login_user(&pool, email, password, &client).await {
+        Ok(LoginStep::Complete(user)) => user,
+        Ok(LoginStep::MfaRequired { mfa_token, expires_in }) => {
+            return Ok(HttpResponse::Ok().json(serde_json::json!({
+                "mfa_required": true,
+                "mfa_token": mfa_token,
+                "expires_in": expires_in,
+            })))
+        }
+        Err(e) => return Ok(auth_error_response(e)),
+    };
+
+    match auth_svc::start_session(&pool, &user).await {
+        Ok(tokens) => Ok(HttpResponse::Ok().json(session_json(user.id, &user.email, tokens))),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/login/mfa
+/// Second login step: a TOTP or recovery code for the `mfa_token` from /api/auth/login.
+#[post("/api/auth/login/mfa")]
+pub async fn login_mfa(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    payload: web::Json<MfaLoginPayload>,
+) -> Result<HttpResponse> {
+    let client = client_addr(&req);
+    let user = match auth_svc::complete_mfa_login(&pool, payload.mfa_token.trim(), &payload.code, &client).await {
+        Ok(user) => user,
+        Err(e) => return Ok(auth_error_response(e)),
+    };
+
+    match auth_svc::start_session(&pool, &user).await {
+        Ok(tokens) => Ok(HttpResponse::Ok().json(session_json(user.id, &user.email, tokens))),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+fn sso_not_configured() -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "sso_not_configured"
+    }))
+}
+
+fn found(location: String) -> HttpResponse {
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+/// Cookie tying an SSO login to the browser that started it.
+const OIDC_BINDING_COOKIE: &str = "securascan_oidc";
+
+fn oidc_binding_cookie(oidc: &OidcClient, value: String) -> Cookie<'static> {
+    Cookie::build(OIDC_BINDING_COOKIE, value)
+        .path("/api/auth/oidc")
+        .http_only(true)
+        // Lax still sends it on the provider's top-level redirect back
+        .same_site(SameSite::Lax)
+        .secure(oidc.config.redirect_uri.starts_with("https://"))
+        .max_age(time::Duration::seconds(oidc_svc::STATE_TTL_SECS))
+        .finish()
+}
+
+/// Redirect at the end of an SSO callback, dropping the binding cookie.
+fn sso_redirect(oidc: &OidcClient, location: String) -> HttpResponse {
+    let mut res = found(location);
+    let _ = res.add_removal_cookie(&oidc_binding_cookie(oidc, String::new()));
+    res
+}
+
+/// GET /api/auth/oidc
+/// Whether single sign-on is available, for the login page.
+#[get("/api/auth/oidc")]
+pub async fn oidc_config(oidc: Option<web::Data<OidcClient>>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "enabled": oidc.is_some(),
+        "provider_name": oidc.as_ref().map(|o| o.config.provider_name.clone()),
+    })))
+}
+
+/// GET /api/auth/oidc/login
+/// Redirect the browser to the identity provider (authorization code flow with PKCE).
+#[get("/api/auth/oidc/login")]
+pub async fn oidc_login(
+    pool: web::Data<DbPool>,
+    oidc: Option<web::Data<OidcClient>>,
+    query: web::Query<OidcLoginQuery>,
+) -> Result<HttpResponse> {
+    let Some(oidc) = oidc else {
+        return Ok(sso_not_configured());
+    };
+    match oidc.authorization_url(&pool, query.redirect_to.as_deref()).await {
+        Ok(start) => {
+            let mut res = found(start.url);
+            let _ = res.add_cookie(&oidc_binding_cookie(&oidc, start.browser_binding));
+            Ok(res)
+        }
+        Err(e) => {
+            tracing::error!("could not start SSO login: {}", e);
+            Ok(found(MailConfig::global().app_url(&format!("/login?sso_error={}", e.code()))))
+        }
+    }
+}
+
+/// GET /api/auth/oidc/callback
+/// Where the identity provider sends the browser back. Redirects to the frontend's
+/// `/sso/callback` with the session (or an MFA challenge) in the URL fragment,
+/// or to `/login?sso_error=…`.
+#[get("/api/auth/oidc/callback")]
+pub async fn oidc_callback(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    oidc: Option<web::Data<OidcClient>>,
+    query: web::Query<OidcCallbackQuery>,
+) -> Result<HttpResponse> {
+    let Some(oidc) = oidc else {
+        return Ok(sso_not_configured());
+    };
+    let app = MailConfig::global();
+    let client = client_addr(&req);
+    let binding = req.cookie(OIDC_BINDING_COOKIE);
+
+    let result = match (&query.error, &query.code, &query.state) {
+        (Some(error), _, _) => Err(OidcError::Denied(format!(
+            "{} {}",
+            error,
+            query.error_description.as_deref().unwrap_or_default()
+        ))),
+        (None, Some(code), Some(state)) => match &binding {
+            Some(binding) => {
+                oidc.complete_login(&pool, code, state, binding.value(), client.ip.as_deref())
+                    .await
+            }
+            // A callback opened in another browser than the one that started the login
+            None => Err(OidcError::InvalidState),
+        },
+        _ => Err(OidcError::InvalidState),
+    };
+    let SsoLogin { step, redirect_to } = match result {
+        Ok(sso) => sso,
         Err(e) => {
-            let resp = match e {
-                AuthError::InvalidCredentials => {
-                    HttpResponse::Unauthorized().json(serde_json::json!({
-                        "error": "invalid_credentials"
-                    }))
-                }
-                AuthError::EmailTaken => {
-                    HttpResponse::BadRequest().json(serde_json::json!({
-                        "error": "unexpected_email_taken"
-                    }))
-                }
-                AuthError::Db(_) | AuthError::Hash => {
-                    HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "internal_error"
-                    }))
-                }
-            };
-            Ok(resp)
+            tracing::warn!("SSO login failed: {}", e);
+            return Ok(sso_redirect(&oidc, app.app_url(&format!("/login?sso_error={}", e.code()))));
         }
+    };
+
+    // The fragment is not sent to servers, keeping tokens out of access logs.
+    let mut fragment = url::form_urlencoded::Serializer::new(String::new());
+    match step {
+        LoginStep::Complete(user) => match auth_svc::start_session(&pool, &user).await {
+            Ok(tokens) => {
+                fragment
+                    .append_pair("access_token", &tokens.access_token)
+                    .append_pair("refresh_token", &tokens.refresh_token)
+                    .append_pair("expires_in", &tokens.expires_in.to_string());
+            }
+            Err(e) => {
+                tracing::error!("could not start session after SSO login: {}", e);
+                return Ok(sso_redirect(&oidc, app.app_url("/login?sso_error=internal_error")));
+            }
+        },
+        LoginStep::MfaRequired { mfa_token, expires_in } => {
+            fragment
+                .append_pair("mfa_token", &mfa_token)
+                .append_pair("expires_in", &expires_in.to_string());
+        }
+    }
+    if let Some(path) = redirect_to {
+        fragment.append_pair("redirect_to", &path);
+    }
+    Ok(sso_redirect(&oidc, app.app_url(&format!("/sso/callback#{}", fragment.finish()))))
+}
+
+/// POST /api/auth/refresh
+/// Exchange a refresh token for a new access token and a new refresh token.
+/// The presented refresh token is invalidated.
+#[post("/api/auth/refresh")]
+pub async fn refresh(
+    pool: web::Data<DbPool>,
+    payload: web::Json<RefreshPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::refresh_session(&pool, payload.refresh_token.trim()).await {
+        Ok((user, tokens)) => Ok(HttpResponse::Ok().json(session_json(user.id, &user.email, tokens))),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/logout
+/// Revoke the session the refresh token belongs to.
+#[post("/api/auth/logout")]
+pub async fn logout(
+    pool: web::Data<DbPool>,
+    payload: web::Json<RefreshPayload>,
+) -> Result<HttpResponse> {
+    match auth_svc::end_session(&pool, payload.refresh_token.trim()).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// GET /api/auth/me
+/// The user the access token was issued to.
+#[get("/api/auth/me")]
+pub async fn me(user: AuthUser) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": user.id,
+        "email": user.email,
+    })))
+}
+
+/// Two-factor management needs a user session, not an API key.
+fn session_only(user: &AuthUser) -> Option<HttpResponse> {
+    user.api_key_id.map(|_| {
+        HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "session_required"
+        }))
+    })
+}
+
+/// GET /api/auth/login-attempts
+/// The 50 most recent sign-in attempts on the caller's account, failed ones included.
+#[get("/api/auth/login-attempts")]
+pub async fn login_attempts(pool: web::Data<DbPool>, user: AuthUser) -> Result<HttpResponse> {
+    if let Some(resp) = session_only(&user) {
+        return Ok(resp);
+    }
+    match login_attempts_repo::list_for_user(&pool, user.id, 50).await {
+        Ok(rows) => Ok(HttpResponse::Ok().json(rows)),
+        Err(e) => Ok(auth_error_response(AuthError::Db(e))),
+    }
+}
+
+/// GET /api/auth/mfa
+/// Whether two-factor authentication is on, and how many recovery codes are left.
+#[get("/api/auth/mfa")]
+pub async fn mfa_status(pool: web::Data<DbPool>, user: AuthUser) -> Result<HttpResponse> {
+    let state = mfa_repo::get_mfa(&pool, user.id).await;
+    let remaining = mfa_repo::count_unused_recovery_codes(&pool, user.id).await;
+    match (state, remaining) {
+        (Ok(state), Ok(remaining)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "enabled": state.as_ref().is_some_and(|m| m.enabled_at.is_some()),
+            "pending": state.as_ref().is_some_and(|m| m.enabled_at.is_none()),
+            "enabled_at": state.and_then(|m| m.enabled_at),
+            "recovery_codes_remaining": remaining,
+        }))),
+        (Err(e), _) | (_, Err(e)) => Ok(auth_error_response(AuthError::Db(e))),
+    }
+}
+
+/// POST /api/auth/mfa/totp/enroll
+/// Start TOTP enrollment; returns the secret and an `otpauth://` provisioning URI.
+#[post("/api/auth/mfa/totp/enroll")]
+pub async fn mfa_enroll(pool: web::Data<DbPool>, user: AuthUser) -> Result<HttpResponse> {
+    if let Some(resp) = session_only(&user) {
+        return Ok(resp);
+    }
+    match mfa::enroll(&pool, user.id, &user.email).await {
+        Ok(enrollment) => Ok(HttpResponse::Ok().json(enrollment)),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/mfa/totp/confirm
+/// Turn TOTP on with a first code. Returns one-time recovery codes and a new
+/// session; every other session is signed out.
+#[post("/api/auth/mfa/totp/confirm")]
+pub async fn mfa_confirm(
+    pool: web::Data<DbPool>,
+    payload: web::Json<MfaCodePayload>,
+    user: AuthUser,
+) -> Result<HttpResponse> {
+    if let Some(resp) = session_only(&user) {
+        return Ok(resp);
+    }
+    let codes = match mfa::confirm(&pool, user.id, &payload.code).await {
+        Ok(codes) => codes,
+        Err(e) => return Ok(auth_error_response(e)),
+    };
+
+    let session = match users_repo::find_by_id(pool.get_ref(), user.id).await {
+        Ok(Some(u)) => auth_svc::start_session(&pool, &u).await.map(|t| session_json(u.id, &u.email, t)),
+        Ok(None) => Err(AuthError::InvalidToken),
+        Err(e) => Err(AuthError::Db(e)),
+    };
+    match session {
+        Ok(mut body) => {
+            body["recovery_codes"] = serde_json::json!(codes);
+            Ok(HttpResponse::Ok().json(body))
+        }
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/mfa/recovery-codes
+/// Replace all recovery codes; needs a current TOTP code.
+#[post("/api/auth/mfa/recovery-codes")]
+pub async fn mfa_recovery_codes(
+    pool: web::Data<DbPool>,
+    payload: web::Json<MfaCodePayload>,
+    user: AuthUser,
+) -> Result<HttpResponse> {
+    if let Some(resp) = session_only(&user) {
+        return Ok(resp);
+    }
+    match mfa::regenerate_recovery_codes(&pool, user.id, &payload.code).await {
+        Ok(codes) => Ok(HttpResponse::Ok().json(serde_json::json!({ "recovery_codes": codes }))),
+        Err(e) => Ok(auth_error_response(e)),
+    }
+}
+
+/// POST /api/auth/mfa/disable
+/// Turn two-factor authentication off with a TOTP or recovery code.
+#[post("/api/auth/mfa/disable")]
+pub async fn mfa_disable(
+    pool: web::Data<DbPool>,
+    payload: web::Json<MfaCodePayload>,
+    user: AuthUser,
+) -> Result<HttpResponse> {
+    if let Some(resp) = session_only(&user) {
+        return Ok(resp);
+    }
+    match mfa::disable(&pool, user.id, &payload.code).await {
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Ok(auth_error_response(e)),
     }
 }
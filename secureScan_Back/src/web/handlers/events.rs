@@ -0,0 +1,93 @@
+use actix_web::web::Bytes;
+use actix_web::{get, web, HttpResponse, Responder};
+use futures_util::stream;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::scans_repo;
+use crate::jobs::events::{EventHub, ScanEvent, ScanEventMessage};
+use crate::services::auth::scopes;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
+
+/// Interval between SSE keep-alive comments so proxies don't close idle streams.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Format one event as an SSE frame.
+fn sse_frame(event: &ScanEvent) -> Bytes {
+    let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event.name(), data))
+}
+
+struct StreamState {
+    rx: broadcast::Receiver<ScanEventMessage>,
+    scan_id: Uuid,
+    pending: Option<ScanEvent>,
+    done: bool,
+}
+
+/// GET /api/scans/{id}/events
+/// Streams live scan updates as Server-Sent Events. The first event is always
+/// the current status; the stream ends after the scan completes or fails.
+/// `EventSource` cannot send headers, so the token may be passed as `?access_token=`.
+#[get("/api/scans/{id}/events")]
+pub async fn scan_events(
+    pool: web::Data<DbPool>,
+    hub: web::Data<EventHub>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let id = path.into_inner();
+
+    // Subscribe before reading the current status so no transition is missed.
+    let rx = hub.subscribe();
+
+    let scan = scans_repo::get_org_scan(pool.get_ref(), id, ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("scan not found".into()))?;
+
+    let state = StreamState {
+        rx,
+        scan_id: id,
+        pending: Some(ScanEvent::Status { status: scan.status.clone() }),
+        done: false,
+    };
+
+    let body = stream::unfold(state, |mut st| async move {
+        if st.done {
+            return None;
+        }
+
+        if let Some(event) = st.pending.take() {
+            st.done = event.is_terminal();
+            return Some((Ok::<_, actix_web::Error>(sse_frame(&event)), st));
+        }
+
+        loop {
+            match tokio::time::timeout(KEEP_ALIVE, st.rx.recv()).await {
+                Err(_) => return Some((Ok(Bytes::from_static(b": keep-alive\n\n")), st)),
+                Ok(Ok(msg)) if msg.scan_id == st.scan_id => {
+                    st.done = msg.event.is_terminal();
+                    return Some((Ok(sse_frame(&msg.event)), st));
+                }
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(n))) => {
+                    tracing::warn!("SSE subscriber for {} lagged by {} events", st.scan_id, n);
+                    continue;
+                }
+                Ok(Err(RecvError::Closed)) => return None,
+            }
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
@@ -0,0 +1,210 @@
+use actix_web::http::header::ContentDisposition;
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::StreamExt;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::orgs_repo::Permission;
+use crate::domain::scans_repo::{self, FindingSearchRow, ScanListRow};
+use crate::domain::suppressions_repo;
+use crate::services::auth::scopes;
+use crate::services::exports::{self, csv_record, ExportConfig, Format};
+use crate::web::auth::OrgContext;
+use crate::web::handlers::findings::FindingsQuery;
+use crate::web::handlers::scans::{list_row_json, summary_row_json, ListScansQuery};
+
+const SCAN_COLUMNS: [&str; 17] = [
+    "id",
+    "url",
+    "status",
+    "created_at",
+    "completed_at",
+    "target_id",
+    "schedule_id",
+    "owner_id",
+    "grade",
+    "risk_score",
+    "critical",
+    "high",
+    "medium",
+    "low",
+    "info",
+    "total",
+    "org_id",
+];
+
+const FINDING_COLUMNS: [&str; 13] = [
+    "id",
+    "scan_id",
+    "target_id",
+    "target_url",
+    "host",
+    "rule_id",
+    "severity",
+    "status",
+    "title",
+    "location",
+    "evidence",
+    "fingerprint",
+    "created_at",
+];
+
+fn parse_format(raw: &str) -> Result<Format, ApiError> {
+    Format::parse(raw).ok_or_else(|| ApiError::BadRequest(format!("unsupported export format: {}", raw)))
+}
+
+/// `limit` is an optional cap on exported rows, not a page size.
+fn export_limit(limit: Option<i64>) -> Result<Option<i64>, ApiError> {
+    match limit {
+        Some(l) if l < 1 => Err(ApiError::BadRequest("limit must be positive".into())),
+        other => Ok(other),
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn scan_csv(row: ScanListRow) -> String {
+    let counts = row.counts();
+    csv_record([
+        row.id.to_string(),
+        row.url,
+        row.status,
+        timestamp(row.created_at),
+        opt(row.completed_at.map(timestamp)),
+        opt(row.target_id),
+        opt(row.schedule_id),
+        opt(row.owner_id),
+        opt(row.grade),
+        opt(row.risk_score),
+        counts.critical.to_string(),
+        counts.high.to_string(),
+        counts.medium.to_string(),
+        counts.low.to_string(),
+        counts.info.to_string(),
+        counts.total.to_string(),
+        opt(row.org_id),
+    ])
+}
+
+fn finding_csv(row: FindingSearchRow) -> String {
+    csv_record([
+        row.id.to_string(),
+        row.scan_id.to_string(),
+        opt(row.target_id),
+        opt(row.target_url),
+        opt(row.host),
+        row.rule_id,
+        row.severity,
+        row.status,
+        row.title,
+        row.location,
+        opt(row.evidence),
+        row.fingerprint,
+        timestamp(row.created_at),
+    ])
+}
+
+fn json_line<T: serde::Serialize>(value: &T) -> String {
+    let mut line = serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string());
+    line.push('\n');
+    line
+}
+
+/// 503 while the maximum number of exports are running.
+fn busy() -> HttpResponse {
+    HttpResponse::ServiceUnavailable()
+        .insert_header(("Retry-After", "30"))
+        .json(serde_json::json!({ "error": "too_many_exports" }))
+}
+
+fn attachment(kind: &str, format: Format) -> ContentDisposition {
+    ContentDisposition::attachment(format!("{}-{}.{}", kind, Utc::now().format("%Y%m%dT%H%M%SZ"), format.extension()))
+}
+
+/// GET /api/exports/scans.{csv|jsonl}
+/// Every scan matching the GET /api/scans filters, streamed. CSV rows are always
+/// summaries; JSON Lines rows carry findings unless `summary=true`. `cursor`
+/// resumes after a listing page and `limit` caps the number of rows.
+#[get("/api/exports/scans.{format}")]
+pub async fn export_scans(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<ListScansQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let format = parse_format(&path.into_inner())?;
+    let mut filter = query.to_filter(ctx.org_id)?;
+    filter.limit = export_limit(query.limit)?;
+    filter.include_findings = format == Format::JsonLines && !query.summary;
+    let sql = scans_repo::list_scans_query(&filter);
+    let Some(permit) = ExportConfig::global().try_start() else {
+        return Ok(busy());
+    };
+
+    let body = match format {
+        Format::Csv => exports::stream_rows(permit, pool.get_ref().clone(), sql, Some(csv_record(SCAN_COLUMNS)), scan_csv)
+            .boxed(),
+        Format::JsonLines if query.summary => {
+            exports::stream_rows(permit, pool.get_ref().clone(), sql, None, |row: ScanListRow| {
+                json_line(&summary_row_json(row))
+            })
+            .boxed()
+        }
+        Format::JsonLines => {
+            let suppressions = suppressions_repo::list_active(pool.get_ref(), Some(ctx.org_id))
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            exports::stream_rows(permit, pool.get_ref().clone(), sql, None, move |row: ScanListRow| {
+                json_line(&list_row_json(row, &suppressions))
+            })
+            .boxed()
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(attachment("scans", format))
+        .streaming(body))
+}
+
+/// GET /api/exports/findings.{csv|jsonl}
+/// Every finding matching the GET /api/findings filters, newest first, streamed.
+/// `limit` caps the number of rows; `cursor` is ignored.
+#[get("/api/exports/findings.{format}")]
+pub async fn export_findings(
+    pool: web::Data<DbPool>,
+    path: web::Path<String>,
+    query: web::Query<FindingsQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let format = parse_format(&path.into_inner())?;
+    let search = query.to_search(ctx.org_id)?;
+    let sql = scans_repo::search_findings_query(&search, None, export_limit(query.limit)?);
+    let Some(permit) = ExportConfig::global().try_start() else {
+        return Ok(busy());
+    };
+
+    let body = match format {
+        Format::Csv => {
+            exports::stream_rows(permit, pool.get_ref().clone(), sql, Some(csv_record(FINDING_COLUMNS)), finding_csv)
+                .boxed()
+        }
+        Format::JsonLines => {
+            exports::stream_rows(permit, pool.get_ref().clone(), sql, None, |row: FindingSearchRow| json_line(&row)).boxed()
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(attachment("findings", format))
+        .streaming(body))
+}
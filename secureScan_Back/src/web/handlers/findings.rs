@@ -0,0 +1,176 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Map, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::cursor::Cursor;
+use crate::domain::errors::ApiError;
+use crate::domain::findings::Severity;
+use crate::domain::scans_repo::{self, FindingSearch};
+use crate::services::auth::scopes;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+const STATUSES: [&str; 3] = ["open", "fixed", "suppressed"];
+
+/// Query string for GET /api/findings. List parameters are comma-separated.
+#[derive(Deserialize)]
+pub struct FindingsQuery {
+    pub rule_id: Option<String>,
+    pub severity: Option<String>,
+    pub host: Option<String>,
+    /// `open`, `fixed`, `suppressed`.
+    pub status: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Free-text search over title and evidence.
+    pub q: Option<String>,
+    /// `latest` (default): one row per problem per target; `all`: every occurrence.
+    pub scope: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+fn split_list(raw: &Option<String>) -> Vec<String> {
+    raw.as_deref()
+        .map(|s| {
+            s.split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl FindingsQuery {
+    pub fn to_search(&self, org_id: Uuid) -> Result<FindingSearch, ApiError> {
+        let severities = split_list(&self.severity)
+            .iter()
+            .map(|s| {
+                Severity::parse(s)
+                    .map(|sev| sev.as_str().to_string())
+                    .ok_or_else(|| ApiError::BadRequest(format!("unknown severity: {}", s)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let statuses: Vec<String> = split_list(&self.status)
+            .into_iter()
+            .map(|s| s.to_ascii_lowercase())
+            .collect();
+        if let Some(bad) = statuses.iter().find(|s| !STATUSES.contains(&s.as_str())) {
+            return Err(ApiError::BadRequest(format!("unknown status: {}", bad)));
+        }
+
+        let latest_only = match self.scope.as_deref() {
+            None | Some("latest") => true,
+            Some("all") => false,
+            Some(other) => return Err(ApiError::BadRequest(format!("unknown scope: {}", other))),
+        };
+
+        Ok(FindingSearch {
+            org_id: Some(org_id),
+            rule_ids: split_list(&self.rule_id),
+            severities,
+            hosts: split_list(&self.host)
+                .into_iter()
+                .map(|h| h.to_ascii_lowercase())
+                .collect(),
+            statuses,
+            from: self.from,
+            to: self.to,
+            text: self.q.as_ref().map(|q| q.trim().to_string()),
+            latest_only,
+        })
+    }
+}
+
+/// Shape aggregate rows into `{ total, by_severity, by_status, top_rules, top_hosts }`.
+fn aggregations_json(rows: Vec<scans_repo::FindingAggregateRow>) -> JsonValue {
+    let mut total = 0;
+    let mut by_severity = Map::new();
+    let mut by_status = Map::new();
+    for s in Severity::ALL {
+        by_severity.insert(s.as_str().to_string(), json!(0));
+    }
+    for s in STATUSES {
+        by_status.insert(s.to_string(), json!(0));
+    }
+    let mut top_rules = Vec::new();
+    let mut top_hosts = Vec::new();
+
+    for row in rows {
+        let value = row.value.unwrap_or_default();
+        match row.dimension.as_str() {
+            "total" => total = row.count,
+            "severity" => {
+                by_severity.insert(value, json!(row.count));
+            }
+            "status" => {
+                by_status.insert(value, json!(row.count));
+            }
+            "rule_id" => top_rules.push(json!({ "rule_id": value, "count": row.count })),
+            "host" => top_hosts.push(json!({ "host": value, "count": row.count })),
+            _ => {}
+        }
+    }
+
+    json!({
+        "total": total,
+        "by_severity": by_severity,
+        "by_status": by_status,
+        "top_rules": top_rules,
+        "top_hosts": top_hosts,
+    })
+}
+
+/// GET /api/findings
+/// Search findings across the organization's scans with filters, cursor pagination and
+/// aggregation counts over the whole filtered set.
+#[get("/api/findings")]
+pub async fn search_findings(
+    pool: web::Data<DbPool>,
+    query: web::Query<FindingsQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let search = query.to_search(ctx.org_id)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let after = match query.cursor.as_deref() {
+        None | Some("") => None,
+        Some(raw) => {
+            let cursor = Cursor::decode(raw).ok_or_else(|| ApiError::BadRequest("invalid cursor".into()))?;
+            let created_at = DateTime::parse_from_rfc3339(&cursor.key)
+                .map_err(|_| ApiError::BadRequest("invalid cursor".into()))?
+                .with_timezone(&Utc);
+            Some((created_at, cursor.id))
+        }
+    };
+
+    // Fetch one extra row to know whether another page exists.
+    let mut items = scans_repo::search_findings(pool.get_ref(), &search, after, limit + 1)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|last| {
+            Cursor::new(last.created_at.to_rfc3339_opts(SecondsFormat::Nanos, true), last.id).encode()
+        })
+    } else {
+        None
+    };
+
+    let aggregations = scans_repo::aggregate_findings(pool.get_ref(), &search)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "items": items,
+        "next_cursor": next_cursor,
+        "aggregations": aggregations_json(aggregations),
+    })))
+}
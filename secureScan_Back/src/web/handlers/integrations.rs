@@ -0,0 +1,234 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::orgs_repo::Permission;
+use crate::domain::webhook_integrations_repo::{self, IntegrationRow};
+use crate::services::auth::scopes;
+use crate::services::secret_box::SecretBox;
+use crate::services::webhooks::{self, provider};
+use crate::web::auth::OrgContext;
+
+/// Shortest secret accepted when the caller brings their own.
+const MIN_SECRET_LEN: usize = 16;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request body for POST /api/integrations
+#[derive(Deserialize)]
+pub struct CreateIntegrationPayload {
+    /// `github`, `gitlab` or `generic`.
+    pub provider: String,
+    pub name: String,
+    /// Omit to have a secret generated, e.g. `whsec_…`.
+    pub secret: Option<String>,
+}
+
+/// Request body for PUT /api/integrations/{id}
+#[derive(Deserialize)]
+pub struct UpdateIntegrationPayload {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Request body for POST /api/integrations/{id}/rotate-secret
+#[derive(Deserialize, Default)]
+pub struct RotateSecretPayload {
+    pub secret: Option<String>,
+}
+
+/// Request body for PUT /api/integrations/{id}/report-token
+#[derive(Deserialize)]
+pub struct ReportTokenPayload {
+    pub token: String,
+}
+
+fn integration_json(row: &IntegrationRow, secret: Option<&str>) -> serde_json::Value {
+    let mut body = json!(row);
+    body["webhook_path"] = json!(format!("/api/webhooks/{}", row.id));
+    body["has_report_token"] = json!(row.report_token.is_some());
+    if let Some(secret) = secret {
+        body["secret"] = json!(secret);
+    }
+    body
+}
+
+fn clean_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name is required".into()));
+    }
+    Ok(name.to_string())
+}
+
+/// The caller's secret if acceptable, or a generated one.
+fn choose_secret(secret: Option<String>) -> Result<String, ApiError> {
+    match secret.map(|s| s.trim().to_string()) {
+        Some(s) if s.chars().count() < MIN_SECRET_LEN => Err(ApiError::BadRequest(format!(
+            "secret must be at least {} characters",
+            MIN_SECRET_LEN
+        ))),
+        Some(s) => Ok(s),
+        None => Ok(webhooks::generate_secret()),
+    }
+}
+
+/// POST /api/integrations
+/// Create an inbound webhook endpoint. The secret is only returned here and when rotated.
+#[post("/api/integrations")]
+pub async fn create_integration(
+    pool: web::Data<DbPool>,
+    payload: web::Json<CreateIntegrationPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let payload = payload.into_inner();
+
+    let provider = payload.provider.trim().to_ascii_lowercase();
+    if !provider::ALL.contains(&provider.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "provider must be one of: {}",
+            provider::ALL.join(", ")
+        )));
+    }
+    let name = clean_name(&payload.name)?;
+    let secret = choose_secret(payload.secret)?;
+
+    let row = webhook_integrations_repo::create_integration(pool.get_ref(), ctx.org_id, &provider, &name, &secret, ctx.user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(integration_json(&row, Some(&secret))))
+}
+
+/// GET /api/integrations
+/// The organization's integrations, without their secrets.
+#[get("/api/integrations")]
+pub async fn list_integrations(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let rows = webhook_integrations_repo::list_integrations(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let body: Vec<_> = rows.iter().map(|r| integration_json(r, None)).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// GET /api/integrations/{id}
+#[get("/api/integrations/{id}")]
+pub async fn get_integration(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let row = webhook_integrations_repo::get_integration(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    Ok(HttpResponse::Ok().json(integration_json(&row, None)))
+}
+
+/// PUT /api/integrations/{id}
+/// Rename, disable or re-enable an integration. Disabled integrations answer 404 to deliveries.
+#[put("/api/integrations/{id}")]
+pub async fn update_integration(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateIntegrationPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let name = clean_name(&payload.name)?;
+    let row = webhook_integrations_repo::update_integration(pool.get_ref(), path.into_inner(), ctx.org_id, &name, payload.enabled)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    Ok(HttpResponse::Ok().json(integration_json(&row, None)))
+}
+
+/// POST /api/integrations/{id}/rotate-secret
+/// Replace the secret; deliveries signed with the old one are rejected from now on.
+#[post("/api/integrations/{id}/rotate-secret")]
+pub async fn rotate_integration_secret(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: Option<web::Json<RotateSecretPayload>>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let secret = choose_secret(payload.map(|p| p.into_inner()).unwrap_or_default().secret)?;
+    let row = webhook_integrations_repo::set_secret(pool.get_ref(), path.into_inner(), ctx.org_id, &secret)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    Ok(HttpResponse::Ok().json(integration_json(&row, Some(&secret))))
+}
+
+/// PUT /api/integrations/{id}/report-token
+/// Store the GitHub token that posts commit statuses for this integration's scans.
+/// It needs *Commit statuses: write* on the mapped repositories and is never returned.
+#[put("/api/integrations/{id}/report-token")]
+pub async fn set_report_token(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<ReportTokenPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let id = path.into_inner();
+    let token = payload.token.trim();
+    if token.is_empty() {
+        return Err(ApiError::BadRequest("token is required".into()));
+    }
+    let integration = webhook_integrations_repo::get_integration(pool.get_ref(), id, ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    if integration.provider != provider::GITHUB {
+        return Err(ApiError::BadRequest("only GitHub integrations report on commits".into()));
+    }
+    let sealed = SecretBox::global().seal(token);
+    let row = webhook_integrations_repo::set_report_token(pool.get_ref(), id, ctx.org_id, Some(&sealed))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    Ok(HttpResponse::Ok().json(integration_json(&row, None)))
+}
+
+/// DELETE /api/integrations/{id}/report-token
+/// Stop reporting this integration's scans on commits.
+#[delete("/api/integrations/{id}/report-token")]
+pub async fn delete_report_token(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let row = webhook_integrations_repo::set_report_token(pool.get_ref(), path.into_inner(), ctx.org_id, None)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("integration not found".into()))?;
+    Ok(HttpResponse::Ok().json(integration_json(&row, None)))
+}
+
+/// DELETE /api/integrations/{id}
+#[delete("/api/integrations/{id}")]
+pub async fn delete_integration(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let deleted = webhook_integrations_repo::delete_integration(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("integration not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
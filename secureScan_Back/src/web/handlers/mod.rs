@@ -1,4 +1,17 @@
 pub mod health;
 pub mod scans;
+pub mod events;
+pub mod exports;
+pub mod findings;
+pub mod integrations;
+pub mod notification_channels;
+pub mod outbound_webhooks;
+pub mod repository_targets;
+pub mod schedules;
+pub mod suppressions;
+pub mod targets;
 pub mod webhook;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod api_keys;
+pub mod orgs;
+pub mod reports;
@@ -0,0 +1,200 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::findings::Severity;
+use crate::domain::notification_channels_repo::{self, NotificationChannelInput, NotificationChannelRow};
+use crate::domain::orgs_repo::Permission;
+use crate::services::auth::scopes;
+use crate::services::notifications::{self, kind};
+use crate::web::auth::OrgContext;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request body for POST /api/notification-channels and PUT /api/notification-channels/{id}
+#[derive(Deserialize)]
+pub struct NotificationChannelPayload {
+    /// `slack` or `teams`.
+    pub kind: String,
+    pub name: String,
+    /// Incoming webhook URL; required on create, omit on update to keep the current one.
+    pub webhook_url: Option<String>,
+    /// Notify when a completed scan has open findings at or above this severity (default `critical`).
+    pub min_severity: Option<String>,
+    #[serde(default)]
+    pub only_new: bool,
+    /// Restrict to scans of these targets; empty or omitted means all targets.
+    #[serde(default)]
+    pub target_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub notify_on_failure: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// The channel with its webhook URL reduced to scheme and host.
+fn channel_json(row: &NotificationChannelRow) -> serde_json::Value {
+    let mut body = json!(row);
+    body["webhook_host"] = json!(Url::parse(&row.webhook_url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| format!("{}://{}", u.scheme(), h))));
+    body
+}
+
+fn invalid_webhook_url(kind: &str) -> ApiError {
+    ApiError::BadRequest(format!("webhook_url must be an HTTPS {} incoming webhook URL", kind))
+}
+
+fn to_input(payload: NotificationChannelPayload, creating: bool) -> Result<NotificationChannelInput, ApiError> {
+    let kind = payload.kind.trim().to_ascii_lowercase();
+    if !kind::ALL.contains(&kind.as_str()) {
+        return Err(ApiError::BadRequest(format!("kind must be one of: {}", kind::ALL.join(", "))));
+    }
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name is required".into()));
+    }
+
+    let webhook_url = match payload.webhook_url.map(|u| u.trim().to_string()) {
+        Some(url) => match Url::parse(&url) {
+            Ok(u) if notifications::is_webhook_url(&kind, &u) => Some(url),
+            _ => return Err(invalid_webhook_url(&kind)),
+        },
+        None if creating => return Err(ApiError::BadRequest("webhook_url is required".into())),
+        None => None,
+    };
+
+    let min_severity = match payload.min_severity {
+        Some(s) => Severity::parse(&s)
+            .ok_or_else(|| ApiError::BadRequest("min_severity must be one of: critical, high, medium, low, info".into()))?,
+        None => Severity::Critical,
+    };
+
+    let mut target_ids = payload.target_ids;
+    target_ids.sort();
+    target_ids.dedup();
+
+    Ok(NotificationChannelInput {
+        kind,
+        name,
+        webhook_url,
+        min_severity: min_severity.as_str().to_string(),
+        only_new: payload.only_new,
+        target_ids,
+        notify_on_failure: payload.notify_on_failure,
+        enabled: payload.enabled,
+    })
+}
+
+/// POST /api/notification-channels
+/// Add a Slack or Teams channel with its routing rules.
+#[post("/api/notification-channels")]
+pub async fn create_notification_channel(
+    pool: web::Data<DbPool>,
+    payload: web::Json<NotificationChannelPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner(), true)?;
+    let row = notification_channels_repo::create_channel(pool.get_ref(), ctx.org_id, ctx.user.id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(channel_json(&row)))
+}
+
+/// GET /api/notification-channels
+#[get("/api/notification-channels")]
+pub async fn list_notification_channels(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let rows = notification_channels_repo::list_channels(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let body: Vec<_> = rows.iter().map(channel_json).collect();
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// GET /api/notification-channels/{id}
+#[get("/api/notification-channels/{id}")]
+pub async fn get_notification_channel(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let row = notification_channels_repo::get_channel(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("notification channel not found".into()))?;
+    Ok(HttpResponse::Ok().json(channel_json(&row)))
+}
+
+/// PUT /api/notification-channels/{id}
+#[put("/api/notification-channels/{id}")]
+pub async fn update_notification_channel(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<NotificationChannelPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner(), false)?;
+    let id = path.into_inner();
+    if input.webhook_url.is_none() {
+        // The kept URL must suit the kind, which may have changed.
+        let current = notification_channels_repo::get_channel(pool.get_ref(), id, ctx.org_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound("notification channel not found".into()))?;
+        if !Url::parse(&current.webhook_url).is_ok_and(|u| notifications::is_webhook_url(&input.kind, &u)) {
+            return Err(invalid_webhook_url(&input.kind));
+        }
+    }
+    let row = notification_channels_repo::update_channel(pool.get_ref(), id, ctx.org_id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("notification channel not found".into()))?;
+    Ok(HttpResponse::Ok().json(channel_json(&row)))
+}
+
+/// DELETE /api/notification-channels/{id}
+#[delete("/api/notification-channels/{id}")]
+pub async fn delete_notification_channel(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let deleted = notification_channels_repo::delete_channel(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("notification channel not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/notification-channels/{id}/test
+/// Send a test message now, also to disabled channels. Answers 502 with the
+/// chat service's status code when it is not accepted.
+#[post("/api/notification-channels/{id}/test")]
+pub async fn test_notification_channel(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let row = notification_channels_repo::get_channel(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("notification channel not found".into()))?;
+    match notifications::send_test(pool.get_ref(), &row).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(json!({"status": "sent"}))),
+        Err(e) => Ok(HttpResponse::BadGateway().json(json!({"status": "failed", "error": e.to_string()}))),
+    }
+}
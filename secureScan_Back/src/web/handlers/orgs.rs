@@ -0,0 +1,416 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::{mfa_repo, oidc_repo};
+use crate::domain::orgs_repo::{self, Permission, Role};
+use crate::services::oidc::OidcClient;
+use crate::services::orgs::{self as orgs_svc, InvitationError};
+use crate::web::auth::{AuthUser, OrgContext};
+
+/// Request body for POST /api/orgs and PUT /api/orgs/{id}
+#[derive(Deserialize)]
+pub struct OrgPayload {
+    pub name: String,
+}
+
+/// Request body for PUT /api/orgs/{id}/mfa
+#[derive(Deserialize)]
+pub struct MfaPolicyPayload {
+    pub require_mfa: bool,
+}
+
+/// Request body for PUT /api/orgs/{id}/members/{user_id}
+#[derive(Deserialize)]
+pub struct RolePayload {
+    pub role: Role,
+}
+
+/// Request body for POST /api/orgs/{id}/invitations
+#[derive(Deserialize)]
+pub struct InvitationPayload {
+    pub email: String,
+    pub role: Role,
+}
+
+/// Request body for POST /api/orgs/{id}/sso/groups
+#[derive(Deserialize)]
+pub struct GroupMappingPayload {
+    pub group: String,
+    pub role: Role,
+}
+
+/// Request body for POST /api/invitations/accept
+#[derive(Deserialize)]
+pub struct AcceptPayload {
+    pub token: String,
+}
+
+fn org_name(raw: &str) -> Result<String, ApiError> {
+    let name = raw.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name is required".into()));
+    }
+    Ok(name.to_string())
+}
+
+/// Granting or taking away the owner role needs owner rights.
+fn require_role_change(ctx: &OrgContext, from: Option<Role>, to: Option<Role>) -> Result<(), ApiError> {
+    ctx.require(Permission::ManageMembers)?;
+    if from == Some(Role::Owner) || to == Some(Role::Owner) {
+        ctx.require(Permission::ManageOwners)?;
+    }
+    Ok(())
+}
+
+/// Refuse to demote or remove the last owner of an organization.
+async fn ensure_other_owner(pool: &DbPool, org_id: Uuid) -> Result<(), ApiError> {
+    let owners = orgs_repo::count_owners(pool, org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if owners <= 1 {
+        return Err(ApiError::BadRequest("an organization needs at least one owner".into()));
+    }
+    Ok(())
+}
+
+/// POST /api/orgs
+/// Create an organization with the caller as its owner.
+#[post("/api/orgs")]
+pub async fn create_org(
+    pool: web::Data<DbPool>,
+    payload: web::Json<OrgPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let name = org_name(&payload.name)?;
+    let org = orgs_repo::create_org(pool.get_ref(), &name, user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(json!({
+        "org_id": org.id,
+        "name": org.name,
+        "require_mfa": org.require_mfa,
+        "role": Role::Owner,
+        "joined_at": org.created_at,
+    })))
+}
+
+/// GET /api/orgs
+/// Organizations the caller belongs to, with their role in each. The first one
+/// is used when a request does not send `X-Org-Id`.
+#[get("/api/orgs")]
+pub async fn list_orgs(pool: web::Data<DbPool>, user: AuthUser) -> Result<impl Responder, ApiError> {
+    let rows = orgs_repo::list_memberships(pool.get_ref(), user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/orgs/{id}
+#[get("/api/orgs/{id}")]
+pub async fn get_org(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    let row = orgs_repo::get_membership(pool.get_ref(), path.into_inner(), user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("organization not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// PUT /api/orgs/{id}
+/// Rename an organization. Owners only.
+#[put("/api/orgs/{id}")]
+pub async fn rename_org(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<OrgPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    ctx.require(Permission::ManageOwners)?;
+    let name = org_name(&payload.name)?;
+    let row = orgs_repo::rename_org(pool.get_ref(), ctx.org_id, &name)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("organization not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// PUT /api/orgs/{id}/mfa
+/// Require two-factor authentication from every member. Members without it
+/// lose access until they enable it. Admins only, and they must have it themselves.
+#[put("/api/orgs/{id}/mfa")]
+pub async fn set_mfa_policy(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<MfaPolicyPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    ctx.require(Permission::ManageMembers)?;
+
+    if payload.require_mfa {
+        let enabled = mfa_repo::is_enabled(pool.get_ref(), ctx.user.id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if !enabled {
+            return Err(ApiError::BadRequest(
+                "enable two-factor authentication on your own account first".into(),
+            ));
+        }
+    }
+
+    let row = orgs_repo::set_require_mfa(pool.get_ref(), ctx.org_id, payload.require_mfa)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("organization not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// GET /api/orgs/{id}/members
+/// Includes whether each member has two-factor authentication enabled.
+#[get("/api/orgs/{id}/members")]
+pub async fn list_members(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    let rows = orgs_repo::list_members(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// PUT /api/orgs/{id}/members/{user_id}
+/// Change a member's role. Admins manage roles below owner; only owners grant
+/// or revoke the owner role.
+#[put("/api/orgs/{id}/members/{user_id}")]
+pub async fn update_member(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    payload: web::Json<RolePayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let (org_id, member_id) = path.into_inner();
+    let ctx = OrgContext::for_org(pool.get_ref(), user, org_id).await?;
+
+    let current = orgs_repo::get_membership(pool.get_ref(), ctx.org_id, member_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("member not found".into()))?
+        .role();
+    let role = payload.role;
+    require_role_change(&ctx, Some(current), Some(role))?;
+    if current == Role::Owner && role != Role::Owner {
+        ensure_other_owner(pool.get_ref(), ctx.org_id).await?;
+    }
+
+    orgs_repo::upsert_member(pool.get_ref(), ctx.org_id, member_id, role)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(json!({
+        "org_id": ctx.org_id,
+        "user_id": member_id,
+        "role": role,
+    })))
+}
+
+/// DELETE /api/orgs/{id}/members/{user_id}
+/// Remove a member. Any member may remove themselves to leave the organization.
+#[delete("/api/orgs/{id}/members/{user_id}")]
+pub async fn remove_member(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let (org_id, member_id) = path.into_inner();
+    // Leaving needs no permission, and stays possible without MFA in organizations requiring it.
+    let ctx = if member_id == user.id {
+        None
+    } else {
+        Some(OrgContext::for_org(pool.get_ref(), user, org_id).await?)
+    };
+
+    let current = orgs_repo::get_membership(pool.get_ref(), org_id, member_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("member not found".into()))?
+        .role();
+    if let Some(ctx) = &ctx {
+        require_role_change(ctx, Some(current), None)?;
+    }
+    if current == Role::Owner {
+        ensure_other_owner(pool.get_ref(), org_id).await?;
+    }
+
+    orgs_repo::remove_member(pool.get_ref(), org_id, member_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/orgs/{id}/invitations
+/// Invite someone by email. The invitation token is only returned in this response.
+#[post("/api/orgs/{id}/invitations")]
+pub async fn create_invitation(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<InvitationPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    require_role_change(&ctx, None, Some(payload.role))?;
+
+    let email = payload.email.trim().to_lowercase();
+    if !email.contains('@') {
+        return Err(ApiError::BadRequest("a valid email is required".into()));
+    }
+
+    let (row, token) = orgs_svc::invite(pool.get_ref(), ctx.org_id, &email, payload.role, ctx.user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let mut body = json!(row);
+    body["token"] = json!(token);
+    Ok(HttpResponse::Created().json(body))
+}
+
+/// GET /api/orgs/{id}/invitations
+/// Invitations that are neither accepted nor expired.
+#[get("/api/orgs/{id}/invitations")]
+pub async fn list_invitations(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    ctx.require(Permission::ManageMembers)?;
+    let rows = orgs_repo::list_pending_invitations(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// DELETE /api/orgs/{id}/invitations/{invitation_id}
+#[delete("/api/orgs/{id}/invitations/{invitation_id}")]
+pub async fn delete_invitation(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let (org_id, invitation_id) = path.into_inner();
+    let ctx = OrgContext::for_org(pool.get_ref(), user, org_id).await?;
+    ctx.require(Permission::ManageMembers)?;
+    let deleted = orgs_repo::delete_invitation(pool.get_ref(), ctx.org_id, invitation_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("invitation not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// GET /api/orgs/{id}/sso/groups
+/// IdP groups whose members get a role in the organization at SSO login.
+#[get("/api/orgs/{id}/sso/groups")]
+pub async fn list_group_mappings(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    ctx.require(Permission::ManageMembers)?;
+    let rows = oidc_repo::list_group_mappings(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// POST /api/orgs/{id}/sso/groups
+/// Map an IdP group to a role below owner, replacing an earlier mapping of the group.
+/// Takes effect at each member's next SSO login. Only organizations listed in
+/// `OIDC_GROUP_MAPPING_ORGS` may map groups.
+#[post("/api/orgs/{id}/sso/groups")]
+pub async fn upsert_group_mapping(
+    pool: web::Data<DbPool>,
+    oidc: Option<web::Data<OidcClient>>,
+    path: web::Path<Uuid>,
+    payload: web::Json<GroupMappingPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let ctx = OrgContext::for_org(pool.get_ref(), user, path.into_inner()).await?;
+    ctx.require(Permission::ManageMembers)?;
+    if !oidc.is_some_and(|o| o.config.allows_group_mappings(ctx.org_id)) {
+        return Err(ApiError::Forbidden(
+            "SSO group mappings are not enabled for this organization".into(),
+        ));
+    }
+
+    let group = payload.group.trim();
+    if group.is_empty() {
+        return Err(ApiError::BadRequest("group is required".into()));
+    }
+    if payload.role == Role::Owner {
+        return Err(ApiError::BadRequest("the owner role cannot be granted through SSO groups".into()));
+    }
+
+    let row = oidc_repo::upsert_group_mapping(pool.get_ref(), ctx.org_id, group, payload.role, ctx.user.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// DELETE /api/orgs/{id}/sso/groups/{mapping_id}
+/// Members keep their role until their next SSO login.
+#[delete("/api/orgs/{id}/sso/groups/{mapping_id}")]
+pub async fn delete_group_mapping(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let (org_id, mapping_id) = path.into_inner();
+    let ctx = OrgContext::for_org(pool.get_ref(), user, org_id).await?;
+    ctx.require(Permission::ManageMembers)?;
+    let deleted = oidc_repo::delete_group_mapping(pool.get_ref(), ctx.org_id, mapping_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("group mapping not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/invitations/accept
+/// Join the organization an invitation was sent for.
+#[post("/api/invitations/accept")]
+pub async fn accept_invitation(
+    pool: web::Data<DbPool>,
+    payload: web::Json<AcceptPayload>,
+    user: AuthUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_session()?;
+    let membership = orgs_svc::accept_invitation(pool.get_ref(), payload.token.trim(), user.id)
+        .await
+        .map_err(|e| match e {
+            InvitationError::Invalid => ApiError::NotFound(e.to_string()),
+            InvitationError::WrongRecipient | InvitationError::EmailUnverified => ApiError::Forbidden(e.to_string()),
+            InvitationError::Db(e) => ApiError::Internal(e.to_string()),
+        })?;
+    Ok(HttpResponse::Ok().json(membership))
+}
@@ -0,0 +1,272 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::orgs_repo::Permission;
+use crate::domain::outbound_webhooks_repo::{self, OutboundWebhookInput, OutboundWebhookRow};
+use crate::services::auth::scopes;
+use crate::services::egress::{self, EgressError};
+use crate::services::outbound_webhooks::{self, events, OutboundWebhookConfig};
+use crate::services::webhooks;
+use crate::web::auth::OrgContext;
+
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request body for POST /api/outbound-webhooks and PUT /api/outbound-webhooks/{id}
+#[derive(Deserialize)]
+pub struct OutboundWebhookPayload {
+    /// `http(s)` URL receiving the deliveries.
+    pub url: String,
+    pub description: Option<String>,
+    /// Subset of `scan.completed`, `scan.failed`, `finding.new`; omitted means all of them.
+    pub events: Option<Vec<String>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+fn webhook_json(row: &OutboundWebhookRow, secret: Option<&str>) -> serde_json::Value {
+    let mut body = json!(row);
+    if let Some(secret) = secret {
+        body["secret"] = json!(secret);
+    }
+    body
+}
+
+/// Refuse endpoints on the server's own networks up front; each delivery checks again.
+async fn check_public(url: &str) -> Result<(), ApiError> {
+    if OutboundWebhookConfig::global().allow_private {
+        return Ok(());
+    }
+    let url = Url::parse(url).map_err(|_| ApiError::BadRequest("url must be an http(s) URL".into()))?;
+    match egress::resolve_public(&url).await {
+        Err(EgressError::NonPublic(_)) => Err(ApiError::BadRequest(
+            "url must not point to a loopback, private or otherwise non-public address".into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn to_input(payload: OutboundWebhookPayload) -> Result<OutboundWebhookInput, ApiError> {
+    let url = payload.url.trim();
+    match Url::parse(url) {
+        Ok(u) if matches!(u.scheme(), "http" | "https") && u.host_str().is_some() => {}
+        _ => return Err(ApiError::BadRequest("url must be an http(s) URL".into())),
+    }
+
+    let mut selected: Vec<String> = match payload.events {
+        Some(list) => list.iter().map(|e| e.trim().to_ascii_lowercase()).collect(),
+        None => events::ALL.iter().map(|e| e.to_string()).collect(),
+    };
+    selected.sort();
+    selected.dedup();
+    if selected.is_empty() {
+        return Err(ApiError::BadRequest("events must not be empty".into()));
+    }
+    if let Some(unknown) = selected.iter().find(|e| !events::ALL.contains(&e.as_str())) {
+        return Err(ApiError::BadRequest(format!(
+            "unknown event {}; expected one of: {}",
+            unknown,
+            events::ALL.join(", ")
+        )));
+    }
+
+    Ok(OutboundWebhookInput {
+        url: url.to_string(),
+        description: payload
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty()),
+        events: selected,
+        enabled: payload.enabled,
+    })
+}
+
+async fn load_webhook(pool: &DbPool, id: Uuid, org_id: Uuid) -> Result<OutboundWebhookRow, ApiError> {
+    outbound_webhooks_repo::get_webhook(pool, id, org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("webhook not found".into()))
+}
+
+/// POST /api/outbound-webhooks
+/// Register an endpoint. Its signing secret is only returned here and when rotated.
+#[post("/api/outbound-webhooks")]
+pub async fn create_outbound_webhook(
+    pool: web::Data<DbPool>,
+    payload: web::Json<OutboundWebhookPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    check_public(&input.url).await?;
+    let secret = webhooks::generate_secret();
+    let row = outbound_webhooks_repo::create_webhook(pool.get_ref(), ctx.org_id, ctx.user.id, &input, &secret)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(webhook_json(&row, Some(&secret))))
+}
+
+/// GET /api/outbound-webhooks
+#[get("/api/outbound-webhooks")]
+pub async fn list_outbound_webhooks(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let rows = outbound_webhooks_repo::list_webhooks(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/outbound-webhooks/{id}
+#[get("/api/outbound-webhooks/{id}")]
+pub async fn get_outbound_webhook(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let row = load_webhook(pool.get_ref(), path.into_inner(), ctx.org_id).await?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// PUT /api/outbound-webhooks/{id}
+/// Replace an endpoint's URL, description, events and enabled flag.
+#[put("/api/outbound-webhooks/{id}")]
+pub async fn update_outbound_webhook(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<OutboundWebhookPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    check_public(&input.url).await?;
+    let row = outbound_webhooks_repo::update_webhook(pool.get_ref(), path.into_inner(), ctx.org_id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("webhook not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// POST /api/outbound-webhooks/{id}/rotate-secret
+/// Replace the signing secret; pending deliveries are signed with the new one.
+#[post("/api/outbound-webhooks/{id}/rotate-secret")]
+pub async fn rotate_outbound_webhook_secret(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let secret = webhooks::generate_secret();
+    let row = outbound_webhooks_repo::set_secret(pool.get_ref(), path.into_inner(), ctx.org_id, &secret)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("webhook not found".into()))?;
+    Ok(HttpResponse::Ok().json(webhook_json(&row, Some(&secret))))
+}
+
+/// DELETE /api/outbound-webhooks/{id}
+/// Removes the endpoint together with its delivery log.
+#[delete("/api/outbound-webhooks/{id}")]
+pub async fn delete_outbound_webhook(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let deleted = outbound_webhooks_repo::delete_webhook(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("webhook not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/outbound-webhooks/{id}/ping
+/// Queue a `ping` delivery to test the endpoint.
+#[post("/api/outbound-webhooks/{id}/ping")]
+pub async fn ping_outbound_webhook(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let webhook = load_webhook(pool.get_ref(), path.into_inner(), ctx.org_id).await?;
+    let delivery = outbound_webhooks::ping(pool.get_ref(), &webhook)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Accepted().json(delivery))
+}
+
+/// GET /api/outbound-webhooks/{id}/deliveries?limit=
+/// The endpoint's delivery log, newest first.
+#[get("/api/outbound-webhooks/{id}/deliveries")]
+pub async fn list_outbound_webhook_deliveries(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    query: web::Query<DeliveriesQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let webhook = load_webhook(pool.get_ref(), path.into_inner(), ctx.org_id).await?;
+    let limit = query.limit.unwrap_or(DEFAULT_DELIVERY_LIMIT).clamp(1, MAX_DELIVERY_LIMIT);
+    let rows = outbound_webhooks_repo::list_deliveries(pool.get_ref(), webhook.id, limit)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/outbound-webhooks/{id}/deliveries/{delivery_id}
+#[get("/api/outbound-webhooks/{id}/deliveries/{delivery_id}")]
+pub async fn get_outbound_webhook_delivery(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let (id, delivery_id) = path.into_inner();
+    let webhook = load_webhook(pool.get_ref(), id, ctx.org_id).await?;
+    let row = outbound_webhooks_repo::get_delivery(pool.get_ref(), delivery_id, webhook.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("delivery not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// POST /api/outbound-webhooks/{id}/deliveries/{delivery_id}/redeliver
+/// Queue a new delivery with the same payload and event ID.
+#[post("/api/outbound-webhooks/{id}/deliveries/{delivery_id}/redeliver")]
+pub async fn redeliver_outbound_webhook_delivery(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageIntegrations, scopes::INTEGRATIONS_WRITE)?;
+    let (id, delivery_id) = path.into_inner();
+    let webhook = load_webhook(pool.get_ref(), id, ctx.org_id).await?;
+    if !webhook.enabled {
+        return Err(ApiError::BadRequest("webhook is disabled".into()));
+    }
+    let original = outbound_webhooks_repo::get_delivery(pool.get_ref(), delivery_id, webhook.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("delivery not found".into()))?;
+    let delivery = outbound_webhooks::redeliver(pool.get_ref(), &original)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Accepted().json(delivery))
+}
@@ -0,0 +1,95 @@
+use actix_web::http::header::ContentDisposition;
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::findings::Severity;
+use crate::domain::orgs_repo::Permission;
+use crate::services::auth::scopes;
+use crate::services::reports::{html, junit, sarif, ScanReport};
+use crate::web::auth::OrgContext;
+use crate::web::handlers::scans::org_scan;
+
+/// The report of a scan of the caller's organization, or an error if it has no results yet.
+async fn load_report(pool: &DbPool, id: Uuid, ctx: &OrgContext) -> Result<ScanReport, ApiError> {
+    let scan = org_scan(pool, id, ctx).await?;
+    let scan_id = scan.id;
+    ScanReport::load(pool, scan)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::BadRequest(format!("scan {} has no results yet", scan_id)))
+}
+
+/// GET /api/scans/{id}/report.sarif
+/// SARIF 2.1.0 log of the scan's findings, e.g. for GitHub code scanning.
+#[get("/api/scans/{id}/report.sarif")]
+pub async fn sarif_report(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let report = load_report(pool.get_ref(), path.into_inner(), &ctx).await?;
+    let body = serde_json::to_vec_pretty(&sarif::render(&report)).map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok()
+        .content_type(sarif::CONTENT_TYPE)
+        .insert_header(ContentDisposition::attachment(report.file_name("sarif")))
+        .body(body))
+}
+
+/// GET /api/scans/{id}/report.html
+/// Self-contained HTML report (inline CSS and charts, no external assets) to share with customers.
+#[get("/api/scans/{id}/report.html")]
+pub async fn html_report(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let report = load_report(pool.get_ref(), path.into_inner(), &ctx).await?;
+    Ok(HttpResponse::Ok()
+        .content_type(html::CONTENT_TYPE)
+        .insert_header(("Content-Security-Policy", html::CONTENT_SECURITY_POLICY))
+        .insert_header(("X-Content-Type-Options", "nosniff"))
+        .insert_header(ContentDisposition::attachment(report.file_name("html")))
+        .body(html::render(&report, Utc::now())))
+}
+
+/// Query string for GET /api/scans/{id}/report.junit.xml
+#[derive(Deserialize)]
+pub struct JunitQuery {
+    /// Lowest severity that fails a check (default `high`).
+    pub fail_on: Option<String>,
+    /// Only fail on findings that are new since the target's previous completed scan.
+    #[serde(default)]
+    pub new_only: bool,
+}
+
+/// GET /api/scans/{id}/report.junit.xml
+/// JUnit XML for CI: one test case per scanner check, failing when the scan has open
+/// findings of it at or above `fail_on`.
+#[get("/api/scans/{id}/report.junit.xml")]
+pub async fn junit_report(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    query: web::Query<JunitQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let threshold = match query.fail_on.as_deref() {
+        Some(s) => Severity::parse(s)
+            .ok_or_else(|| ApiError::BadRequest("fail_on must be one of: critical, high, medium, low, info".into()))?,
+        None => Severity::High,
+    };
+    let report = load_report(pool.get_ref(), path.into_inner(), &ctx).await?;
+    let gate = junit::Gate::load(pool.get_ref(), &report, threshold, query.new_only)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok()
+        .content_type(junit::CONTENT_TYPE)
+        .insert_header(ContentDisposition::attachment(report.file_name("junit.xml")))
+        .body(junit::render(&report, &gate)))
+}
@@ -0,0 +1,211 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use serde::Deserialize;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::findings::Severity;
+use crate::domain::orgs_repo::Permission;
+use crate::domain::repository_targets_repo::{self, RepositoryTargetInput};
+use crate::scanner::ScanOptions;
+use crate::services::auth::scopes;
+use crate::services::webhooks::{self, events, provider};
+use crate::web::auth::OrgContext;
+
+fn default_provider() -> String {
+    provider::GITHUB.to_string()
+}
+
+fn default_events() -> Vec<String> {
+    events::DEFAULT.iter().map(|e| e.to_string()).collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_fail_on_severity() -> String {
+    Severity::High.as_str().to_string()
+}
+
+/// Request body for creating or replacing a repository target.
+#[derive(Deserialize)]
+pub struct RepositoryTargetPayload {
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// `owner/name`, as in the webhook's `repository.full_name`.
+    pub repository: String,
+    /// Scanned on pushes and pull requests, and on deployments without a usable URL.
+    pub target_url: Option<String>,
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+    /// Branch names; a trailing `*` matches any suffix. Empty means every branch.
+    #[serde(default)]
+    pub branches: Vec<String>,
+    /// Deployment environments; same matching as `branches`.
+    #[serde(default)]
+    pub environments: Vec<String>,
+    #[serde(default = "default_true")]
+    pub use_deployment_url: bool,
+    #[serde(default)]
+    pub options: ScanOptions,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Post each scan's result on the commit that triggered it.
+    #[serde(default = "default_true")]
+    pub report_to_provider: bool,
+    /// Open findings of this severity or worse fail the commit status.
+    #[serde(default = "default_fail_on_severity")]
+    pub fail_on_severity: String,
+}
+
+fn clean_list(values: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Validate a payload.
+fn to_input(payload: RepositoryTargetPayload) -> Result<RepositoryTargetInput, ApiError> {
+    if !provider::ALL.contains(&payload.provider.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "provider must be one of: {}",
+            provider::ALL.join(", ")
+        )));
+    }
+
+    let repository = payload.repository.trim().trim_matches('/').to_lowercase();
+    if !webhooks::is_valid_repository(&payload.provider, &repository) {
+        return Err(ApiError::BadRequest(
+            "repository must look like owner/name (letters, digits, '_', '.' and '-')".into(),
+        ));
+    }
+
+    let target_url = payload
+        .target_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(url) = &target_url {
+        Url::parse(url).map_err(|_| ApiError::BadRequest("invalid target_url".into()))?;
+    }
+
+    let events = clean_list(payload.events);
+    if events.is_empty() {
+        return Err(ApiError::BadRequest("events must not be empty".into()));
+    }
+    if let Some(unknown) = events.iter().find(|e| !events::ALL.contains(&e.as_str())) {
+        return Err(ApiError::BadRequest(format!(
+            "unknown event {}; expected one of: {}",
+            unknown,
+            events::ALL.join(", ")
+        )));
+    }
+
+    // Without a target_url only deployments that report their URL can be scanned.
+    let deployments_only = events.iter().all(|e| e == events::DEPLOYMENT_STATUS) && payload.use_deployment_url;
+    if target_url.is_none() && !deployments_only {
+        return Err(ApiError::BadRequest(
+            "target_url is required unless only deployment_status events with use_deployment_url are selected".into(),
+        ));
+    }
+
+    let fail_on_severity = Severity::parse(&payload.fail_on_severity).ok_or_else(|| {
+        ApiError::BadRequest("fail_on_severity must be one of: critical, high, medium, low, info".into())
+    })?;
+
+    let options = serde_json::to_value(&payload.options).map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(RepositoryTargetInput {
+        provider: payload.provider,
+        repository,
+        target_url,
+        events,
+        branches: clean_list(payload.branches),
+        environments: clean_list(payload.environments),
+        use_deployment_url: payload.use_deployment_url,
+        options,
+        enabled: payload.enabled,
+        report_to_provider: payload.report_to_provider,
+        fail_on_severity: fail_on_severity.as_str().to_string(),
+    })
+}
+
+/// POST /api/repository-targets
+#[post("/api/repository-targets")]
+pub async fn create_repository_target(
+    pool: web::Data<DbPool>,
+    payload: web::Json<RepositoryTargetPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    let row = repository_targets_repo::create_repository_target(pool.get_ref(), ctx.org_id, ctx.user.id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+/// GET /api/repository-targets
+#[get("/api/repository-targets")]
+pub async fn list_repository_targets(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let rows = repository_targets_repo::list_repository_targets(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/repository-targets/{id}
+#[get("/api/repository-targets/{id}")]
+pub async fn get_repository_target(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::INTEGRATIONS_READ)?;
+    let row = repository_targets_repo::get_repository_target(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("repository target not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// PUT /api/repository-targets/{id}
+#[put("/api/repository-targets/{id}")]
+pub async fn update_repository_target(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<RepositoryTargetPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::INTEGRATIONS_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    let row = repository_targets_repo::update_repository_target(pool.get_ref(), path.into_inner(), ctx.org_id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("repository target not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// DELETE /api/repository-targets/{id}
+#[delete("/api/repository-targets/{id}")]
+pub async fn delete_repository_target(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::INTEGRATIONS_WRITE)?;
+    let deleted = repository_targets_repo::delete_repository_target(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("repository target not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,4 +1,5 @@
 use actix_web::{get, post, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
@@ -6,32 +7,216 @@ use url::Url;
 
 use crate::db::DbPool;
 use crate::jobs::queue::{ScanJob, ScanQueue};
+use crate::domain::cursor::Cursor;
 use crate::domain::errors::ApiError;
+use crate::domain::findings;
 use crate::domain::scans_repo;
+use crate::domain::suppressions_repo::{self, SuppressionRow};
+use crate::scanner::ScanOptions;
+use crate::services::auth::scopes;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
 
+// تبدیل به شکلی که فرانت انتظار دارد: findings + completed_at
+pub fn list_row_json(row: scans_repo::ScanListRow, suppressions: &[SuppressionRow]) -> serde_json::Value {
+    let parsed = findings::parse_issues(row.findings.as_ref(), &row.url);
+    let annotated = findings::annotate(parsed, suppressions, row.target_id, Utc::now());
+    json!({
+        "id": row.id,
+        "url": row.url,
+        "status": row.status,
+        "created_at": row.created_at,
+        "completed_at": row.completed_at,
+        "schedule_id": row.schedule_id,
+        "target_id": row.target_id,
+        "owner_id": row.owner_id,
+        "org_id": row.org_id,
+        "counts": row.counts(),
+        "risk_score": row.risk_score,
+        "grade": row.grade,
+        "suppressed_count": annotated.suppressed_count,
+        "findings": annotated.findings
+    })
+}
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+const STATUSES: [&str; 4] = ["queued", "running", "completed", "failed"];
+
+/// Header carrying the cursor of the next page of GET /api/scans.
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+fn split_list(raw: &Option<String>) -> Vec<String> {
+    raw.as_deref()
+        .map(|s| {
+            s.split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Lightweight listing entry without findings bodies. Counts are the stored
+/// open (unsuppressed) counts.
+pub fn summary_row_json(row: scans_repo::ScanListRow) -> serde_json::Value {
+    json!({
+        "id": row.id,
+        "url": row.url,
+        "status": row.status,
+        "created_at": row.created_at,
+        "completed_at": row.completed_at,
+        "schedule_id": row.schedule_id,
+        "target_id": row.target_id,
+        "owner_id": row.owner_id,
+        "org_id": row.org_id,
+        "counts": row.counts(),
+        "risk_score": row.risk_score,
+        "grade": row.grade,
+    })
+}
+
+/// Query string for GET /api/scans
+#[derive(Deserialize)]
+pub struct ListScansQuery {
+    /// `created_at` (default), `risk_score`, `grade`, `critical_count` or `high_count`.
+    pub sort: Option<String>,
+    /// `asc` or `desc` (default).
+    pub order: Option<String>,
+    /// Comma-separated statuses, e.g. `queued,running`.
+    pub status: Option<String>,
+    /// Case-insensitive substring of the scanned URL.
+    pub url: Option<String>,
+    /// Only scans created at or after this instant (RFC 3339).
+    pub from: Option<DateTime<Utc>>,
+    /// Only scans created before this instant (RFC 3339).
+    pub to: Option<DateTime<Utc>>,
+    /// Comma-separated grades, e.g. `D,F`.
+    pub grade: Option<String>,
+    pub min_risk_score: Option<i32>,
+    pub max_risk_score: Option<i32>,
+    /// Value of the `X-Next-Cursor` header of the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// Omit findings bodies; counts, score and grade are still returned.
+    #[serde(default)]
+    pub summary: bool,
+    /// Only scans started by this member of the organization.
+    pub owner_id: Option<Uuid>,
+}
+
+impl ListScansQuery {
+    fn page_size(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    pub fn to_filter(&self, org_id: Uuid) -> Result<scans_repo::ScanListFilter, ApiError> {
+        let sort = match self.sort.as_deref() {
+            None => scans_repo::ScanSort::default(),
+            Some(s) => scans_repo::ScanSort::parse(s)
+                .ok_or_else(|| ApiError::BadRequest(format!("unsupported sort: {}", s)))?,
+        };
+        let descending = match self.order.as_deref() {
+            None | Some("desc") => true,
+            Some("asc") => false,
+            Some(o) => return Err(ApiError::BadRequest(format!("unsupported order: {}", o))),
+        };
+
+        let statuses: Vec<String> = split_list(&self.status)
+            .into_iter()
+            .map(|s| s.to_ascii_lowercase())
+            .collect();
+        if let Some(bad) = statuses.iter().find(|s| !STATUSES.contains(&s.as_str())) {
+            return Err(ApiError::BadRequest(format!("unknown status: {}", bad)));
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from >= to {
+                return Err(ApiError::BadRequest("from must be before to".into()));
+            }
+        }
+
+        let after = match self.cursor.as_deref() {
+            None | Some("") => None,
+            Some(raw) => {
+                let cursor = Cursor::decode(raw)
+                    .filter(|c| sort.is_valid_key(&c.key))
+                    .ok_or_else(|| ApiError::BadRequest("invalid cursor".into()))?;
+                Some(cursor)
+            }
+        };
+
+        let grades = split_list(&self.grade)
+            .into_iter()
+            .map(|g| g.to_ascii_uppercase())
+            .collect();
+
+        Ok(scans_repo::ScanListFilter {
+            org_id: Some(org_id),
+            owner_id: self.owner_id,
+            statuses,
+            url_contains: self.url.as_ref().map(|u| u.trim().to_string()),
+            created_from: self.from,
+            created_to: self.to,
+            grades,
+            min_risk_score: self.min_risk_score,
+            max_risk_score: self.max_risk_score,
+            sort,
+            descending,
+            after,
+            // One extra row tells whether another page exists.
+            limit: Some(self.page_size() + 1),
+            include_findings: !self.summary,
+            ..scans_repo::ScanListFilter::default()
+        })
+    }
+}
+
+/// GET /api/scans
+/// One page of the organization's scans as a JSON array; the cursor of the next page, if any, is
+/// returned in the `X-Next-Cursor` header.
 #[get("/api/scans")]
-pub async fn list_scans(pool: web::Data<DbPool>) -> Result<impl Responder, ApiError> {
-    let items = scans_repo::list_scans(pool.get_ref())
+pub async fn list_scans(
+    pool: web::Data<DbPool>,
+    query: web::Query<ListScansQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let filter = query.to_filter(ctx.org_id)?;
+    let mut items = scans_repo::list_scans(pool.get_ref(), &filter)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let page_size = query.page_size();
+    let next_cursor = if items.len() as i64 > page_size {
+        items.truncate(page_size as usize);
+        items
+            .last()
+            .map(|last| Cursor::new(filter.sort.cursor_key(last, filter.descending), last.id).encode())
+    } else {
+        None
+    };
+
+    let suppressions = suppressions_repo::list_active(pool.get_ref(), Some(ctx.org_id))
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-    // تبدیل به شکلی که فرانت انتظار دارد: findings + completed_at
     let response: Vec<_> = items
         .into_iter()
         .map(|row| {
-            let findings = row.issues.unwrap_or_else(|| json!([]));
-            json!({
-                "id": row.id,
-                "url": row.url,
-                "status": row.status,
-                "created_at": row.created_at,
-                "completed_at": row.completed_at,
-                "findings": findings
-            })
+            if query.summary {
+                summary_row_json(row)
+            } else {
+                list_row_json(row, &suppressions)
+            }
         })
         .collect();
 
-    Ok(HttpResponse::Ok().json(response))
+    let mut builder = HttpResponse::Ok();
+    if let Some(cursor) = next_cursor {
+        builder.insert_header((NEXT_CURSOR_HEADER, cursor));
+    }
+    Ok(builder.json(response))
 }
 
 // Minimal mock endpoint for early public Beta.
@@ -60,13 +245,16 @@ pub async fn mock_scan(payload: web::Json<serde_json::Value>) -> impl Responder
 pub async fn start_scan(
     pool: web::Data<DbPool>,
     queue: web::Data<ScanQueue>,
+    ctx: OrgContext,
     body: String,
 ) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::SCANS_WRITE)?;
     // Log raw request body for debugging.
     tracing::info!("start_scan raw body: {:?}", body);
 
     let trimmed = body.trim();
     let mut target_opt: Option<String> = None;
+    let mut options = ScanOptions::default();
 
     // 1) If body looks like JSON (starts with '{'), try to parse JSON first.
     if trimmed.starts_with('{') {
@@ -80,6 +268,10 @@ pub async fn start_scan(
                 {
                     target_opt = Some(s.to_string());
                 }
+                if let Some(opts) = value.get("options") {
+                    options = serde_json::from_value(opts.clone())
+                        .map_err(|e| ApiError::BadRequest(format!("invalid options: {}", e)))?;
+                }
             }
             Err(e) => {
                 // Invalid JSON: log and continue to fallback parsing.
@@ -114,15 +306,25 @@ pub async fn start_scan(
     Url::parse(target_str)
         .map_err(|_| ApiError::BadRequest("invalid target_url".into()))?;
 
+    let options_json = serde_json::to_value(&options)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
     // Insert into DB (status starts as 'queued').
-    let row = scans_repo::create_scan(pool.get_ref(), target_str)
+    let new_scan = scans_repo::NewScan {
+        url: target_str.to_string(),
+        options: options_json,
+        schedule_id: None,
+        owner_id: Some(ctx.user.id),
+        org_id: Some(ctx.org_id),
+    };
+    let row = scans_repo::create_scan(pool.get_ref(), &new_scan)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
     // Enqueue background worker job (non-blocking). If enqueue fails, log but still return success.
     match Url::parse(&row.url) {
         Ok(parsed) => {
-            let job = ScanJob { id: row.id, target: parsed };
+            let job = ScanJob { id: row.id, target: parsed, options };
             if let Err(e) = queue.enqueue(job).await {
                 tracing::error!("failed to enqueue scan job {}: {}", row.id, e);
             }
@@ -139,15 +341,25 @@ pub async fn start_scan(
     })))
 }
 
+/// A scan of the caller's organization. Scans of other organizations are reported as not found.
+pub async fn org_scan(pool: &DbPool, id: Uuid, ctx: &OrgContext) -> Result<scans_repo::ScanRow, ApiError> {
+    scans_repo::get_org_scan(pool, id, ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("scan not found".into()))
+}
+
 #[get("/api/scans/{id}")]
 pub async fn get_scan(
     pool: web::Data<DbPool>,
     path: web::Path<Uuid>,
+    ctx: OrgContext,
 ) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
     let id = path.into_inner();
 
     // First fetch the scan row from `scans` table.
-    let scan_row = scans_repo::get_scan(pool.get_ref(), id)
+    let scan_row = scans_repo::get_org_scan(pool.get_ref(), id, ctx.org_id)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
@@ -159,14 +371,29 @@ pub async fn get_scan(
 
         // If we have a result, include extra fields in the response.
         let body = if let Some(res) = latest_result {
+            let suppressions = suppressions_repo::list_active(pool.get_ref(), Some(ctx.org_id))
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            let parsed = scans_repo::load_findings(pool.get_ref(), scan.id)
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+            let annotated = findings::annotate(parsed, &suppressions, scan.target_id, Utc::now());
             json!({
+                "counts": scan.counts(),
+                "risk_score": scan.risk_score,
+                "grade": scan.grade,
                 "id": scan.id,
                 "url": scan.url,
                 "status": scan.status,
                 "created_at": scan.created_at,
+                "schedule_id": scan.schedule_id,
+                "target_id": scan.target_id,
+                "owner_id": scan.owner_id,
+                "org_id": scan.org_id,
                 "headers": res.headers,
                 "ssl_grade": res.ssl_grade,
-                "findings": res.issues.unwrap_or_else(|| json!([])),
+                "suppressed_count": annotated.suppressed_count,
+                "findings": annotated.findings,
                 "completed_at": res.completed_at,
             })
         } else {
@@ -176,8 +403,16 @@ pub async fn get_scan(
                 "url": scan.url,
                 "status": scan.status,
                 "created_at": scan.created_at,
+                "schedule_id": scan.schedule_id,
+                "target_id": scan.target_id,
+                "owner_id": scan.owner_id,
+                "org_id": scan.org_id,
                 "headers": null,
                 "ssl_grade": null,
+                "counts": scan.counts(),
+                "risk_score": scan.risk_score,
+                "grade": scan.grade,
+                "suppressed_count": 0,
                 "findings": [],
                 "completed_at": null,
             })
@@ -188,3 +423,65 @@ pub async fn get_scan(
         Err(ApiError::NotFound("scan not found".into()))
     }
 }
+
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    /// Baseline scan; defaults to the previous completed scan of the same target.
+    pub against: Option<Uuid>,
+}
+
+/// Findings of a scan's latest result, or an error if it has none yet.
+async fn load_findings(
+    pool: &DbPool,
+    scan: &scans_repo::ScanRow,
+) -> Result<Vec<findings::Finding>, ApiError> {
+    scans_repo::get_latest_scan_result(pool, scan.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::BadRequest(format!("scan {} has no results yet", scan.id)))?;
+    scans_repo::load_findings(pool, scan.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))
+}
+
+/// GET /api/scans/{id}/diff?against={other}
+/// Compare findings of a scan against another run, keyed by rule ID and location.
+#[get("/api/scans/{id}/diff")]
+pub async fn diff_scan(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    query: web::Query<DiffQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let scan = org_scan(pool.get_ref(), path.into_inner(), &ctx).await?;
+
+    let baseline = match query.against {
+        Some(other) => scans_repo::get_org_scan(pool.get_ref(), other, ctx.org_id)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound("baseline scan not found".into()))?,
+        None => scans_repo::get_previous_completed_scan(pool.get_ref(), &scan)
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::NotFound("no previous completed scan for this target".into()))?,
+    };
+
+    let current = load_findings(pool.get_ref(), &scan).await?;
+    let previous = load_findings(pool.get_ref(), &baseline).await?;
+    let diff = findings::diff(&current, &previous);
+
+    Ok(HttpResponse::Ok().json(json!({
+        "scan_id": scan.id,
+        "against": baseline.id,
+        "same_target": scan.target_id.is_some() && scan.target_id == baseline.target_id,
+        "summary": {
+            "new": diff.new.len(),
+            "fixed": diff.fixed.len(),
+            "unchanged": diff.unchanged.len(),
+        },
+        "new": diff.new,
+        "fixed": diff.fixed,
+        "unchanged": diff.unchanged,
+    })))
+}
@@ -0,0 +1,153 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::schedules_repo::{self, ScheduleInput};
+use crate::jobs::scheduler::{self, MISSED_RUN_POLICIES};
+use crate::scanner::ScanOptions;
+use crate::services::auth::scopes;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_missed_run_policy() -> String {
+    "run_once".to_string()
+}
+
+/// Request body for creating or replacing a schedule.
+#[derive(Deserialize)]
+pub struct SchedulePayload {
+    pub name: Option<String>,
+    pub target_url: String,
+    /// Cron expression, 5-field (`min hour dom mon dow`) or 6-field with seconds.
+    #[serde(alias = "cron")]
+    pub cron_expression: String,
+    /// IANA timezone name the cron expression is evaluated in.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default)]
+    pub options: ScanOptions,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// `skip` or `run_once`: what to do when runs were missed during downtime.
+    #[serde(default = "default_missed_run_policy")]
+    pub missed_run_policy: String,
+}
+
+/// Validate a payload and compute the first run time.
+fn to_input(payload: SchedulePayload) -> Result<ScheduleInput, ApiError> {
+    let target_url = payload.target_url.trim().to_string();
+    Url::parse(&target_url).map_err(|_| ApiError::BadRequest("invalid target_url".into()))?;
+
+    if !MISSED_RUN_POLICIES.contains(&payload.missed_run_policy.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "missed_run_policy must be one of: {}",
+            MISSED_RUN_POLICIES.join(", ")
+        )));
+    }
+
+    let next_run_at = scheduler::next_run_after(&payload.cron_expression, &payload.timezone, Utc::now())
+        .map_err(ApiError::BadRequest)?;
+    if next_run_at.is_none() {
+        return Err(ApiError::BadRequest("cron expression never fires".into()));
+    }
+
+    let options = serde_json::to_value(&payload.options).map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(ScheduleInput {
+        name: payload.name,
+        target_url,
+        cron_expression: payload.cron_expression.trim().to_string(),
+        timezone: payload.timezone,
+        options,
+        enabled: payload.enabled,
+        missed_run_policy: payload.missed_run_policy,
+        next_run_at: if payload.enabled { next_run_at } else { None },
+    })
+}
+
+/// POST /api/schedules
+#[post("/api/schedules")]
+pub async fn create_schedule(
+    pool: web::Data<DbPool>,
+    payload: web::Json<SchedulePayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::SCHEDULES_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    let row = schedules_repo::create_schedule(pool.get_ref(), ctx.org_id, ctx.user.id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+/// GET /api/schedules
+#[get("/api/schedules")]
+pub async fn list_schedules(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCHEDULES_READ)?;
+    let rows = schedules_repo::list_schedules(pool.get_ref(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/schedules/{id}
+#[get("/api/schedules/{id}")]
+pub async fn get_schedule(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCHEDULES_READ)?;
+    let row = schedules_repo::get_schedule(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("schedule not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// PUT /api/schedules/{id}
+/// Replaces the schedule definition and recomputes its next run.
+#[put("/api/schedules/{id}")]
+pub async fn update_schedule(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SchedulePayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::SCHEDULES_WRITE)?;
+    let input = to_input(payload.into_inner())?;
+    let row = schedules_repo::update_schedule(pool.get_ref(), path.into_inner(), ctx.org_id, &input)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("schedule not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// DELETE /api/schedules/{id}
+#[delete("/api/schedules/{id}")]
+pub async fn delete_schedule(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::RunScans, scopes::SCHEDULES_WRITE)?;
+    let deleted = schedules_repo::delete_schedule(pool.get_ref(), path.into_inner(), ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !deleted {
+        return Err(ApiError::NotFound("schedule not found".into()));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
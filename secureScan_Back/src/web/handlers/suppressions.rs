@@ -0,0 +1,148 @@
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::suppressions_repo::{self, NewSuppression, SuppressionState};
+use crate::domain::targets_repo;
+use crate::services::auth::scopes;
+use crate::services::risk;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
+
+fn default_location_pattern() -> String {
+    "*".to_string()
+}
+
+/// Request body for POST /api/suppressions
+#[derive(Deserialize)]
+pub struct SuppressionPayload {
+    pub rule_id: String,
+    /// Limit the suppression to one target; omit to apply it to every target.
+    pub target_id: Option<Uuid>,
+    /// Glob over the finding location (`*` any run, `?` one character).
+    #[serde(default = "default_location_pattern")]
+    pub location_pattern: String,
+    pub state: SuppressionState,
+    pub reason: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    pub include_expired: bool,
+}
+
+/// POST /api/suppressions
+/// Mark findings matching rule + target + location pattern as suppressed.
+/// The caller's email is recorded as the author.
+#[post("/api/suppressions")]
+pub async fn create_suppression(
+    pool: web::Data<DbPool>,
+    payload: web::Json<SuppressionPayload>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageSuppressions, scopes::SUPPRESSIONS_WRITE)?;
+    let payload = payload.into_inner();
+
+    let rule_id = payload.rule_id.trim().to_string();
+    if rule_id.is_empty() {
+        return Err(ApiError::BadRequest("rule_id is required".into()));
+    }
+    let reason = payload.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(ApiError::BadRequest("reason is required".into()));
+    }
+    if payload.expires_at.is_some_and(|e| e <= Utc::now()) {
+        return Err(ApiError::BadRequest("expires_at must be in the future".into()));
+    }
+    if let Some(target_id) = payload.target_id {
+        targets_repo::get_target(pool.get_ref(), target_id, Some(ctx.org_id))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::BadRequest("unknown target_id".into()))?;
+    }
+
+    let location_pattern = match payload.location_pattern.trim() {
+        "" => default_location_pattern(),
+        p => p.to_string(),
+    };
+
+    let row = suppressions_repo::create_suppression(
+        pool.get_ref(),
+        &NewSuppression {
+            org_id: ctx.org_id,
+            rule_id,
+            target_id: payload.target_id,
+            location_pattern,
+            state: payload.state,
+            reason,
+            author: ctx.user.email.clone(),
+            expires_at: payload.expires_at,
+        },
+    )
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    // Stored counts and scores exclude suppressed findings; refresh the affected scans.
+    risk::spawn_rescore(pool.get_ref().clone(), Some(ctx.org_id), row.target_id, false);
+
+    Ok(HttpResponse::Created().json(row))
+}
+
+/// GET /api/suppressions?include_expired=true
+#[get("/api/suppressions")]
+pub async fn list_suppressions(
+    pool: web::Data<DbPool>,
+    query: web::Query<ListQuery>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SUPPRESSIONS_READ)?;
+    let rows = suppressions_repo::list_suppressions(pool.get_ref(), Some(ctx.org_id), query.include_expired)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/suppressions/{id}
+#[get("/api/suppressions/{id}")]
+pub async fn get_suppression(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SUPPRESSIONS_READ)?;
+    let row = suppressions_repo::get_suppression(pool.get_ref(), path.into_inner())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|row| row.org_id == ctx.org_id)
+        .ok_or_else(|| ApiError::NotFound("suppression not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// DELETE /api/suppressions/{id}
+/// Lift a suppression; matching findings count again.
+#[delete("/api/suppressions/{id}")]
+pub async fn delete_suppression(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::ManageSuppressions, scopes::SUPPRESSIONS_WRITE)?;
+    let row = suppressions_repo::get_suppression(pool.get_ref(), path.into_inner())
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .filter(|row| row.org_id == ctx.org_id)
+        .ok_or_else(|| ApiError::NotFound("suppression not found".into()))?;
+
+    suppressions_repo::delete_suppression(pool.get_ref(), row.id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    risk::spawn_rescore(pool.get_ref().clone(), Some(ctx.org_id), row.target_id, false);
+
+    Ok(HttpResponse::NoContent().finish())
+}
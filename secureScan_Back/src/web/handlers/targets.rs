@@ -0,0 +1,68 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::domain::errors::ApiError;
+use crate::domain::{scans_repo, suppressions_repo, targets_repo};
+use crate::services::auth::scopes;
+use crate::domain::orgs_repo::Permission;
+use crate::web::auth::OrgContext;
+use crate::web::handlers::scans::list_row_json;
+
+/// GET /api/targets
+/// Targets the organization has scanned, with their scan count and last scan time.
+#[get("/api/targets")]
+pub async fn list_targets(pool: web::Data<DbPool>, ctx: OrgContext) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let rows = targets_repo::list_targets(pool.get_ref(), Some(ctx.org_id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// GET /api/targets/{id}
+#[get("/api/targets/{id}")]
+pub async fn get_target(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let row = targets_repo::get_target(pool.get_ref(), path.into_inner(), Some(ctx.org_id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("target not found".into()))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+/// GET /api/targets/{id}/scans
+/// Scan history of a target, newest first.
+#[get("/api/targets/{id}/scans")]
+pub async fn list_target_scans(
+    pool: web::Data<DbPool>,
+    path: web::Path<Uuid>,
+    ctx: OrgContext,
+) -> Result<impl Responder, ApiError> {
+    ctx.require_with_scope(Permission::View, scopes::SCANS_READ)?;
+    let target = targets_repo::get_target(pool.get_ref(), path.into_inner(), Some(ctx.org_id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("target not found".into()))?;
+
+    let scans = scans_repo::list_scans_for_target(pool.get_ref(), target.id, ctx.org_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let suppressions = suppressions_repo::list_active(pool.get_ref(), Some(ctx.org_id))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "target": target,
+        "scans": scans
+            .into_iter()
+            .map(|row| list_row_json(row, &suppressions))
+            .collect::<Vec<_>>(),
+    })))
+}
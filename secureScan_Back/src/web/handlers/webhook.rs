@@ -1,55 +1,142 @@
-use actix_web::{HttpRequest, HttpResponse};
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use actix_web::http::header::HeaderMap;
 use actix_web::web::Bytes;
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
-use uuid::Uuid;
-use hex::decode as hex_decode;
-use subtle::ConstantTimeEq;
+use serde_json::json;
 use std::env;
+use uuid::Uuid;
 
-type HmacSha256 = Hmac<Sha256>;
+use crate::db::DbPool;
+use crate::domain::webhook_deliveries_repo::DeliveryKey;
+use crate::domain::webhook_integrations_repo;
+use crate::jobs::queue::ScanQueue;
+use crate::services::webhooks::{self, provider, DeliveryOutcome, HeaderLookup, ParsedEvent};
 
-/// POST /api/ci/webhook/github
-/// Verifies X-Hub-Signature-256 header against raw body using GITHUB_WEBHOOK_SECRET
-pub async fn github_webhook(req: HttpRequest, body: Bytes) -> HttpResponse {
-    // Extract signature header
-    let sig_hdr = match req.headers().get("X-Hub-Signature-256") {
-        Some(v) => match v.to_str() {
-            Ok(s) => s.to_string(),
-            Err(_) => return HttpResponse::Unauthorized().finish(),
-        },
-        None => return HttpResponse::Unauthorized().finish(),
-    };
-
-    // Expected format: sha256=<hex>
-    let parts: Vec<&str> = sig_hdr.splitn(2, '=').collect();
-    if parts.len() != 2 || parts[0] != "sha256" {
+impl HeaderLookup for HeaderMap {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+/// Where a verified delivery may start scans.
+struct Receiver<'a> {
+    provider: &'a str,
+    secret: &'a [u8],
+    /// `None` for the legacy GitHub endpoint, which is not tied to an integration.
+    integration_id: Option<Uuid>,
+    /// Organization whose repository targets apply.
+    org_id: Uuid,
+}
+
+/// Verify, parse and deduplicate a delivery, then start the scans it maps to.
+async fn receive(
+    receiver: Receiver<'_>,
+    req: &HttpRequest,
+    body: &[u8],
+    pool: &DbPool,
+    queue: &ScanQueue,
+) -> HttpResponse {
+    if let Err(reason) = webhooks::verify(receiver.provider, receiver.secret, req.headers(), body) {
+        tracing::warn!("{} webhook rejected: {}", receiver.provider, reason);
         return HttpResponse::Unauthorized().finish();
     }
-    let signature_hex = parts[1];
-    let signature_bytes = match hex_decode(signature_hex) {
-        Ok(b) => b,
-        Err(_) => return HttpResponse::Unauthorized().finish(),
-    };
+    if let Some(id) = receiver.integration_id {
+        if let Err(e) = webhook_integrations_repo::touch_last_delivery(pool, id).await {
+            tracing::error!("could not record delivery time of integration {}: {}", id, e);
+        }
+    }
 
-    // Get secret
-    let secret = match env::var("GITHUB_WEBHOOK_SECRET") {
-        Ok(s) => s.into_bytes(),
-        Err(_) => return HttpResponse::InternalServerError().body("webhook secret not configured"),
+    let payload: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(v) => v,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("invalid JSON payload: {}", e)})),
+    };
+    let inbound = match webhooks::parse(receiver.provider, req.headers(), &payload) {
+        Ok(inbound) => inbound,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e})),
+    };
+    let event = match inbound.parsed {
+        ParsedEvent::Event(event) => event,
+        ParsedEvent::Ping => return HttpResponse::Ok().json(json!({"status": "pong"})),
+        ParsedEvent::Ignored(reason) => {
+            return HttpResponse::Ok().json(json!({"status": "ignored", "reason": reason}))
+        }
     };
 
-    // Compute HMAC
-    let mut mac = HmacSha256::new_from_slice(&secret).expect("HMAC can take key of any size");
-    mac.update(&body);
-    let result = mac.finalize();
-    let computed = result.into_bytes();
-
-    // Constant time compare
-    if computed.len() != signature_bytes.len() || computed.ct_eq(&signature_bytes).unwrap_u8() != 1u8 {
-        return HttpResponse::Unauthorized().finish();
+    let key = DeliveryKey {
+        provider: receiver.provider,
+        integration_id: receiver.integration_id,
+        delivery_id: &inbound.delivery_id,
+    };
+    match webhooks::handle_delivery(pool, queue, key, receiver.org_id, &event).await {
+        Ok(DeliveryOutcome::Duplicate { scan_ids }) => {
+            HttpResponse::Ok().json(json!({"status": "duplicate", "scan_ids": scan_ids}))
+        }
+        Ok(DeliveryOutcome::Handled { scans, skipped }) if scans.is_empty() => {
+            HttpResponse::Ok().json(json!({"status": "ignored", "reason": "no matching repository target", "skipped": skipped}))
+        }
+        Ok(DeliveryOutcome::Handled { scans, skipped }) => {
+            HttpResponse::Accepted().json(json!({"status": "queued", "scans": scans, "skipped": skipped}))
+        }
+        Err(e) => {
+            tracing::error!("{} webhook delivery {} failed: {}", receiver.provider, inbound.delivery_id, e);
+            HttpResponse::InternalServerError().json(json!({"error": "internal_error"}))
+        }
     }
+}
 
-    // At this point verification passed. Create job id and return.
-    let job_id = Uuid::new_v4();
-    HttpResponse::Ok().json(serde_json::json!({"job_id": job_id.to_string()}))
+/// POST /api/webhooks/{integration_id}
+/// Inbound webhook of an integration: GitHub (`X-Hub-Signature-256`), GitLab
+/// (`X-Gitlab-Token`) or the generic signed format, verified with the integration's
+/// secret. Only the integration's organization's repository targets apply.
+#[post("/api/webhooks/{integration_id}")]
+pub async fn integration_webhook(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: Bytes,
+    pool: web::Data<DbPool>,
+    queue: web::Data<ScanQueue>,
+) -> HttpResponse {
+    let integration = match webhook_integrations_repo::find_integration(pool.get_ref(), path.into_inner()).await {
+        Ok(Some(integration)) if integration.enabled => integration,
+        Ok(_) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("could not load webhook integration: {}", e);
+            return HttpResponse::InternalServerError().json(json!({"error": "internal_error"}));
+        }
+    };
+    let receiver = Receiver {
+        provider: &integration.provider,
+        secret: integration.secret.as_bytes(),
+        integration_id: Some(integration.id),
+        org_id: integration.org_id,
+    };
+    receive(receiver, &req, &body, pool.get_ref(), queue.get_ref()).await
+}
+
+/// POST /api/ci/webhook/github
+/// Verifies X-Hub-Signature-256 header against raw body using GITHUB_WEBHOOK_SECRET.
+/// Kept for existing setups: only the repository targets of the organization in
+/// GITHUB_WEBHOOK_ORG_ID apply. New webhooks should use an integration
+/// (`/api/webhooks/{integration_id}`).
+#[post("/api/ci/webhook/github")]
+pub async fn github_webhook(
+    req: HttpRequest,
+    body: Bytes,
+    pool: web::Data<DbPool>,
+    queue: web::Data<ScanQueue>,
+) -> HttpResponse {
+    let secret = match env::var("GITHUB_WEBHOOK_SECRET") {
+        Ok(s) if !s.is_empty() => s.into_bytes(),
+        _ => return HttpResponse::InternalServerError().body("webhook secret not configured"),
+    };
+    let Some(org_id) = webhooks::legacy_github_org() else {
+        tracing::warn!("legacy GitHub webhook refused: GITHUB_WEBHOOK_ORG_ID is not set");
+        return HttpResponse::InternalServerError().body("webhook organization not configured");
+    };
+    let receiver = Receiver {
+        provider: provider::GITHUB,
+        secret: &secret,
+        integration_id: None,
+        org_id,
+    };
+    receive(receiver, &req, &body, pool.get_ref(), queue.get_ref()).await
 }
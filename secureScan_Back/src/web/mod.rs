@@ -1 +1,2 @@
+pub mod auth;
 pub mod handlers;
@@ -1,6 +1,7 @@
 // Central API client (single source of truth for API base)
 import type { ApiScan, ScanItem } from "@/types/api";
 import { notify } from "@/components/Toast";
+import { authHeaders, refreshSession } from "@/lib/session";
 
 // Build-time canonical API base (Vite)
 const baked = (import.meta as any)?.env?.VITE_API_BASE?.toString?.() ?? "";
@@ -36,9 +37,10 @@ export function apiUrl(path: string): string {
 
 type HttpError = Error & { status?: number; bodyText?: string };
 
-async function http<T>(path: string, init?: RequestInit): Promise<T> {
+async function http<T>(path: string, init?: RequestInit, retried = false): Promise<T> {
   const headers: HeadersInit = {
     "Content-Type": "application/json",
+    ...authHeaders(),
     ...(init?.headers ?? {}),
   };
 
@@ -47,6 +49,11 @@ async function http<T>(path: string, init?: RequestInit): Promise<T> {
     headers,
   });
 
+  // Access tokens are short-lived: refresh once and retry.
+  if (res.status === 401 && !retried && (await refreshSession(API_BASE))) {
+    return http<T>(path, init, true);
+  }
+
   if (!res.ok) {
     const err: HttpError = new Error(`HTTP ${res.status} ${res.statusText}`);
     err.status = res.status;
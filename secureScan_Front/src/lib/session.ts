@@ -0,0 +1,50 @@
+// Session tokens issued by /api/auth/login and /api/auth/refresh.
+const ACCESS_KEY = "securascan.access_token";
+const REFRESH_KEY = "securascan.refresh_token";
+
+export type SessionTokens = {
+  access_token: string;
+  refresh_token: string;
+};
+
+export function saveSession(tokens: SessionTokens) {
+  localStorage.setItem(ACCESS_KEY, tokens.access_token);
+  localStorage.setItem(REFRESH_KEY, tokens.refresh_token);
+}
+
+export function clearSession() {
+  localStorage.removeItem(ACCESS_KEY);
+  localStorage.removeItem(REFRESH_KEY);
+}
+
+export function getAccessToken(): string | null {
+  return localStorage.getItem(ACCESS_KEY);
+}
+
+export function authHeaders(): Record<string, string> {
+  const token = getAccessToken();
+  return token ? { Authorization: `Bearer ${token}` } : {};
+}
+
+// Exchange the stored refresh token for a new pair. Returns false (and clears
+// the session) when the refresh token is missing, expired or revoked.
+export async function refreshSession(apiBase: string): Promise<boolean> {
+  const refresh_token = localStorage.getItem(REFRESH_KEY);
+  if (!refresh_token) return false;
+
+  try {
+    const res = await fetch(`${apiBase}/api/auth/refresh`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ refresh_token }),
+    });
+    if (!res.ok) {
+      clearSession();
+      return false;
+    }
+    saveSession(await res.json());
+    return true;
+  } catch {
+    return false;
+  }
+}
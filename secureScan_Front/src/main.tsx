@@ -19,6 +19,10 @@ import { ToastProvider } from "@/components/Toast";
 // Auth pages (login & register)
 import LoginPage from "./pages/LoginPage";
 import RegisterPage from "./pages/RegisterPage";
+import VerifyEmailPage from "./pages/VerifyEmailPage";
+import ForgotPasswordPage from "./pages/ForgotPasswordPage";
+import ResetPasswordPage from "./pages/ResetPasswordPage";
+import SsoCallbackPage from "./pages/SsoCallbackPage";
 
 const router = createBrowserRouter([
   // Main app layout (navbar, tabs, ...)
@@ -47,6 +51,26 @@ const router = createBrowserRouter([
     path: "/register",
     element: <RegisterPage />,
   },
+
+  // Links sent by email
+  {
+    path: "/verify-email",
+    element: <VerifyEmailPage />,
+  },
+  {
+    path: "/forgot-password",
+    element: <ForgotPasswordPage />,
+  },
+  {
+    path: "/reset-password",
+    element: <ResetPasswordPage />,
+  },
+
+  // Return from single sign-on
+  {
+    path: "/sso/callback",
+    element: <SsoCallbackPage />,
+  },
 ]);
 
 ReactDOM.createRoot(document.getElementById("root")!).render(
@@ -0,0 +1,76 @@
+// src/pages/ForgotPasswordPage.tsx
+import { FormEvent, useState } from "react";
+import { Link } from "react-router-dom";
+
+const API_BASE = import.meta.env.VITE_API_BASE ?? "http://localhost:8080";
+
+export default function ForgotPasswordPage() {
+  const [email, setEmail] = useState("");
+  const [sent, setSent] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  async function handleSubmit(e: FormEvent) {
+    e.preventDefault();
+    setError(null);
+    setLoading(true);
+
+    try {
+      const res = await fetch(`${API_BASE}/api/auth/password-reset`, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ email }),
+      });
+      if (!res.ok) throw new Error("Could not send reset link");
+      setSent(true);
+    } catch (err: any) {
+      setError(err.message || "Unexpected error");
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
+      <div className="w-full max-w-md bg-zinc-950/80 border border-zinc-700 rounded-2xl p-8 shadow-lg">
+        <h1 className="text-2xl font-semibold mb-6 text-center">Reset your password</h1>
+
+        {sent ? (
+          <p className="text-sm text-zinc-300 text-center">
+            If an account exists for {email}, a reset link is on its way.
+          </p>
+        ) : (
+          <form onSubmit={handleSubmit} className="space-y-4">
+            <div>
+              <label className="block mb-1 text-sm text-zinc-300">Email</label>
+              <input
+                type="email"
+                className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+                value={email}
+                onChange={(e) => setEmail(e.target.value)}
+                required
+                autoComplete="email"
+              />
+            </div>
+
+            {error && <p className="text-sm text-red-400">{error}</p>}
+
+            <button
+              type="submit"
+              disabled={loading}
+              className="w-full mt-2 rounded-lg bg-violet-600 hover:bg-violet-500 disabled:opacity-60 py-2 text-sm font-medium"
+            >
+              {loading ? "Sending..." : "Send reset link"}
+            </button>
+          </form>
+        )}
+
+        <p className="mt-4 text-xs text-zinc-400 text-center">
+          <Link to="/login" className="text-violet-400 hover:underline">
+            Back to login
+          </Link>
+        </p>
+      </div>
+    </div>
+  );
+}
@@ -1,10 +1,20 @@
 import React, { useEffect, useMemo, useRef, useState } from "react";
 import { API_BASE } from "@/lib/api";
+import { authHeaders } from "@/lib/session";
+import SeverityBadge from "@/components/SeverityBadge";
 import type { ApiScan } from "@/types/api";
 
 type LogLevel = "info" | "ok" | "warn" | "error";
 type ScanPhase = "idle" | "starting" | "queued" | "running" | "completed" | "failed";
 
+type LiveFinding = {
+  rule_id?: string;
+  severity?: string;
+  title?: string;
+  location?: string;
+  fingerprint?: string;
+};
+
 type LiveLog = {
   at: string;
   level: LogLevel;
@@ -99,7 +109,7 @@ export default function LiveScan() {
   async function fetchScanById(id: string): Promise<ApiScan | null> {
     try {
       const res = await fetch(`${apiBaseText}/api/scans/${id}`, {
-        headers: { "Content-Type": "application/json" },
+        headers: { "Content-Type": "application/json", ...authHeaders() },
       });
       if (!res.ok) return null;
       return (await res.json()) as ApiScan;
@@ -172,7 +182,7 @@ export default function LiveScan() {
 
       const res = await fetch(`${apiBaseText}/api/scans`, {
         method: "POST",
-        headers: { "Content-Type": "application/json" },
+        headers: { "Content-Type": "application/json", ...authHeaders() },
         body: JSON.stringify(payload),
       });
 
@@ -216,9 +226,11 @@ export default function LiveScan() {
     el.scrollTop = el.scrollHeight;
   }, [logs, paused]);
 
-  const findings: string[] = useMemo(() => {
+  const findings: LiveFinding[] = useMemo(() => {
     const f = (current as any)?.findings;
-    return Array.isArray(f) ? f : [];
+    if (!Array.isArray(f)) return [];
+    // older results may still hold plain strings
+    return f.map((x: any) => (typeof x === "string" ? { title: x } : (x as LiveFinding)));
   }, [current]);
 
   const risk = useMemo(() => riskFromFindingsCount(findings.length), [findings.length]);
@@ -427,10 +439,16 @@ export default function LiveScan() {
               <ul className="mt-4 space-y-2">
                 {findings.map((f, idx) => (
                   <li
-                    key={`${idx}-${f}`}
+                    key={f.fingerprint || `${idx}-${f.title}`}
                     className="rounded-xl border border-gray-800 bg-gray-900/40 px-4 py-3 text-sm text-gray-100"
                   >
-                    {f}
+                    <div className="flex items-center justify-between gap-3">
+                      <span>{f.title || f.rule_id || "Finding"}</span>
+                      <SeverityBadge severity={f.severity || "info"} />
+                    </div>
+                    {f.location ? (
+                      <div className="mt-1 break-all text-xs text-gray-400">{f.location}</div>
+                    ) : null}
                   </li>
                 ))}
               </ul>
@@ -1,19 +1,96 @@
 // src/pages/LoginPage.tsx
-import { FormEvent, useState } from "react";
-import { useNavigate, Link } from "react-router-dom";
+import { FormEvent, useEffect, useState } from "react";
+import { useNavigate, useLocation, useSearchParams, Link } from "react-router-dom";
+import { saveSession } from "@/lib/session";
 
 const API_BASE = import.meta.env.VITE_API_BASE ?? "http://localhost:8080";
 
+const SSO_ERRORS: Record<string, string> = {
+  access_denied: "Single sign-on was cancelled or refused.",
+  email_not_verified: "Your identity provider has not verified your email address.",
+  missing_email: "Your identity provider did not share an email address.",
+  not_provisioned: "No SecuraScan account is linked to this identity.",
+  invalid_state: "The sign-in link expired. Please try again.",
+};
+
+function throttledMessage(data: any): string {
+  const secs = Number(data?.retry_after) || 0;
+  const wait = secs >= 120 ? `${Math.ceil(secs / 60)} minutes` : `${secs} seconds`;
+  return `Too many failed attempts. Try again in ${wait}.`;
+}
+
 export default function LoginPage() {
   const [email, setEmail] = useState("");
   const [password, setPassword] = useState("");
   const [error, setError] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
+  const [unverified, setUnverified] = useState(false);
+  const location = useLocation();
+  const [params] = useSearchParams();
+  // Set when the account has two-factor authentication: the password (or SSO) step passed.
+  const [mfaToken, setMfaToken] = useState<string | null>(location.state?.mfaToken ?? null);
+  const [code, setCode] = useState("");
+  const [sso, setSso] = useState<{ enabled: boolean; provider_name: string | null } | null>(null);
   const navigate = useNavigate();
+  const redirectTo: string = location.state?.redirectTo ?? "/dashboard";
+
+  useEffect(() => {
+    const ssoError = params.get("sso_error");
+    if (ssoError) setError(SSO_ERRORS[ssoError] ?? "Single sign-on failed.");
+
+    fetch(`${API_BASE}/api/auth/oidc`)
+      .then((res) => (res.ok ? res.json() : null))
+      .then(setSso)
+      .catch(() => setSso(null));
+  }, [params]);
+
+  async function handleMfaSubmit(e: FormEvent) {
+    e.preventDefault();
+    setError(null);
+    setLoading(true);
+
+    try {
+      const res = await fetch(`${API_BASE}/api/auth/login/mfa`, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ mfa_token: mfaToken, code }),
+      });
+
+      if (!res.ok) {
+        const data = await res.json().catch(() => null);
+        if (data?.error === "invalid_token") {
+          setMfaToken(null);
+          throw new Error("The sign-in took too long. Enter your password again.");
+        }
+        if (data?.error === "too_many_attempts") {
+          throw new Error(throttledMessage(data));
+        }
+        throw new Error("Invalid code");
+      }
+
+      saveSession(await res.json());
+      navigate(redirectTo);
+    } catch (err: any) {
+      setError(err.message || "Unexpected error");
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  async function resendVerification() {
+    await fetch(`${API_BASE}/api/auth/verify-email/resend`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ email }),
+    }).catch(() => null);
+    setUnverified(false);
+    setError("A new confirmation link was sent if the account needs one.");
+  }
 
   async function handleSubmit(e: FormEvent) {
     e.preventDefault();
     setError(null);
+    setUnverified(false);
     setLoading(true);
 
     try {
@@ -26,10 +103,23 @@ export default function LoginPage() {
 
       if (!res.ok) {
         const data = await res.json().catch(() => null);
+        if (data?.error === "email_not_verified") {
+          setUnverified(true);
+          throw new Error("Confirm your email address before logging in.");
+        }
+        if (data?.error === "too_many_attempts") {
+          throw new Error(throttledMessage(data));
+        }
         throw new Error(data?.message || "Login failed");
       }
 
-      // Later we can update the user context/state
+      const data = await res.json();
+      if (data.mfa_required) {
+        setCode("");
+        setMfaToken(data.mfa_token);
+        return;
+      }
+      saveSession(data);
       navigate("/dashboard");
     } catch (err: any) {
       setError(err.message || "Unexpected error");
@@ -38,6 +128,45 @@ export default function LoginPage() {
     }
   }
 
+  if (mfaToken) {
+    return (
+      <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
+        <div className="w-full max-w-md bg-zinc-950/80 border border-zinc-700 rounded-2xl p-8 shadow-lg">
+          <h1 className="text-2xl font-semibold mb-6 text-center">
+            Two-factor authentication
+          </h1>
+
+          <form onSubmit={handleMfaSubmit} className="space-y-4">
+            <div>
+              <label className="block mb-1 text-sm text-zinc-300">
+                Code from your authenticator app, or a recovery code
+              </label>
+              <input
+                type="text"
+                className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+                value={code}
+                onChange={(e) => setCode(e.target.value)}
+                required
+                autoFocus
+                autoComplete="one-time-code"
+              />
+            </div>
+
+            {error && <p className="text-sm text-red-400">{error}</p>}
+
+            <button
+              type="submit"
+              disabled={loading}
+              className="w-full mt-2 rounded-lg bg-violet-600 hover:bg-violet-500 disabled:opacity-60 py-2 text-sm font-medium"
+            >
+              {loading ? "Verifying..." : "Verify"}
+            </button>
+          </form>
+        </div>
+      </div>
+    );
+  }
+
   return (
     <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
       <div className="w-full max-w-md bg-zinc-950/80 border border-zinc-700 rounded-2xl p-8 shadow-lg">
@@ -71,6 +200,15 @@ export default function LoginPage() {
           </div>
 
           {error && <p className="text-sm text-red-400">{error}</p>}
+          {unverified && (
+            <button
+              type="button"
+              onClick={resendVerification}
+              className="text-xs text-violet-400 hover:underline"
+            >
+              Resend confirmation email
+            </button>
+          )}
 
           <button
             type="submit"
@@ -81,7 +219,22 @@ export default function LoginPage() {
           </button>
         </form>
 
+        {sso?.enabled && (
+          <a
+            href={`${API_BASE}/api/auth/oidc/login?redirect_to=${encodeURIComponent(redirectTo)}`}
+            className="block w-full mt-3 rounded-lg border border-zinc-600 hover:bg-zinc-800 py-2 text-sm font-medium text-center"
+          >
+            Sign in with {sso.provider_name ?? "SSO"}
+          </a>
+        )}
+
         <p className="mt-4 text-xs text-zinc-400 text-center">
+          <Link to="/forgot-password" className="text-violet-400 hover:underline">
+            Forgot your password?
+          </Link>
+        </p>
+
+        <p className="mt-2 text-xs text-zinc-400 text-center">
           Don&apos;t have an account?{" "}
           <Link to="/register" className="text-violet-400 hover:underline">
             Create one
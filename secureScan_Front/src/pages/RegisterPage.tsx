@@ -1,6 +1,6 @@
 // src/pages/RegisterPage.tsx
 import { FormEvent, useState } from "react";
-import { useNavigate, Link } from "react-router-dom";
+import { Link } from "react-router-dom";
 
 const API_BASE = import.meta.env.VITE_API_BASE ?? "http://localhost:8080";
 
@@ -10,7 +10,7 @@ export default function RegisterPage() {
   const [confirm, setConfirm] = useState("");
   const [error, setError] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
-  const navigate = useNavigate();
+  const [registered, setRegistered] = useState(false);
 
   async function handleSubmit(e: FormEvent) {
     e.preventDefault();
@@ -31,10 +31,13 @@ export default function RegisterPage() {
 
       if (!res.ok) {
         const data = await res.json().catch(() => null);
+        if (data?.error === "weak_password") {
+          throw new Error(`Password ${data.violations.map((v: any) => v.message).join("; ")}.`);
+        }
         throw new Error(data?.message || "Registration failed");
       }
 
-      navigate("/login");
+      setRegistered(true);
     } catch (err: any) {
       setError(err.message || "Unexpected error");
     } finally {
@@ -49,54 +52,60 @@ export default function RegisterPage() {
           Create your SecuraScan account
         </h1>
 
-        <form onSubmit={handleSubmit} className="space-y-4">
-          <div>
-            <label className="block mb-1 text-sm text-zinc-300">Email</label>
-            <input
-              type="email"
-              className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
-              value={email}
-              onChange={(e) => setEmail(e.target.value)}
-              required
-              autoComplete="email"
-            />
-          </div>
+        {registered ? (
+          <p className="text-sm text-zinc-300 text-center">
+            We sent an email to {email}. Open the confirmation link in it to activate your account, then log in.
+          </p>
+        ) : (
+          <form onSubmit={handleSubmit} className="space-y-4">
+            <div>
+              <label className="block mb-1 text-sm text-zinc-300">Email</label>
+              <input
+                type="email"
+                className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+                value={email}
+                onChange={(e) => setEmail(e.target.value)}
+                required
+                autoComplete="email"
+              />
+            </div>
 
-          <div>
-            <label className="block mb-1 text-sm text-zinc-300">Password</label>
-            <input
-              type="password"
-              className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
-              value={password}
-              onChange={(e) => setPassword(e.target.value)}
-              required
-              autoComplete="new-password"
-            />
-          </div>
+            <div>
+              <label className="block mb-1 text-sm text-zinc-300">Password</label>
+              <input
+                type="password"
+                className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                required
+                autoComplete="new-password"
+              />
+            </div>
 
-          <div>
-            <label className="block mb-1 text-sm text-zinc-300">
-              Confirm password
-            </label>
-            <input
-              type="password"
-              className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
-              value={confirm}
-              onChange={(e) => setConfirm(e.target.value)}
-              required
-            />
-          </div>
+            <div>
+              <label className="block mb-1 text-sm text-zinc-300">
+                Confirm password
+              </label>
+              <input
+                type="password"
+                className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+                value={confirm}
+                onChange={(e) => setConfirm(e.target.value)}
+                required
+              />
+            </div>
 
-          {error && <p className="text-sm text-red-400">{error}</p>}
+            {error && <p className="text-sm text-red-400">{error}</p>}
 
-          <button
-            type="submit"
-            disabled={loading}
-            className="w-full mt-2 rounded-lg bg-emerald-600 hover:bg-emerald-500 disabled:opacity-60 py-2 text-sm font-medium"
-          >
-            {loading ? "Creating account..." : "Sign up"}
-          </button>
-        </form>
+            <button
+              type="submit"
+              disabled={loading}
+              className="w-full mt-2 rounded-lg bg-emerald-600 hover:bg-emerald-500 disabled:opacity-60 py-2 text-sm font-medium"
+            >
+              {loading ? "Creating account..." : "Sign up"}
+            </button>
+          </form>
+        )}
 
         <p className="mt-4 text-xs text-zinc-400 text-center">
           Already have an account?{" "}
@@ -0,0 +1,95 @@
+// src/pages/ResetPasswordPage.tsx
+import { FormEvent, useState } from "react";
+import { Link, useNavigate, useSearchParams } from "react-router-dom";
+
+const API_BASE = import.meta.env.VITE_API_BASE ?? "http://localhost:8080";
+
+export default function ResetPasswordPage() {
+  const [params] = useSearchParams();
+  const token = params.get("token") ?? "";
+  const [password, setPassword] = useState("");
+  const [confirm, setConfirm] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [loading, setLoading] = useState(false);
+  const navigate = useNavigate();
+
+  async function handleSubmit(e: FormEvent) {
+    e.preventDefault();
+    setError(null);
+
+    if (password !== confirm) {
+      setError("Passwords do not match");
+      return;
+    }
+
+    setLoading(true);
+    try {
+      const res = await fetch(`${API_BASE}/api/auth/password-reset/confirm`, {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ token, password }),
+      });
+      if (!res.ok) {
+        const data = await res.json().catch(() => null);
+        if (data?.error === "weak_password") {
+          throw new Error(`Password ${data.violations.map((v: any) => v.message).join("; ")}.`);
+        }
+        throw new Error("This link is invalid or has expired");
+      }
+      navigate("/login");
+    } catch (err: any) {
+      setError(err.message || "Unexpected error");
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
+      <div className="w-full max-w-md bg-zinc-950/80 border border-zinc-700 rounded-2xl p-8 shadow-lg">
+        <h1 className="text-2xl font-semibold mb-6 text-center">Choose a new password</h1>
+
+        <form onSubmit={handleSubmit} className="space-y-4">
+          <div>
+            <label className="block mb-1 text-sm text-zinc-300">New password</label>
+            <input
+              type="password"
+              className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+              value={password}
+              onChange={(e) => setPassword(e.target.value)}
+              required
+              autoComplete="new-password"
+            />
+          </div>
+
+          <div>
+            <label className="block mb-1 text-sm text-zinc-300">Confirm password</label>
+            <input
+              type="password"
+              className="w-full rounded-lg bg-zinc-800 border border-zinc-600 px-3 py-2 text-sm focus:outline-none focus:ring focus:ring-violet-500"
+              value={confirm}
+              onChange={(e) => setConfirm(e.target.value)}
+              required
+            />
+          </div>
+
+          {error && <p className="text-sm text-red-400">{error}</p>}
+
+          <button
+            type="submit"
+            disabled={loading || !token}
+            className="w-full mt-2 rounded-lg bg-violet-600 hover:bg-violet-500 disabled:opacity-60 py-2 text-sm font-medium"
+          >
+            {loading ? "Saving..." : "Set password"}
+          </button>
+        </form>
+
+        <p className="mt-4 text-xs text-zinc-400 text-center">
+          <Link to="/forgot-password" className="text-violet-400 hover:underline">
+            Request a new link
+          </Link>
+        </p>
+      </div>
+    </div>
+  );
+}
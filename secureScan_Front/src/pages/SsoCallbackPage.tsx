@@ -0,0 +1,36 @@
+// src/pages/SsoCallbackPage.tsx
+import { useEffect } from "react";
+import { useNavigate } from "react-router-dom";
+import { saveSession } from "@/lib/session";
+
+// The backend redirects here after single sign-on with the session in the URL
+// fragment (never sent to a server), or with an MFA challenge to finish on /login.
+export default function SsoCallbackPage() {
+  const navigate = useNavigate();
+
+  useEffect(() => {
+    const params = new URLSearchParams(window.location.hash.slice(1));
+    const redirectTo = params.get("redirect_to") || "/dashboard";
+    // Drop the tokens from the address bar and history.
+    window.history.replaceState(null, "", window.location.pathname);
+
+    const accessToken = params.get("access_token");
+    const refreshToken = params.get("refresh_token");
+    const mfaToken = params.get("mfa_token");
+
+    if (accessToken && refreshToken) {
+      saveSession({ access_token: accessToken, refresh_token: refreshToken });
+      navigate(redirectTo, { replace: true });
+    } else if (mfaToken) {
+      navigate("/login", { replace: true, state: { mfaToken, redirectTo } });
+    } else {
+      navigate("/login?sso_error=invalid_state", { replace: true });
+    }
+  }, [navigate]);
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
+      <p className="text-sm text-zinc-300">Signing you in…</p>
+    </div>
+  );
+}
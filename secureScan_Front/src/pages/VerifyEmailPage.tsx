@@ -0,0 +1,52 @@
+// src/pages/VerifyEmailPage.tsx
+import { useEffect, useRef, useState } from "react";
+import { Link, useSearchParams } from "react-router-dom";
+
+const API_BASE = import.meta.env.VITE_API_BASE ?? "http://localhost:8080";
+
+type State = "verifying" | "done" | "failed";
+
+export default function VerifyEmailPage() {
+  const [params] = useSearchParams();
+  const token = params.get("token") ?? "";
+  const [state, setState] = useState<State>(token ? "verifying" : "failed");
+  // StrictMode runs effects twice in development; the token only works once.
+  const sent = useRef(false);
+
+  useEffect(() => {
+    if (!token || sent.current) return;
+    sent.current = true;
+
+    fetch(`${API_BASE}/api/auth/verify-email`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ token }),
+    })
+      .then((res) => setState(res.ok ? "done" : "failed"))
+      .catch(() => setState("failed"));
+  }, [token]);
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-zinc-900 text-white">
+      <div className="w-full max-w-md bg-zinc-950/80 border border-zinc-700 rounded-2xl p-8 shadow-lg text-center">
+        <h1 className="text-2xl font-semibold mb-6">Email verification</h1>
+
+        {state === "verifying" && <p className="text-sm text-zinc-300">Verifying your email…</p>}
+        {state === "done" && (
+          <p className="text-sm text-zinc-300">Your email is confirmed. You can log in now.</p>
+        )}
+        {state === "failed" && (
+          <p className="text-sm text-red-400">
+            This link is invalid or has expired. Log in to request a new one.
+          </p>
+        )}
+
+        <p className="mt-6 text-xs text-zinc-400">
+          <Link to="/login" className="text-violet-400 hover:underline">
+            Go to login
+          </Link>
+        </p>
+      </div>
+    </div>
+  );
+}